@@ -5,15 +5,24 @@ use std::{
 };
 pub use wallet::Settings as SettingsRust;
 use wallet_core::c::{
-    symmetric_cipher_decrypt, vote, wallet_convert, wallet_convert_ignored,
-    wallet_convert_transactions_get, wallet_convert_transactions_size, wallet_delete_conversion,
-    wallet_delete_error, wallet_delete_proposal, wallet_delete_settings, wallet_delete_wallet,
-    wallet_id, wallet_import_keys, wallet_recover, wallet_retrieve_funds, wallet_set_state,
+    block_update_confirmed_get, block_update_confirmed_size, block_update_expired_get,
+    block_update_expired_size, mnemonics_entry_completions_get, mnemonics_entry_completions_size,
+    mnemonics_entry_is_valid, mnemonics_entry_words_get, mnemonics_entry_words_size,
+    reconciliation_confirmed_get, reconciliation_confirmed_size, reconciliation_dropped_get,
+    reconciliation_dropped_size, reconciliation_status, symmetric_cipher_decrypt, vote,
+    wallet_apply_block, wallet_check_mnemonics, wallet_convert, wallet_convert_ignored,
+    wallet_convert_transactions_get, wallet_convert_transactions_size, wallet_delegate,
+    wallet_delete_block_update, wallet_delete_conversion, wallet_delete_error,
+    wallet_delete_mnemonics_entry, wallet_delete_proposal, wallet_delete_reconciliation,
+    wallet_delete_settings, wallet_delete_wallet, wallet_export, wallet_id, wallet_import,
+    wallet_import_keys, wallet_reconcile_state, wallet_recover, wallet_recovered_schemes,
+    wallet_retrieve_funds, wallet_send, wallet_set_confirmation_settings, wallet_set_state,
     wallet_total_value, wallet_vote_cast,
 };
 use wallet_core::{
-    Conversion as ConversionRust, Error as ErrorRust, Proposal as ProposalRust,
-    Wallet as WalletRust,
+    BlockUpdate as BlockUpdateRust, Conversion as ConversionRust, Error as ErrorRust,
+    MnemonicsEntry as MnemonicsEntryRust, Proposal as ProposalRust,
+    Reconciliation as ReconciliationRust, SyncStatus as SyncStatusRust, Wallet as WalletRust,
 };
 
 #[repr(C)]
@@ -25,12 +34,21 @@ pub struct Conversion {}
 #[repr(C)]
 pub struct Proposal {}
 #[repr(C)]
+pub struct BlockUpdate {}
+#[repr(C)]
+pub struct Reconciliation {}
+#[repr(C)]
+pub struct MnemonicsEntry {}
+#[repr(C)]
 pub struct Error {}
 
 pub type WalletPtr = *mut Wallet;
 pub type SettingsPtr = *mut Settings;
 pub type ConversionPtr = *mut Conversion;
 pub type ProposalPtr = *mut Proposal;
+pub type BlockUpdatePtr = *mut BlockUpdate;
+pub type ReconciliationPtr = *mut Reconciliation;
+pub type MnemonicsEntryPtr = *mut MnemonicsEntry;
 pub type ErrorPtr = *mut Error;
 
 /// Payload type for voting
@@ -47,6 +65,30 @@ impl From<PayloadType> for PayloadTypeRust {
     }
 }
 
+/// how the node's spending counter of the account compares to the local
+/// confirmed spending counter
+#[repr(u8)]
+pub enum SyncStatus {
+    /// the node and the local state use the same spending counter
+    Consistent = 0,
+    /// the node already applied transactions of the account that are not
+    /// confirmed in the local state
+    Ahead = 1,
+    /// the node did not apply yet transactions of the account that are
+    /// confirmed in the local state
+    Behind = 2,
+}
+
+impl From<SyncStatusRust> for SyncStatus {
+    fn from(status: SyncStatusRust) -> Self {
+        match status {
+            SyncStatusRust::Consistent => SyncStatus::Consistent,
+            SyncStatusRust::Ahead => SyncStatus::Ahead,
+            SyncStatusRust::Behind => SyncStatus::Behind,
+        }
+    }
+}
+
 /// retrieve a wallet from the given mnemonics, password and protocol magic
 ///
 /// this function will work for all yoroi, daedalus and other wallets
@@ -59,9 +101,12 @@ impl From<PayloadType> for PayloadTypeRust {
 ///
 /// # parameters
 ///
-/// * mnemonics: a null terminated utf8 string (already normalized NFKD) in english;
+/// * mnemonics: a null terminated utf8 string (normalized to NFKD by the function) in
+///   any of the supported languages, the language is detected from the words;
 /// * password: pointer to the password (in bytes, can be UTF8 string or a bytes of anything);
-///   this value is optional and passing a null pointer will result in no password;
+///   this value is optional and passing a null pointer will result in no password.
+///   The Daedalus wallet is not recovered when a password is given (see
+///   `iohk_jormungandr_wallet_recovered_schemes`);
 /// * password_length: the length of the password;
 /// * wallet_out: a pointer to a pointer. The recovered wallet will be allocated on this pointer;
 ///
@@ -357,6 +402,40 @@ pub unsafe extern "C" fn iohk_jormungandr_wallet_convert_ignored(
     r.into_c_api() as ErrorPtr
 }
 
+/// the legacy wallet schemes recovered along with the account, the funds
+/// of the schemes that were not recovered are not retrieved
+///
+/// `daedalus_out` is set to `true` if the Daedalus wallet was recovered,
+/// it is not recovered when the mnemonics are protected with a password.
+/// `yoroi_out` is set to `true` if the Yoroi wallet was recovered.
+///
+/// # Errors
+///
+/// * this function may fail if the wallet pointer is null;
+/// * this function may fail if `daedalus_out` or `yoroi_out` is null;
+///
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_recovered_schemes(
+    wallet: WalletPtr,
+    daedalus_out: *mut bool,
+    yoroi_out: *mut bool,
+) -> ErrorPtr {
+    let r = wallet_recovered_schemes(wallet as *mut WalletRust, daedalus_out, yoroi_out);
+
+    r.into_c_api() as ErrorPtr
+}
+
 /// get the total value in the wallet
 ///
 /// make sure to call `retrieve_funds` prior to calling this function
@@ -401,6 +480,9 @@ pub unsafe extern "C" fn iohk_jormungandr_wallet_total_value(
 /// transactions otherwise future transactions may fail to be accepted by any
 /// nodes of the blockchain because of invalid signature state.
 ///
+/// The pending transactions that are still valid with the given counter are
+/// kept, the ones using an older spending counter are dropped.
+///
 /// # Errors
 ///
 /// * this function may fail if the wallet pointer is null;
@@ -427,158 +509,138 @@ pub extern "C" fn iohk_jormungandr_wallet_set_state(
     r.into_c_api() as ErrorPtr
 }
 
-/// build the proposal object
+/// synchronise the wallet account state with the value and the spending
+/// counter of the account as reported by the node
+///
+/// Same as `iohk_jormungandr_wallet_set_state` but the reconciliation report
+/// is returned in `reconciliation_out`, see
+/// `iohk_jormungandr_wallet_reconciliation_status`,
+/// `iohk_jormungandr_wallet_reconciliation_dropped_get` and
+/// `iohk_jormungandr_wallet_reconciliation_confirmed_get`.
 ///
 /// # Errors
 ///
-/// This function may fail if:
+/// * this function may fail if the wallet pointer or `reconciliation_out` is null;
 ///
-/// * `proposal_out` is null.
-/// * `num_choices` is out of the allowed range.
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
 ///
 /// # Safety
 ///
-/// This function dereference raw pointers. Even though the function checks if
-/// the pointers are null. Mind not to put random values in or you may see
-/// unexpected behaviors.
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+/// Don't forget to remove `reconciliation_out` with
+/// `iohk_jormungandr_wallet_delete_reconciliation`.
 #[no_mangle]
-pub unsafe extern "C" fn iohk_jormungandr_vote_proposal_new_public(
-    vote_plan_id: *const u8,
-    index: u8,
-    num_choices: u8,
-    proposal_out: *mut ProposalPtr,
+pub unsafe extern "C" fn iohk_jormungandr_wallet_reconcile_state(
+    wallet: WalletPtr,
+    value: u64,
+    counter: u32,
+    reconciliation_out: *mut ReconciliationPtr,
 ) -> ErrorPtr {
-    let r = vote::proposal_new(
-        vote_plan_id,
-        index,
-        num_choices,
-        vote::ProposalPublic,
-        proposal_out as *mut *mut ProposalRust,
+    let r = wallet_reconcile_state(
+        wallet as *mut WalletRust,
+        value,
+        counter,
+        reconciliation_out as *mut *mut ReconciliationRust,
     );
 
     r.into_c_api() as ErrorPtr
 }
 
-/// build the proposal object
-///
-/// * `vote_encryption_key`: the vote encryption key argument is expected
-/// to be a 65 bytes array
+/// how the node's spending counter of the account compared to the local
+/// confirmed spending counter
 ///
 /// # Errors
 ///
-/// This function may fail if:
-///
-/// * `proposal_out` is null.
-/// * `num_choices` is out of the allowed range.
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
 ///
 /// # Safety
 ///
-/// This function dereference raw pointers. Even though the function checks if
-/// the pointers are null. Mind not to put random values in or you may see
-/// unexpected behaviors.
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
 #[no_mangle]
-pub unsafe extern "C" fn iohk_jormungandr_vote_proposal_new_private(
-    vote_plan_id: *const u8,
-    index: u8,
-    num_choices: u8,
-    vote_encryption_key: *const u8,
-    proposal_out: *mut ProposalPtr,
+pub unsafe extern "C" fn iohk_jormungandr_wallet_reconciliation_status(
+    reconciliation: ReconciliationPtr,
+    status_out: *mut SyncStatus,
 ) -> ErrorPtr {
-    let r = vote::proposal_new(
-        vote_plan_id,
-        index,
-        num_choices,
-        vote::ProposalPrivate(vote_encryption_key),
-        proposal_out as *mut *mut ProposalRust,
+    let status_out = if let Some(status_out) = status_out.as_mut() {
+        status_out
+    } else {
+        let r: wallet_core::Result = ErrorRust::invalid_input("status_out").into();
+        return r.into_c_api() as ErrorPtr;
+    };
+
+    let mut status = SyncStatusRust::Consistent;
+    let r = reconciliation_status(
+        reconciliation as *mut ReconciliationRust,
+        &mut status as *mut SyncStatusRust,
     );
 
+    if r.is_ok() {
+        *status_out = status.into();
+    }
+
     r.into_c_api() as ErrorPtr
 }
 
-/// build the vote cast transaction
-///
-/// # Errors
-///
-/// This function may fail upon receiving a null pointer or a `choice` value
-/// that does not fall within the range specified in `proposal`.
+/// get the number of pending transactions dropped by the reconciliation
 ///
 /// # Safety
 ///
-/// This function dereference raw pointers. Even though the function checks if
-/// the pointers are null. Mind not to put random values in or you may see
-/// unexpected behaviors.
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
 ///
-/// Don't forget to remove `transaction_out` with
-/// `iohk_jormungandr_waller_delete_buffer`.
 #[no_mangle]
-pub unsafe extern "C" fn iohk_jormungandr_wallet_vote_cast(
-    wallet: WalletPtr,
-    settings: SettingsPtr,
-    proposal: ProposalPtr,
-    choice: u8,
-    transaction_out: *mut *const u8,
-    len_out: *mut usize,
-) -> ErrorPtr {
-    let r = wallet_vote_cast(
-        wallet as *mut WalletRust,
-        settings as *mut SettingsRust,
-        proposal as *mut ProposalRust,
-        choice,
-        transaction_out,
-        len_out,
-    );
-
-    r.into_c_api() as ErrorPtr
+pub unsafe extern "C" fn iohk_jormungandr_wallet_reconciliation_dropped_size(
+    reconciliation: ReconciliationPtr,
+) -> usize {
+    reconciliation_dropped_size(reconciliation as *mut ReconciliationRust)
 }
 
-/// decrypt payload of the wallet transfer protocol
+/// retrieve the index-nth transaction id (32 bytes) dropped by the
+/// reconciliation, starting from 0 and finishing at `size-1` where size is
+/// retrieved from `iohk_jormungandr_wallet_reconciliation_dropped_size`.
 ///
-/// Parameters
+/// the memory returned is not owned and should not be kept for longer
+/// than potential call to `iohk_jormungandr_wallet_delete_reconciliation`
 ///
-/// password: byte buffer with the encryption password
-/// password_length: length of the password buffer
-/// ciphertext: byte buffer with the encryption password
-/// ciphertext_length: length of the password buffer
-/// plaintext_out: used to return a pointer to a byte buffer with the decrypted text
-/// plaintext_out_length: used to return the length of decrypted text
+/// # Errors
 ///
-/// The returned buffer is in the heap, so make sure to call the delete_buffer function
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
 ///
 /// # Safety
 ///
-/// This function dereference raw pointers. Even though the function checks if
-/// the pointers are null. Mind not to put random values in or you may see
-/// unexpected behaviors.
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
 #[no_mangle]
-pub unsafe extern "C" fn iohk_jormungandr_symmetric_cipher_decrypt(
-    password: *const u8,
-    password_length: usize,
-    ciphertext: *const u8,
-    ciphertext_length: usize,
-    plaintext_out: *mut *const u8,
-    plaintext_out_length: *mut usize,
+pub unsafe extern "C" fn iohk_jormungandr_wallet_reconciliation_dropped_get(
+    reconciliation: ReconciliationPtr,
+    index: usize,
+    id_out: *mut *const u8,
 ) -> ErrorPtr {
-    let r = symmetric_cipher_decrypt(
-        password,
-        password_length,
-        ciphertext,
-        ciphertext_length,
-        plaintext_out,
-        plaintext_out_length,
-    );
+    let r = reconciliation_dropped_get(reconciliation as *mut ReconciliationRust, index, id_out);
 
     r.into_c_api() as ErrorPtr
 }
 
-/// Get a string describing the error, this will return an allocated
-/// null terminated string describing the error.
-///
-/// If the given error is a `NULL` pointer, the string is and always
-/// is `"success"`. This string still need to be deleted with the
-/// `iohk_jormungandr_wallet_delete_string` function.
-///
-/// This function returns an allocated null terminated pointer. Don't
-/// forget to free the memory with: `iohk_jormungandr_wallet_delete_string`.
+/// get the number of transactions confirmed locally that are part of the
+/// node's state
 ///
 /// # Safety
 ///
@@ -587,25 +649,26 @@ pub unsafe extern "C" fn iohk_jormungandr_symmetric_cipher_decrypt(
 /// in or you may see unexpected behaviors
 ///
 #[no_mangle]
-pub unsafe extern "C" fn iohk_jormungandr_wallet_error_to_string(error: ErrorPtr) -> *mut c_char {
-    if let Some(error) = (error as *mut ErrorRust).as_ref() {
-        CString::new(error.to_string()).unwrap().into_raw()
-    } else {
-        CString::new(b"success".to_vec()).unwrap().into_raw()
-    }
+pub unsafe extern "C" fn iohk_jormungandr_wallet_reconciliation_confirmed_size(
+    reconciliation: ReconciliationPtr,
+) -> usize {
+    reconciliation_confirmed_size(reconciliation as *mut ReconciliationRust)
 }
 
-/// Get a string describing the error, this will return an allocated
-/// null terminated string providing extra details regarding the source
-/// of the error.
+/// retrieve the index-nth transaction id (32 bytes) confirmed locally that
+/// is part of the node's state, starting from 0 and finishing at `size-1`
+/// where size is retrieved from
+/// `iohk_jormungandr_wallet_reconciliation_confirmed_size`.
 ///
-/// If the given error is a `NULL` pointer, the string is and always
-/// is `"success"`. If no details are available the function will return
-/// `"no more details"`. This string still need to be deleted with the
-/// `iohk_jormungandr_wallet_delete_string` function.
+/// the memory returned is not owned and should not be kept for longer
+/// than potential call to `iohk_jormungandr_wallet_delete_reconciliation`
 ///
-/// This function returns an allocated null terminated pointer. Don't
-/// forget to free the memory with: `iohk_jormungandr_wallet_delete_string`.
+/// # Errors
+///
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
 ///
 /// # Safety
 ///
@@ -614,21 +677,51 @@ pub unsafe extern "C" fn iohk_jormungandr_wallet_error_to_string(error: ErrorPtr
 /// in or you may see unexpected behaviors
 ///
 #[no_mangle]
-pub unsafe extern "C" fn iohk_jormungandr_wallet_error_details(error: ErrorPtr) -> *mut c_char {
-    if let Some(error) = (error as *mut ErrorRust).as_ref() {
-        if let Some(details) = error.details() {
-            CString::new(details.to_string()).unwrap().into_raw()
-        } else {
-            CString::new(b"no more details".to_vec())
-                .unwrap()
-                .into_raw()
-        }
-    } else {
-        CString::new(b"success".to_vec()).unwrap().into_raw()
-    }
+pub unsafe extern "C" fn iohk_jormungandr_wallet_reconciliation_confirmed_get(
+    reconciliation: ReconciliationPtr,
+    index: usize,
+    id_out: *mut *const u8,
+) -> ErrorPtr {
+    let r = reconciliation_confirmed_get(reconciliation as *mut ReconciliationRust, index, id_out);
+
+    r.into_c_api() as ErrorPtr
 }
 
-/// Delete a null terminated string that was allocated by this library
+/// check the mnemonics entered so far by the user
+///
+/// The words followed by a space (or an ideographic space) are complete and
+/// may be given by their prefix, the last word, if not followed by a space,
+/// is the word being typed. The completions of a single word are then the
+/// completions of the entry of its prefix, and the word a prefix stands for
+/// is the only word of the entry of the prefix followed by a space.
+///
+/// The entry is returned in `entry_out`, see
+/// `iohk_jormungandr_wallet_mnemonics_entry_words_get`,
+/// `iohk_jormungandr_wallet_mnemonics_entry_completions_get` and
+/// `iohk_jormungandr_wallet_mnemonics_entry_is_valid`.
+///
+/// # parameters
+///
+/// * language: a null terminated string, the name of the dictionary
+///   (`english`, `japanese`, `chinese-simplified`, `chinese-traditional`,
+///   `french`, `italian`, `korean` or `spanish`);
+/// * mnemonics: a null terminated utf8 string (normalized to NFKD by the function);
+/// * entry_out: a pointer to a pointer. The entry will be allocated on this pointer;
+///
+/// # errors
+///
+/// The function may fail if:
+///
+/// * the language is not known;
+/// * a complete word does not resolve to exactly one word of the dictionary;
+/// * no word completes the word being typed;
+/// * there are more words than the longest mnemonics (24 words);
+/// * the `entry_out` is null pointer
+///
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
 ///
 /// # Safety
 ///
@@ -636,16 +729,759 @@ pub unsafe extern "C" fn iohk_jormungandr_wallet_error_details(error: ErrorPtr)
 /// the function checks if the pointers are null. Mind not to put random values
 /// in or you may see unexpected behaviors
 ///
+/// Don't forget to remove `entry_out` with
+/// `iohk_jormungandr_wallet_delete_mnemonics_entry`.
 #[no_mangle]
-pub unsafe extern "C" fn iohk_jormungandr_wallet_delete_string(ptr: *mut c_char) {
-    if !ptr.is_null() {
-        let cstring = CString::from_raw(ptr);
-        std::mem::drop(cstring)
-    }
+pub unsafe extern "C" fn iohk_jormungandr_wallet_check_mnemonics(
+    language: *const c_char,
+    mnemonics: *const c_char,
+    entry_out: *mut MnemonicsEntryPtr,
+) -> ErrorPtr {
+    let language = CStr::from_ptr(language).to_string_lossy();
+    let mnemonics = CStr::from_ptr(mnemonics).to_string_lossy();
+
+    let r = wallet_check_mnemonics(
+        &language,
+        &mnemonics,
+        entry_out as *mut *mut MnemonicsEntryRust,
+    );
+
+    r.into_c_api() as ErrorPtr
 }
 
-/// Delete a binary buffer that was returned by this library alongside with its
-/// length.
+/// get whether the complete words of the entry are mnemonics of a supported
+/// length with a valid checksum, they can then be used with
+/// `iohk_jormungandr_wallet_recover`
+///
+/// # Errors
+///
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_mnemonics_entry_is_valid(
+    entry: MnemonicsEntryPtr,
+    valid_out: *mut bool,
+) -> ErrorPtr {
+    let r = mnemonics_entry_is_valid(entry as *mut MnemonicsEntryRust, valid_out);
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// get the number of complete words of the entry
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_mnemonics_entry_words_size(
+    entry: MnemonicsEntryPtr,
+) -> usize {
+    mnemonics_entry_words_size(entry as *mut MnemonicsEntryRust)
+}
+
+/// retrieve the index-nth complete word of the entry, resolved from its
+/// prefix, starting from 0 and finishing at `size-1` where size is retrieved
+/// from `iohk_jormungandr_wallet_mnemonics_entry_words_size`.
+///
+/// The word is a utf8 string of `word_length_out` bytes, it is not null
+/// terminated. The memory returned is not owned and must not be freed.
+///
+/// # Errors
+///
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_mnemonics_entry_words_get(
+    entry: MnemonicsEntryPtr,
+    index: usize,
+    word_out: *mut *const u8,
+    word_length_out: *mut usize,
+) -> ErrorPtr {
+    let r = mnemonics_entry_words_get(
+        entry as *mut MnemonicsEntryRust,
+        index,
+        word_out,
+        word_length_out,
+    );
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// get the number of words completing the word being typed, 0 if no word
+/// is being typed
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_mnemonics_entry_completions_size(
+    entry: MnemonicsEntryPtr,
+) -> usize {
+    mnemonics_entry_completions_size(entry as *mut MnemonicsEntryRust)
+}
+
+/// retrieve the index-nth word completing the word being typed, in the
+/// order of the dictionary, starting from 0 and finishing at `size-1` where
+/// size is retrieved from
+/// `iohk_jormungandr_wallet_mnemonics_entry_completions_size`.
+///
+/// The word is a utf8 string of `word_length_out` bytes, it is not null
+/// terminated. The memory returned is not owned and must not be freed.
+///
+/// # Errors
+///
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_mnemonics_entry_completions_get(
+    entry: MnemonicsEntryPtr,
+    index: usize,
+    word_out: *mut *const u8,
+    word_length_out: *mut usize,
+) -> ErrorPtr {
+    let r = mnemonics_entry_completions_get(
+        entry as *mut MnemonicsEntryRust,
+        index,
+        word_out,
+        word_length_out,
+    );
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// build the proposal object
+///
+/// # Errors
+///
+/// This function may fail if:
+///
+/// * `proposal_out` is null.
+/// * `num_choices` is out of the allowed range.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_vote_proposal_new_public(
+    vote_plan_id: *const u8,
+    index: u8,
+    num_choices: u8,
+    proposal_out: *mut ProposalPtr,
+) -> ErrorPtr {
+    let r = vote::proposal_new(
+        vote_plan_id,
+        index,
+        num_choices,
+        vote::ProposalPublic,
+        proposal_out as *mut *mut ProposalRust,
+    );
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// build the proposal object
+///
+/// * `vote_encryption_key`: the vote encryption key argument is expected
+/// to be a 65 bytes array
+///
+/// # Errors
+///
+/// This function may fail if:
+///
+/// * `proposal_out` is null.
+/// * `num_choices` is out of the allowed range.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_vote_proposal_new_private(
+    vote_plan_id: *const u8,
+    index: u8,
+    num_choices: u8,
+    vote_encryption_key: *const u8,
+    proposal_out: *mut ProposalPtr,
+) -> ErrorPtr {
+    let r = vote::proposal_new(
+        vote_plan_id,
+        index,
+        num_choices,
+        vote::ProposalPrivate(vote_encryption_key),
+        proposal_out as *mut *mut ProposalRust,
+    );
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// build the vote cast transaction
+///
+/// # Errors
+///
+/// This function may fail upon receiving a null pointer or a `choice` value
+/// that does not fall within the range specified in `proposal`.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+///
+/// Don't forget to remove `transaction_out` with
+/// `iohk_jormungandr_waller_delete_buffer`.
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_vote_cast(
+    wallet: WalletPtr,
+    settings: SettingsPtr,
+    proposal: ProposalPtr,
+    choice: u8,
+    transaction_out: *mut *const u8,
+    len_out: *mut usize,
+) -> ErrorPtr {
+    let r = wallet_vote_cast(
+        wallet as *mut WalletRust,
+        settings as *mut SettingsRust,
+        proposal as *mut ProposalRust,
+        choice,
+        transaction_out,
+        len_out,
+    );
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// build a transaction sending `value` to the given `address` from the
+/// wallet's account
+///
+/// The fees are paid by the account on top of the `value`. The returned
+/// transaction is added to the pending transactions of the wallet.
+///
+/// # Parameters
+///
+/// * `address`: the binary encoded address to send the funds to;
+/// * `address_length`: the length of the `address` buffer;
+/// * `value`: the amount of lovelace to send;
+///
+/// # Errors
+///
+/// This function may fail upon receiving a null pointer, an invalid address
+/// or if the account does not have enough funds to pay for the `value` and
+/// the fees.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+///
+/// Don't forget to remove `transaction_out` with
+/// `iohk_jormungandr_waller_delete_buffer`.
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_send(
+    wallet: WalletPtr,
+    settings: SettingsPtr,
+    address: *const u8,
+    address_length: usize,
+    value: u64,
+    transaction_out: *mut *const u8,
+    len_out: *mut usize,
+) -> ErrorPtr {
+    let r = wallet_send(
+        wallet as *mut WalletRust,
+        settings as *mut SettingsRust,
+        address,
+        address_length,
+        value,
+        transaction_out,
+        len_out,
+    );
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// delegate the stake of the wallet's account to the given pools
+///
+/// The fees are paid by the account. The returned transaction is added to
+/// the pending transactions of the wallet.
+///
+/// # Parameters
+///
+/// * `pools`: the identifiers of the pools, `pools_length` identifiers of
+///   32 bytes each;
+/// * `ratios`: the ratio of the stake to delegate to each of the pools,
+///   `pools_length` bytes;
+/// * `pools_length`: the number of pools, `0` to remove the delegation (the
+///   `pools` and `ratios` pointers are then ignored). If there is only one
+///   pool all the stake is delegated to it;
+/// * `owner`: build an owner stake delegation certificate if `true`, a stake
+///   delegation certificate signed by the account otherwise;
+///
+/// # Errors
+///
+/// This function may fail upon receiving a null pointer, invalid ratios
+/// or if the account does not have enough funds to pay for the fees.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+///
+/// Don't forget to remove `transaction_out` with
+/// `iohk_jormungandr_waller_delete_buffer`.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_delegate(
+    wallet: WalletPtr,
+    settings: SettingsPtr,
+    pools: *const u8,
+    ratios: *const u8,
+    pools_length: usize,
+    owner: bool,
+    transaction_out: *mut *const u8,
+    len_out: *mut usize,
+) -> ErrorPtr {
+    let r = wallet_delegate(
+        wallet as *mut WalletRust,
+        settings as *mut SettingsRust,
+        pools,
+        ratios,
+        pools_length,
+        owner,
+        transaction_out,
+        len_out,
+    );
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// set how many blocks deep a transaction needs to be to be confirmed by
+/// `iohk_jormungandr_wallet_apply_block` and after how many blocks a pending
+/// transaction that did not appear in a block is reported as expired
+///
+/// This resets the blocks applied so far.
+///
+/// # Errors
+///
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_set_confirmation_settings(
+    wallet: WalletPtr,
+    depth: u32,
+    deadline: u32,
+) -> ErrorPtr {
+    let r = wallet_set_confirmation_settings(wallet as *mut WalletRust, depth, deadline);
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// apply a new block of the blockchain to the wallet
+///
+/// The pending transactions of the wallet that are in the block are
+/// confirmed once the block is deep enough (see
+/// `iohk_jormungandr_wallet_set_confirmation_settings`). If the parent of
+/// the block is not the previous block applied, the blocks of the previous
+/// branch are considered rolled back.
+///
+/// # Parameters
+///
+/// * `header_id`: the identifier of the block, 32 bytes;
+/// * `parent_id`: the identifier of the parent of the block, 32 bytes;
+/// * `chain_length`: the chain length of the block;
+/// * `epoch` and `slot`: the date of the block;
+/// * `fragment_ids`: the identifiers of the fragments of the block,
+///   `fragment_ids_length` identifiers of 32 bytes each;
+/// * `update_out`: the transactions confirmed and the pending transactions
+///   that expired with this block;
+///
+/// # Errors
+///
+/// This function may fail upon receiving a null pointer, if the parent of
+/// the block has not been applied or if the chain length does not follow
+/// the chain length of the parent.
+///
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+///
+/// Don't forget to remove `update_out` with
+/// `iohk_jormungandr_wallet_delete_block_update`.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_apply_block(
+    wallet: WalletPtr,
+    header_id: *const u8,
+    parent_id: *const u8,
+    chain_length: u32,
+    epoch: u32,
+    slot: u32,
+    fragment_ids: *const u8,
+    fragment_ids_length: usize,
+    update_out: *mut BlockUpdatePtr,
+) -> ErrorPtr {
+    let r = wallet_apply_block(
+        wallet as *mut WalletRust,
+        header_id,
+        parent_id,
+        chain_length,
+        epoch,
+        slot,
+        fragment_ids,
+        fragment_ids_length,
+        update_out as *mut *mut BlockUpdateRust,
+    );
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// get the number of transactions confirmed by the block update
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_block_update_confirmed_size(
+    update: BlockUpdatePtr,
+) -> usize {
+    block_update_confirmed_size(update as *mut BlockUpdateRust)
+}
+
+/// retrieve the index-nth transaction id (32 bytes) confirmed by the block
+/// update, starting from 0 and finishing at `size-1` where size is retrieved
+/// from `iohk_jormungandr_wallet_block_update_confirmed_size`.
+///
+/// the memory returned is not owned and should not be kept for longer
+/// than potential call to `iohk_jormungandr_wallet_delete_block_update`
+///
+/// # Errors
+///
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_block_update_confirmed_get(
+    update: BlockUpdatePtr,
+    index: usize,
+    id_out: *mut *const u8,
+) -> ErrorPtr {
+    let r = block_update_confirmed_get(update as *mut BlockUpdateRust, index, id_out);
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// get the number of pending transactions that expired with the block update
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_block_update_expired_size(
+    update: BlockUpdatePtr,
+) -> usize {
+    block_update_expired_size(update as *mut BlockUpdateRust)
+}
+
+/// retrieve the index-nth pending transaction id (32 bytes) that expired
+/// with the block update, starting from 0 and finishing at `size-1` where
+/// size is retrieved from `iohk_jormungandr_wallet_block_update_expired_size`.
+///
+/// the memory returned is not owned and should not be kept for longer
+/// than potential call to `iohk_jormungandr_wallet_delete_block_update`
+///
+/// # Errors
+///
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_block_update_expired_get(
+    update: BlockUpdatePtr,
+    index: usize,
+    id_out: *mut *const u8,
+) -> ErrorPtr {
+    let r = block_update_expired_get(update as *mut BlockUpdateRust, index, id_out);
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// export the whole state of the wallet (keys, UTxOs, account state and
+/// pending transactions) so it can be restored later with
+/// `iohk_jormungandr_wallet_import`
+///
+/// # Parameters
+///
+/// * `password`: optional password to encrypt the exported wallet with,
+///   passing a null pointer will result in no encryption;
+/// * `password_length`: the length of the `password`;
+/// * `bytes_out`: used to return a pointer to the exported wallet;
+/// * `len_out`: used to return the length of the exported wallet;
+///
+/// # Errors
+///
+/// This function may fail upon receiving a null pointer or if the
+/// encryption failed.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+///
+/// Don't forget to remove `bytes_out` with
+/// `iohk_jormungandr_waller_delete_buffer`.
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_export(
+    wallet: WalletPtr,
+    password: *const u8,
+    password_length: usize,
+    bytes_out: *mut *const u8,
+    len_out: *mut usize,
+) -> ErrorPtr {
+    let r = wallet_export(
+        wallet as *mut WalletRust,
+        password,
+        password_length,
+        bytes_out,
+        len_out,
+    );
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// import a wallet exported with `iohk_jormungandr_wallet_export`
+///
+/// # Parameters
+///
+/// * `bytes`: the exported wallet;
+/// * `bytes_length`: the length of the exported wallet;
+/// * `password`: the password the wallet was exported with, if any.
+///   Passing a null pointer will result in no password;
+/// * `password_length`: the length of the `password`;
+/// * `wallet_out`: a pointer to a pointer. The imported wallet will be allocated on this pointer;
+///
+/// # Errors
+///
+/// This function may fail upon receiving a null pointer, if the password is
+/// missing or invalid or if the exported wallet is malformed.
+///
+/// On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+/// The `ErrorPtr` can then be observed to gathered details of the error.
+/// Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+/// the `ErrorPtr` from memory and avoid memory leaks.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_import(
+    bytes: *const u8,
+    bytes_length: usize,
+    password: *const u8,
+    password_length: usize,
+    wallet_out: *mut WalletPtr,
+) -> ErrorPtr {
+    let r = wallet_import(
+        bytes,
+        bytes_length,
+        password,
+        password_length,
+        wallet_out as *mut *mut WalletRust,
+    );
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// decrypt payload of the wallet transfer protocol
+///
+/// Parameters
+///
+/// password: byte buffer with the encryption password
+/// password_length: length of the password buffer
+/// ciphertext: byte buffer with the encryption password
+/// ciphertext_length: length of the password buffer
+/// plaintext_out: used to return a pointer to a byte buffer with the decrypted text
+/// plaintext_out_length: used to return the length of decrypted text
+///
+/// The returned buffer is in the heap, so make sure to call the delete_buffer function
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_symmetric_cipher_decrypt(
+    password: *const u8,
+    password_length: usize,
+    ciphertext: *const u8,
+    ciphertext_length: usize,
+    plaintext_out: *mut *const u8,
+    plaintext_out_length: *mut usize,
+) -> ErrorPtr {
+    let r = symmetric_cipher_decrypt(
+        password,
+        password_length,
+        ciphertext,
+        ciphertext_length,
+        plaintext_out,
+        plaintext_out_length,
+    );
+
+    r.into_c_api() as ErrorPtr
+}
+
+/// Get a string describing the error, this will return an allocated
+/// null terminated string describing the error.
+///
+/// If the given error is a `NULL` pointer, the string is and always
+/// is `"success"`. This string still need to be deleted with the
+/// `iohk_jormungandr_wallet_delete_string` function.
+///
+/// This function returns an allocated null terminated pointer. Don't
+/// forget to free the memory with: `iohk_jormungandr_wallet_delete_string`.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_error_to_string(error: ErrorPtr) -> *mut c_char {
+    if let Some(error) = (error as *mut ErrorRust).as_ref() {
+        CString::new(error.to_string()).unwrap().into_raw()
+    } else {
+        CString::new(b"success".to_vec()).unwrap().into_raw()
+    }
+}
+
+/// Get a string describing the error, this will return an allocated
+/// null terminated string providing extra details regarding the source
+/// of the error.
+///
+/// If the given error is a `NULL` pointer, the string is and always
+/// is `"success"`. If no details are available the function will return
+/// `"no more details"`. This string still need to be deleted with the
+/// `iohk_jormungandr_wallet_delete_string` function.
+///
+/// This function returns an allocated null terminated pointer. Don't
+/// forget to free the memory with: `iohk_jormungandr_wallet_delete_string`.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_error_details(error: ErrorPtr) -> *mut c_char {
+    if let Some(error) = (error as *mut ErrorRust).as_ref() {
+        if let Some(details) = error.details() {
+            CString::new(details.to_string()).unwrap().into_raw()
+        } else {
+            CString::new(b"no more details".to_vec())
+                .unwrap()
+                .into_raw()
+        }
+    } else {
+        CString::new(b"success".to_vec()).unwrap().into_raw()
+    }
+}
+
+/// Delete a null terminated string that was allocated by this library
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "C" fn iohk_jormungandr_wallet_delete_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        let cstring = CString::from_raw(ptr);
+        std::mem::drop(cstring)
+    }
+}
+
+/// Delete a binary buffer that was returned by this library alongside with its
+/// length.
 ///
 /// # Safety
 ///
@@ -713,6 +1549,45 @@ pub extern "C" fn iohk_jormungandr_wallet_delete_conversion(conversion: Conversi
     wallet_delete_conversion(conversion as *mut ConversionRust)
 }
 
+/// delete the pointer
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub extern "C" fn iohk_jormungandr_wallet_delete_block_update(update: BlockUpdatePtr) {
+    wallet_delete_block_update(update as *mut BlockUpdateRust)
+}
+
+/// delete the pointer
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub extern "C" fn iohk_jormungandr_wallet_delete_reconciliation(reconciliation: ReconciliationPtr) {
+    wallet_delete_reconciliation(reconciliation as *mut ReconciliationRust)
+}
+
+/// delete the pointer
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub extern "C" fn iohk_jormungandr_wallet_delete_mnemonics_entry(entry: MnemonicsEntryPtr) {
+    wallet_delete_mnemonics_entry(entry as *mut MnemonicsEntryRust)
+}
+
 /// delete the pointer
 ///
 /// # Safety
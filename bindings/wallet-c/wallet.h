@@ -19,6 +19,29 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+/**
+ * how the node's spending counter of the account compares to the local
+ * confirmed spending counter
+ */
+enum SyncStatus
+{
+  /**
+   * the node and the local state use the same spending counter
+   */
+  Consistent = 0,
+  /**
+   * the node already applied transactions of the account that are not
+   * confirmed in the local state
+   */
+  Ahead = 1,
+  /**
+   * the node did not apply yet transactions of the account that are
+   * confirmed in the local state
+   */
+  Behind = 2,
+};
+typedef uint8_t SyncStatus;
+
 typedef struct Error
 {
 
@@ -54,6 +77,27 @@ typedef struct Conversion
 
 typedef Conversion *ConversionPtr;
 
+typedef struct BlockUpdate
+{
+
+} BlockUpdate;
+
+typedef BlockUpdate *BlockUpdatePtr;
+
+typedef struct Reconciliation
+{
+
+} Reconciliation;
+
+typedef Reconciliation *ReconciliationPtr;
+
+typedef struct MnemonicsEntry
+{
+
+} MnemonicsEntry;
+
+typedef MnemonicsEntry *MnemonicsEntryPtr;
+
 /**
  * decrypt payload of the wallet transfer protocol
  *
@@ -127,6 +171,182 @@ ErrorPtr iohk_jormungandr_vote_proposal_new_public(const uint8_t *vote_plan_id,
                                                    uint8_t num_choices,
                                                    ProposalPtr *proposal_out);
 
+/**
+ * apply a new block of the blockchain to the wallet
+ *
+ * The pending transactions of the wallet that are in the block are
+ * confirmed once the block is deep enough (see
+ * `iohk_jormungandr_wallet_set_confirmation_settings`). If the parent of
+ * the block is not the previous block applied, the blocks of the previous
+ * branch are considered rolled back.
+ *
+ * # Parameters
+ *
+ * * `header_id`: the identifier of the block, 32 bytes;
+ * * `parent_id`: the identifier of the parent of the block, 32 bytes;
+ * * `chain_length`: the chain length of the block;
+ * * `epoch` and `slot`: the date of the block;
+ * * `fragment_ids`: the identifiers of the fragments of the block,
+ *   `fragment_ids_length` identifiers of 32 bytes each;
+ * * `update_out`: the transactions confirmed and the pending transactions
+ *   that expired with this block;
+ *
+ * # Errors
+ *
+ * This function may fail upon receiving a null pointer, if the parent of
+ * the block has not been applied or if the chain length does not follow
+ * the chain length of the parent.
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though the function checks if
+ * the pointers are null. Mind not to put random values in or you may see
+ * unexpected behaviors.
+ *
+ * Don't forget to remove `update_out` with
+ * `iohk_jormungandr_wallet_delete_block_update`.
+ */
+ErrorPtr iohk_jormungandr_wallet_apply_block(WalletPtr wallet,
+                                             const uint8_t *header_id,
+                                             const uint8_t *parent_id,
+                                             uint32_t chain_length,
+                                             uint32_t epoch,
+                                             uint32_t slot,
+                                             const uint8_t *fragment_ids,
+                                             uintptr_t fragment_ids_length,
+                                             BlockUpdatePtr *update_out);
+
+/**
+ * retrieve the index-nth transaction id (32 bytes) confirmed by the block
+ * update, starting from 0 and finishing at `size-1` where size is retrieved
+ * from `iohk_jormungandr_wallet_block_update_confirmed_size`.
+ *
+ * the memory returned is not owned and should not be kept for longer
+ * than potential call to `iohk_jormungandr_wallet_delete_block_update`
+ *
+ * # Errors
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+ErrorPtr iohk_jormungandr_wallet_block_update_confirmed_get(BlockUpdatePtr update,
+                                                            uintptr_t index,
+                                                            const uint8_t **id_out);
+
+/**
+ * get the number of transactions confirmed by the block update
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+uintptr_t iohk_jormungandr_wallet_block_update_confirmed_size(BlockUpdatePtr update);
+
+/**
+ * retrieve the index-nth pending transaction id (32 bytes) that expired
+ * with the block update, starting from 0 and finishing at `size-1` where
+ * size is retrieved from `iohk_jormungandr_wallet_block_update_expired_size`.
+ *
+ * the memory returned is not owned and should not be kept for longer
+ * than potential call to `iohk_jormungandr_wallet_delete_block_update`
+ *
+ * # Errors
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+ErrorPtr iohk_jormungandr_wallet_block_update_expired_get(BlockUpdatePtr update,
+                                                          uintptr_t index,
+                                                          const uint8_t **id_out);
+
+/**
+ * get the number of pending transactions that expired with the block update
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+uintptr_t iohk_jormungandr_wallet_block_update_expired_size(BlockUpdatePtr update);
+
+/**
+ * check the mnemonics entered so far by the user
+ *
+ * The words followed by a space (or an ideographic space) are complete and
+ * may be given by their prefix, the last word, if not followed by a space,
+ * is the word being typed. The completions of a single word are then the
+ * completions of the entry of its prefix, and the word a prefix stands for
+ * is the only word of the entry of the prefix followed by a space.
+ *
+ * The entry is returned in `entry_out`, see
+ * `iohk_jormungandr_wallet_mnemonics_entry_words_get`,
+ * `iohk_jormungandr_wallet_mnemonics_entry_completions_get` and
+ * `iohk_jormungandr_wallet_mnemonics_entry_is_valid`.
+ *
+ * # parameters
+ *
+ * * language: a null terminated string, the name of the dictionary
+ *   (`english`, `japanese`, `chinese-simplified`, `chinese-traditional`,
+ *   `french`, `italian`, `korean` or `spanish`);
+ * * mnemonics: a null terminated utf8 string (normalized to NFKD by the function);
+ * * entry_out: a pointer to a pointer. The entry will be allocated on this pointer;
+ *
+ * # errors
+ *
+ * The function may fail if:
+ *
+ * * the language is not known;
+ * * a complete word does not resolve to exactly one word of the dictionary;
+ * * no word completes the word being typed;
+ * * there are more words than the longest mnemonics (24 words);
+ * * the `entry_out` is null pointer
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ * Don't forget to remove `entry_out` with
+ * `iohk_jormungandr_wallet_delete_mnemonics_entry`.
+ */
+ErrorPtr iohk_jormungandr_wallet_check_mnemonics(const char *language,
+                                                 const char *mnemonics,
+                                                 MnemonicsEntryPtr *entry_out);
+
 /**
  * once funds have been retrieved with `iohk_jormungandr_wallet_retrieve_funds`
  * it is possible to convert all existing funds to the new wallet.
@@ -222,6 +442,59 @@ ErrorPtr iohk_jormungandr_wallet_convert_transactions_get(ConversionPtr conversi
  */
 uintptr_t iohk_jormungandr_wallet_convert_transactions_size(ConversionPtr conversion);
 
+/**
+ * delegate the stake of the wallet's account to the given pools
+ *
+ * The fees are paid by the account. The returned transaction is added to
+ * the pending transactions of the wallet.
+ *
+ * # Parameters
+ *
+ * * `pools`: the identifiers of the pools, `pools_length` identifiers of
+ *   32 bytes each;
+ * * `ratios`: the ratio of the stake to delegate to each of the pools,
+ *   `pools_length` bytes;
+ * * `pools_length`: the number of pools, `0` to remove the delegation (the
+ *   `pools` and `ratios` pointers are then ignored). If there is only one
+ *   pool all the stake is delegated to it;
+ * * `owner`: build an owner stake delegation certificate if `true`, a stake
+ *   delegation certificate signed by the account otherwise;
+ *
+ * # Errors
+ *
+ * This function may fail upon receiving a null pointer, invalid ratios
+ * or if the account does not have enough funds to pay for the fees.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though the function checks if
+ * the pointers are null. Mind not to put random values in or you may see
+ * unexpected behaviors.
+ *
+ * Don't forget to remove `transaction_out` with
+ * `iohk_jormungandr_waller_delete_buffer`.
+ */
+ErrorPtr iohk_jormungandr_wallet_delegate(WalletPtr wallet,
+                                          SettingsPtr settings,
+                                          const uint8_t *pools,
+                                          const uint8_t *ratios,
+                                          uintptr_t pools_length,
+                                          bool owner,
+                                          const uint8_t **transaction_out,
+                                          uintptr_t *len_out);
+
+/**
+ * delete the pointer
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+void iohk_jormungandr_wallet_delete_block_update(BlockUpdatePtr update);
+
 /**
  * Delete a binary buffer that was returned by this library alongside with its
  * length.
@@ -258,6 +531,18 @@ void iohk_jormungandr_wallet_delete_conversion(ConversionPtr conversion);
  */
 void iohk_jormungandr_wallet_delete_error(ErrorPtr error);
 
+/**
+ * delete the pointer
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+void iohk_jormungandr_wallet_delete_mnemonics_entry(MnemonicsEntryPtr entry);
+
 /**
  * delete the pointer
  *
@@ -270,6 +555,18 @@ void iohk_jormungandr_wallet_delete_error(ErrorPtr error);
  */
 void iohk_jormungandr_wallet_delete_proposal(ProposalPtr proposal);
 
+/**
+ * delete the pointer
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+void iohk_jormungandr_wallet_delete_reconciliation(ReconciliationPtr reconciliation);
+
 /**
  * delete the pointer and free the allocated memory
  *
@@ -348,6 +645,39 @@ char *iohk_jormungandr_wallet_error_details(ErrorPtr error);
  */
 char *iohk_jormungandr_wallet_error_to_string(ErrorPtr error);
 
+/**
+ * export the whole state of the wallet (keys, UTxOs, account state and
+ * pending transactions) so it can be restored later with
+ * `iohk_jormungandr_wallet_import`
+ *
+ * # Parameters
+ *
+ * * `password`: optional password to encrypt the exported wallet with,
+ *   passing a null pointer will result in no encryption;
+ * * `password_length`: the length of the `password`;
+ * * `bytes_out`: used to return a pointer to the exported wallet;
+ * * `len_out`: used to return the length of the exported wallet;
+ *
+ * # Errors
+ *
+ * This function may fail upon receiving a null pointer or if the
+ * encryption failed.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though the function checks if
+ * the pointers are null. Mind not to put random values in or you may see
+ * unexpected behaviors.
+ *
+ * Don't forget to remove `bytes_out` with
+ * `iohk_jormungandr_waller_delete_buffer`.
+ */
+ErrorPtr iohk_jormungandr_wallet_export(WalletPtr wallet,
+                                        const uint8_t *password,
+                                        uintptr_t password_length,
+                                        const uint8_t **bytes_out,
+                                        uintptr_t *len_out);
+
 /**
  * get the wallet id
  *
@@ -382,6 +712,40 @@ char *iohk_jormungandr_wallet_error_to_string(ErrorPtr error);
 ErrorPtr iohk_jormungandr_wallet_id(WalletPtr wallet,
                                     uint8_t *id_out);
 
+/**
+ * import a wallet exported with `iohk_jormungandr_wallet_export`
+ *
+ * # Parameters
+ *
+ * * `bytes`: the exported wallet;
+ * * `bytes_length`: the length of the exported wallet;
+ * * `password`: the password the wallet was exported with, if any.
+ *   Passing a null pointer will result in no password;
+ * * `password_length`: the length of the `password`;
+ * * `wallet_out`: a pointer to a pointer. The imported wallet will be allocated on this pointer;
+ *
+ * # Errors
+ *
+ * This function may fail upon receiving a null pointer, if the password is
+ * missing or invalid or if the exported wallet is malformed.
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though the function checks if
+ * the pointers are null. Mind not to put random values in or you may see
+ * unexpected behaviors.
+ */
+ErrorPtr iohk_jormungandr_wallet_import(const uint8_t *bytes,
+                                        uintptr_t bytes_length,
+                                        const uint8_t *password,
+                                        uintptr_t password_length,
+                                        WalletPtr *wallet_out);
+
 /**
  * recover a wallet from an account and a list of utxo keys
  *
@@ -417,6 +781,240 @@ ErrorPtr iohk_jormungandr_wallet_import_keys(const uint8_t *account_key,
                                              uintptr_t utxo_keys_len,
                                              WalletPtr *wallet_out);
 
+/**
+ * retrieve the index-nth word completing the word being typed, in the
+ * order of the dictionary, starting from 0 and finishing at `size-1` where
+ * size is retrieved from
+ * `iohk_jormungandr_wallet_mnemonics_entry_completions_size`.
+ *
+ * The word is a utf8 string of `word_length_out` bytes, it is not null
+ * terminated. The memory returned is not owned and must not be freed.
+ *
+ * # Errors
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+ErrorPtr iohk_jormungandr_wallet_mnemonics_entry_completions_get(MnemonicsEntryPtr entry,
+                                                                 uintptr_t index,
+                                                                 const uint8_t **word_out,
+                                                                 uintptr_t *word_length_out);
+
+/**
+ * get the number of words completing the word being typed, 0 if no word
+ * is being typed
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+uintptr_t iohk_jormungandr_wallet_mnemonics_entry_completions_size(MnemonicsEntryPtr entry);
+
+/**
+ * get whether the complete words of the entry are mnemonics of a supported
+ * length with a valid checksum, they can then be used with
+ * `iohk_jormungandr_wallet_recover`
+ *
+ * # Errors
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+ErrorPtr iohk_jormungandr_wallet_mnemonics_entry_is_valid(MnemonicsEntryPtr entry,
+                                                          bool *valid_out);
+
+/**
+ * retrieve the index-nth complete word of the entry, resolved from its
+ * prefix, starting from 0 and finishing at `size-1` where size is retrieved
+ * from `iohk_jormungandr_wallet_mnemonics_entry_words_size`.
+ *
+ * The word is a utf8 string of `word_length_out` bytes, it is not null
+ * terminated. The memory returned is not owned and must not be freed.
+ *
+ * # Errors
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+ErrorPtr iohk_jormungandr_wallet_mnemonics_entry_words_get(MnemonicsEntryPtr entry,
+                                                           uintptr_t index,
+                                                           const uint8_t **word_out,
+                                                           uintptr_t *word_length_out);
+
+/**
+ * get the number of complete words of the entry
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+uintptr_t iohk_jormungandr_wallet_mnemonics_entry_words_size(MnemonicsEntryPtr entry);
+
+/**
+ * synchronise the wallet account state with the value and the spending
+ * counter of the account as reported by the node
+ *
+ * Same as `iohk_jormungandr_wallet_set_state` but the reconciliation report
+ * is returned in `reconciliation_out`, see
+ * `iohk_jormungandr_wallet_reconciliation_status`,
+ * `iohk_jormungandr_wallet_reconciliation_dropped_get` and
+ * `iohk_jormungandr_wallet_reconciliation_confirmed_get`.
+ *
+ * # Errors
+ *
+ * * this function may fail if the wallet pointer or `reconciliation_out` is null;
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ * Don't forget to remove `reconciliation_out` with
+ * `iohk_jormungandr_wallet_delete_reconciliation`.
+ */
+ErrorPtr iohk_jormungandr_wallet_reconcile_state(WalletPtr wallet,
+                                                 uint64_t value,
+                                                 uint32_t counter,
+                                                 ReconciliationPtr *reconciliation_out);
+
+/**
+ * retrieve the index-nth transaction id (32 bytes) confirmed locally that
+ * is part of the node's state, starting from 0 and finishing at `size-1`
+ * where size is retrieved from
+ * `iohk_jormungandr_wallet_reconciliation_confirmed_size`.
+ *
+ * the memory returned is not owned and should not be kept for longer
+ * than potential call to `iohk_jormungandr_wallet_delete_reconciliation`
+ *
+ * # Errors
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+ErrorPtr iohk_jormungandr_wallet_reconciliation_confirmed_get(ReconciliationPtr reconciliation,
+                                                              uintptr_t index,
+                                                              const uint8_t **id_out);
+
+/**
+ * get the number of transactions confirmed locally that are part of the
+ * node's state
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+uintptr_t iohk_jormungandr_wallet_reconciliation_confirmed_size(ReconciliationPtr reconciliation);
+
+/**
+ * retrieve the index-nth transaction id (32 bytes) dropped by the
+ * reconciliation, starting from 0 and finishing at `size-1` where size is
+ * retrieved from `iohk_jormungandr_wallet_reconciliation_dropped_size`.
+ *
+ * the memory returned is not owned and should not be kept for longer
+ * than potential call to `iohk_jormungandr_wallet_delete_reconciliation`
+ *
+ * # Errors
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+ErrorPtr iohk_jormungandr_wallet_reconciliation_dropped_get(ReconciliationPtr reconciliation,
+                                                            uintptr_t index,
+                                                            const uint8_t **id_out);
+
+/**
+ * get the number of pending transactions dropped by the reconciliation
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+uintptr_t iohk_jormungandr_wallet_reconciliation_dropped_size(ReconciliationPtr reconciliation);
+
+/**
+ * how the node's spending counter of the account compared to the local
+ * confirmed spending counter
+ *
+ * # Errors
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+ErrorPtr iohk_jormungandr_wallet_reconciliation_status(ReconciliationPtr reconciliation,
+                                                       SyncStatus *status_out);
+
 /**
  * retrieve a wallet from the given mnemonics, password and protocol magic
  *
@@ -430,9 +1028,12 @@ ErrorPtr iohk_jormungandr_wallet_import_keys(const uint8_t *account_key,
  *
  * # parameters
  *
- * * mnemonics: a null terminated utf8 string (already normalized NFKD) in english;
+ * * mnemonics: a null terminated utf8 string (normalized to NFKD by the function) in
+ *   any of the supported languages, the language is detected from the words;
  * * password: pointer to the password (in bytes, can be UTF8 string or a bytes of anything);
- *   this value is optional and passing a null pointer will result in no password;
+ *   this value is optional and passing a null pointer will result in no password.
+ *   The Daedalus wallet is not recovered when a password is given (see
+ *   `iohk_jormungandr_wallet_recovered_schemes`);
  * * password_length: the length of the password;
  * * wallet_out: a pointer to a pointer. The recovered wallet will be allocated on this pointer;
  *
@@ -460,6 +1061,35 @@ ErrorPtr iohk_jormungandr_wallet_recover(const char *mnemonics,
                                          uintptr_t password_length,
                                          WalletPtr *wallet_out);
 
+/**
+ * the legacy wallet schemes recovered along with the account, the funds
+ * of the schemes that were not recovered are not retrieved
+ *
+ * `daedalus_out` is set to `true` if the Daedalus wallet was recovered,
+ * it is not recovered when the mnemonics are protected with a password.
+ * `yoroi_out` is set to `true` if the Yoroi wallet was recovered.
+ *
+ * # Errors
+ *
+ * * this function may fail if the wallet pointer is null;
+ * * this function may fail if `daedalus_out` or `yoroi_out` is null;
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though
+ * the function checks if the pointers are null. Mind not to put random values
+ * in or you may see unexpected behaviors
+ *
+ */
+ErrorPtr iohk_jormungandr_wallet_recovered_schemes(WalletPtr wallet,
+                                                   bool *daedalus_out,
+                                                   bool *yoroi_out);
+
 /**
  * retrieve funds from daedalus or yoroi wallet in the given block0 (or
  * any other blocks).
@@ -500,6 +1130,66 @@ ErrorPtr iohk_jormungandr_wallet_retrieve_funds(WalletPtr wallet,
                                                 uintptr_t block0_length,
                                                 SettingsPtr *settings_out);
 
+/**
+ * build a transaction sending `value` to the given `address` from the
+ * wallet's account
+ *
+ * The fees are paid by the account on top of the `value`. The returned
+ * transaction is added to the pending transactions of the wallet.
+ *
+ * # Parameters
+ *
+ * * `address`: the binary encoded address to send the funds to;
+ * * `address_length`: the length of the `address` buffer;
+ * * `value`: the amount of lovelace to send;
+ *
+ * # Errors
+ *
+ * This function may fail upon receiving a null pointer, an invalid address
+ * or if the account does not have enough funds to pay for the `value` and
+ * the fees.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though the function checks if
+ * the pointers are null. Mind not to put random values in or you may see
+ * unexpected behaviors.
+ *
+ * Don't forget to remove `transaction_out` with
+ * `iohk_jormungandr_waller_delete_buffer`.
+ */
+ErrorPtr iohk_jormungandr_wallet_send(WalletPtr wallet,
+                                      SettingsPtr settings,
+                                      const uint8_t *address,
+                                      uintptr_t address_length,
+                                      uint64_t value,
+                                      const uint8_t **transaction_out,
+                                      uintptr_t *len_out);
+
+/**
+ * set how many blocks deep a transaction needs to be to be confirmed by
+ * `iohk_jormungandr_wallet_apply_block` and after how many blocks a pending
+ * transaction that did not appear in a block is reported as expired
+ *
+ * This resets the blocks applied so far.
+ *
+ * # Errors
+ *
+ * On error the function returns a `ErrorPtr`. On success `NULL` is returned.
+ * The `ErrorPtr` can then be observed to gathered details of the error.
+ * Don't forget to call `iohk_jormungandr_wallet_delete_error` to free
+ * the `ErrorPtr` from memory and avoid memory leaks.
+ *
+ * # Safety
+ *
+ * This function dereference raw pointers. Even though the function checks if
+ * the pointers are null. Mind not to put random values in or you may see
+ * unexpected behaviors.
+ */
+ErrorPtr iohk_jormungandr_wallet_set_confirmation_settings(WalletPtr wallet,
+                                                           uint32_t depth,
+                                                           uint32_t deadline);
+
 /**
  * update the wallet account state
  *
@@ -511,6 +1201,9 @@ ErrorPtr iohk_jormungandr_wallet_retrieve_funds(WalletPtr wallet,
  * transactions otherwise future transactions may fail to be accepted by any
  * nodes of the blockchain because of invalid signature state.
  *
+ * The pending transactions that are still valid with the given counter are
+ * kept, the ones using an older spending counter are dropped.
+ *
  * # Errors
  *
  * * this function may fail if the wallet pointer is null;
@@ -96,6 +96,12 @@ public class WalletPlugin extends CordovaPlugin {
             case "WALLET_CONFIRM_TRANSACTION":
                 walletConfirmTransaction(args, callbackContext);
                 break;
+            case "WALLET_EXPORT":
+                walletExport(args, callbackContext);
+                break;
+            case "WALLET_IMPORT":
+                walletImport(args, callbackContext);
+                break;
             case "CONVERSION_TRANSACTIONS_SIZE":
                 conversionTransactionsSize(args, callbackContext);
                 break;
@@ -135,12 +141,13 @@ public class WalletPlugin extends CordovaPlugin {
 
     private void walletRestore(final CordovaArgs args, final CallbackContext callbackContext) throws JSONException {
         final String mnemonics = args.getString(0);
+        final byte[] password = args.getArrayBuffer(1);
 
         cordova.getThreadPool().execute(new Runnable() {
             public void run() {
                 try {
                     final String normalized = Normalizer.normalize(mnemonics, Form.NFKD);
-                    final long walletPtr = Wallet.recover(normalized);
+                    final long walletPtr = Wallet.recover(normalized, password);
                     callbackContext.success(Long.toString(walletPtr));
                 } catch (final Exception e) {
                     callbackContext.error(e.getMessage());
@@ -284,6 +291,38 @@ public class WalletPlugin extends CordovaPlugin {
         }
     }
 
+    private void walletExport(final CordovaArgs args, final CallbackContext callbackContext) throws JSONException {
+        final Long walletPtr = args.getLong(0);
+        final byte[] password = args.getArrayBuffer(1);
+
+        cordova.getThreadPool().execute(new Runnable() {
+            public void run() {
+                try {
+                    final byte[] exported = Wallet.exportWallet(walletPtr, password);
+                    callbackContext.success(exported);
+                } catch (final Exception e) {
+                    callbackContext.error(e.getMessage());
+                }
+            }
+        });
+    }
+
+    private void walletImport(final CordovaArgs args, final CallbackContext callbackContext) throws JSONException {
+        final byte[] bytes = args.getArrayBuffer(0);
+        final byte[] password = args.getArrayBuffer(1);
+
+        cordova.getThreadPool().execute(new Runnable() {
+            public void run() {
+                try {
+                    final long walletPtr = Wallet.importWallet(bytes, password);
+                    callbackContext.success(Long.toString(walletPtr));
+                } catch (final Exception e) {
+                    callbackContext.error(e.getMessage());
+                }
+            }
+        });
+    }
+
     private void walletConvert(final CordovaArgs args, final CallbackContext callbackContext) throws JSONException {
         final Long walletPtr = args.getLong(0);
         final Long settingsPtr = args.getLong(1);
@@ -8,7 +8,7 @@ const wasm = new Promise(function (resolve, reject) {
 async function walletRestore (successCallback, errorCallback, opts) {
     if (opts && typeof (opts[0]) === 'string') {
         const mnemonics = opts[0];
-        const password = '';
+        const password = new Uint8Array(opts[1]);
         try {
             const wallet = (await wasm).Wallet.recover(mnemonics, password);
             successCallback(wallet.ptr.toString());
@@ -168,6 +168,28 @@ async function walletId (successCallback, errorCallback, opts) {
     }
 }
 
+async function walletExport (successCallback, errorCallback, opts) {
+    const walletPtr = opts[0];
+    const password = opts[1];
+
+    const wallet = (await wasm).Wallet.__wrap(walletPtr);
+
+    try {
+        successCallback(wallet.export_wallet(new Uint8Array(password)));
+    } catch (err) {
+        errorCallback(`couldn't export the wallet ${err}`);
+    }
+}
+
+async function walletImport (successCallback, errorCallback, opts) {
+    try {
+        const wallet = (await wasm).Wallet.import_wallet(new Uint8Array(opts[0]), new Uint8Array(opts[1]));
+        successCallback(wallet.ptr.toString());
+    } catch (err) {
+        errorCallback(`couldn't import the wallet ${err}`);
+    }
+}
+
 async function walletConvert (successCallback, errorCallback, opts) {
     if (opts && typeof (opts[0]) === 'string' && typeof (opts[1]) === 'string') {
         const walletPtr = opts[0];
@@ -360,7 +382,9 @@ const bindings = {
     WALLET_CONFIRM_TRANSACTION: walletConfirmTransaction,
     WALLET_CONVERT: walletConvert,
     WALLET_DELETE: walletDelete,
+    WALLET_EXPORT: walletExport,
     WALLET_ID: walletId,
+    WALLET_IMPORT: walletImport,
     WALLET_IMPORT_KEYS: walletImportKeys,
     WALLET_PENDING_TRANSACTIONS: walletPendingTransactions,
     WALLET_RESTORE: walletRestore,
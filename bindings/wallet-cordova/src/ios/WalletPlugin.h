@@ -14,6 +14,8 @@
 - (void)WALLET_SET_STATE:(CDVInvokedUrlCommand*)command;
 - (void)WALLET_VOTE:(CDVInvokedUrlCommand*)command;
 - (void)WALLET_CONVERT:(CDVInvokedUrlCommand*)command;
+- (void)WALLET_EXPORT:(CDVInvokedUrlCommand*)command;
+- (void)WALLET_IMPORT:(CDVInvokedUrlCommand*)command;
 
 - (void)CONVERSION_TRANSACTIONS_SIZE:(CDVInvokedUrlCommand*)command;
 - (void)CONVERSION_TRANSACTIONS_GET:(CDVInvokedUrlCommand*)command;
@@ -28,13 +28,23 @@ jormungandr_error_to_plugin_result(ErrorPtr error)
 - (void)WALLET_RESTORE:(CDVInvokedUrlCommand*)command
 {
     NSString* mnemonics = [command.arguments objectAtIndex:0];
+    NSData* password = [command.arguments objectAtIndex:1];
+
+    if ([password isEqual:[NSNull null]]) {
+        CDVPluginResult* pluginResult = [CDVPluginResult resultWithStatus:CDVCommandStatus_ERROR
+                                                          messageAsString:@"missing argument"];
+        [self.commandDelegate sendPluginResult:pluginResult callbackId:command.callbackId];
+        return;
+    }
 
     [self.commandDelegate runInBackground:^{
         CDVPluginResult* pluginResult = nil;
 
         WalletPtr wallet_ptr;
-        ErrorPtr result =
-            iohk_jormungandr_wallet_recover([mnemonics UTF8String], nil, 0, &wallet_ptr);
+        ErrorPtr result = iohk_jormungandr_wallet_recover([mnemonics UTF8String],
+            password.length > 0 ? password.bytes : nil,
+            password.length,
+            &wallet_ptr);
 
         if (result != nil) {
             pluginResult = jormungandr_error_to_plugin_result(result);
@@ -295,6 +305,87 @@ jormungandr_error_to_plugin_result(ErrorPtr error)
     [self.commandDelegate sendPluginResult:pluginResult callbackId:command.callbackId];
 }
 
+- (void)WALLET_EXPORT:(CDVInvokedUrlCommand*)command
+{
+    NSString* wallet_ptr_raw = [command.arguments objectAtIndex:0];
+    NSData* password = [command.arguments objectAtIndex:1];
+
+    if ([password isEqual:[NSNull null]]) {
+        CDVPluginResult* pluginResult = [CDVPluginResult resultWithStatus:CDVCommandStatus_ERROR
+                                                          messageAsString:@"missing argument"];
+        [self.commandDelegate sendPluginResult:pluginResult callbackId:command.callbackId];
+        return;
+    }
+
+    WalletPtr wallet_ptr = (WalletPtr)[wallet_ptr_raw longLongValue];
+
+    [self.commandDelegate runInBackground:^{
+        CDVPluginResult* pluginResult = nil;
+
+        const uint8_t* bytes_out = nil;
+        uintptr_t len_out;
+
+        ErrorPtr result = iohk_jormungandr_wallet_export(wallet_ptr,
+            password.length > 0 ? password.bytes : nil,
+            password.length,
+            &bytes_out,
+            &len_out);
+
+        if (result != nil) {
+            pluginResult = jormungandr_error_to_plugin_result(result);
+        } else {
+            NSData* returnValue = [NSData dataWithBytes:bytes_out length:len_out];
+            pluginResult = [CDVPluginResult resultWithStatus:CDVCommandStatus_OK
+                                        messageAsArrayBuffer:returnValue];
+
+            iohk_jormungandr_wallet_delete_buffer((uint8_t*)bytes_out, len_out);
+        }
+
+        [self.commandDelegate sendPluginResult:pluginResult callbackId:command.callbackId];
+    }];
+}
+
+- (void)WALLET_IMPORT:(CDVInvokedUrlCommand*)command
+{
+    NSData* bytes = [command.arguments objectAtIndex:0];
+    NSData* password = [command.arguments objectAtIndex:1];
+
+    if ([bytes isEqual:[NSNull null]]) {
+        CDVPluginResult* pluginResult = [CDVPluginResult resultWithStatus:CDVCommandStatus_ERROR
+                                                          messageAsString:@"missing argument"];
+        [self.commandDelegate sendPluginResult:pluginResult callbackId:command.callbackId];
+        return;
+    }
+
+    if ([password isEqual:[NSNull null]]) {
+        CDVPluginResult* pluginResult = [CDVPluginResult resultWithStatus:CDVCommandStatus_ERROR
+                                                          messageAsString:@"missing argument"];
+        [self.commandDelegate sendPluginResult:pluginResult callbackId:command.callbackId];
+        return;
+    }
+
+    [self.commandDelegate runInBackground:^{
+        CDVPluginResult* pluginResult = nil;
+        WalletPtr wallet_ptr = nil;
+
+        ErrorPtr result = iohk_jormungandr_wallet_import(bytes.bytes,
+            bytes.length,
+            password.length > 0 ? password.bytes : nil,
+            password.length,
+            &wallet_ptr);
+
+        if (result != nil) {
+            pluginResult = jormungandr_error_to_plugin_result(result);
+        } else {
+            NSString* returnValue = [NSString stringWithFormat:@"%ld", (uintptr_t)wallet_ptr];
+            pluginResult = [CDVPluginResult resultWithStatus:CDVCommandStatus_OK
+                                             messageAsString:returnValue];
+        }
+
+        [self.commandDelegate sendPluginResult:pluginResult callbackId:command.callbackId];
+    }];
+}
+
 - (void)CONVERSION_TRANSACTIONS_SIZE:(CDVInvokedUrlCommand*)command
 {
     NSString* conversion_ptr_raw = [command.arguments objectAtIndex:0];
@@ -41,6 +41,8 @@ const walletConfirmTransaction = promisifyP(primitives.walletConfirmTransaction)
 const walletPendingTransactions = promisifyP(primitives.walletPendingTransactions);
 const pendingTransactionsGet = promisifyP(primitives.pendingTransactionsGet);
 const symmetricCipherDecrypt = promisifyP(primitives.symmetricCipherDecrypt);
+const walletExport = promisifyP(primitives.walletExport);
+const walletImport = promisifyP(primitives.walletImport);
 
 
 const tests = [
@@ -75,7 +77,7 @@ const tests = [
         await deleteWallet(walletPtr);
     }],
     ['should import keys', async function () {
-        const walletPtr = await restoreWallet(YOROI_WALLET);
+        const walletPtr = await restoreWallet(YOROI_WALLET, new Uint8Array(0));
         expect(walletPtr !== 0).toBe(true);
         const settingsPtr = await retrieveFunds(walletPtr, hexStringToBytes(BLOCK0));
         expect(settingsPtr !== 0).toBe(true);
@@ -87,21 +89,21 @@ const tests = [
     }],
     // there is nothing we can assert here, I think
     ['should be able to set state', async function () {
-        let wallet = await restoreWallet(YOROI_WALLET);
+        let wallet = await restoreWallet(YOROI_WALLET, new Uint8Array(0));
         const value = 1000;
         const counter = 2;
         await setState(wallet, value, counter);
     }],
     ['should fail with invalid mnemonics', async function () {
         try {
-            await restoreWallet('invalidmnemonics');
+            await restoreWallet('invalidmnemonics', new Uint8Array(0));
             throw Error('Invalid mnemonics should fail');
         } catch (e) {
             return;
         }
     }],
     ['should fail with invalid block', async function () {
-        const wallet = await restoreWallet(YOROI_WALLET);
+        const wallet = await restoreWallet(YOROI_WALLET, new Uint8Array(0));
         try {
             await retrieveFunds(wallet, [0, 0, 0, 0]);
         }
@@ -111,7 +113,7 @@ const tests = [
         throw Error('Invalid block should fail');
     }],
     ['get conversion transaction', async function () {
-        const wallet = await restoreWallet(YOROI_WALLET);
+        const wallet = await restoreWallet(YOROI_WALLET, new Uint8Array(0));
         expect(wallet !== 0).toBe(true);
 
         const settings = await retrieveFunds(wallet, hexStringToBytes(BLOCK0));
@@ -146,7 +148,7 @@ const tests = [
         const numChoices = 3;
 
         const proposalPtr = await proposalNew(votePlanId, payloadType, index, numChoices);
-        const walletPtr = await restoreWallet(YOROI_WALLET);
+        const walletPtr = await restoreWallet(YOROI_WALLET, new Uint8Array(0));
         const settingsPtr = await retrieveFunds(walletPtr, hexStringToBytes(BLOCK0));
         await walletSetState(walletPtr, 1000000, 1);
         await walletVote(walletPtr, settingsPtr, proposalPtr, 0);
@@ -166,7 +168,7 @@ const tests = [
         const numChoices = 3;
 
         const proposalPtr = await proposalNewPrivate(votePlanId, index, numChoices, hexStringToBytes(VOTE_ENCRYPTION_KEY));
-        const walletPtr = await restoreWallet(YOROI_WALLET);
+        const walletPtr = await restoreWallet(YOROI_WALLET, new Uint8Array(0));
         const settingsPtr = await retrieveFunds(walletPtr, hexStringToBytes(BLOCK0));
         await walletSetState(walletPtr, 1000000, 1);
         await walletVote(walletPtr, settingsPtr, proposalPtr, 0);
@@ -175,6 +177,26 @@ const tests = [
         await deleteWallet(walletPtr);
         await deleteProposal(proposalPtr);
     }],
+    ['can export and import the wallet', async function () {
+        const walletPtr = await restoreWallet(YOROI_WALLET, new Uint8Array(0));
+        const settingsPtr = await retrieveFunds(walletPtr, hexStringToBytes(BLOCK0));
+
+        const exported = await walletExport(walletPtr, PASSWORD);
+        const importedPtr = await walletImport(new Uint8Array(exported), PASSWORD);
+        const funds = await totalFunds(importedPtr);
+        expect(parseInt(funds)).toBe(WALLET_VALUE);
+
+        try {
+            await walletImport(new Uint8Array(exported), new Uint8Array(0));
+            throw Error('import without the password should fail');
+        } catch (e) {
+            expect(e.message).not.toBe('import without the password should fail');
+        }
+
+        await deleteSettings(settingsPtr);
+        await deleteWallet(walletPtr);
+        await deleteWallet(importedPtr);
+    }],
     ['decrypts keys correctly', async function () {
         const decryptedKeys = await symmetricCipherDecrypt(PASSWORD, hexStringToBytes(ENCRYPTED_WALLET));
         const account = decryptedKeys.slice(0 * 64, 1 * 64);
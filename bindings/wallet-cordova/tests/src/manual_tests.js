@@ -4,7 +4,7 @@ const { hex, hexStringToBytes } = require('./utils.js');
 
 // TODO: untangle this nesting hell. I still don't know if I can use promises/async here
 function restoreManualInputWallet(mnemonics, hexBlock, callBack) {
-    window.wallet.walletRestore(mnemonics, wallet => {
+    window.wallet.walletRestore(mnemonics, new Uint8Array(0), wallet => {
         window.wallet.walletRetrieveFunds(wallet, hexStringToBytes(hexBlock), settings => {
             window.wallet.walletTotalFunds(wallet, retrievedFunds => {
                 window.wallet.settingsDelete(settings, () => {
@@ -22,7 +22,7 @@ function restoreManualInputWallet(mnemonics, hexBlock, callBack) {
 }
 
 function getAccountId(mnemonics, callBack) {
-    primitives.walletRestore(mnemonics, wallet => {
+    primitives.walletRestore(mnemonics, new Uint8Array(0), wallet => {
         primitives.walletId(wallet, function (id) {
             callBack(undefined, hex(id));
         }, function (err) {
@@ -12,6 +12,8 @@ const WALLET_CONVERT_ACTION_TAG = 'WALLET_CONVERT';
 const WALLET_SET_STATE_ACTION_TAG = 'WALLET_SET_STATE';
 const WALLET_VOTE_ACTION_TAG = 'WALLET_VOTE';
 const WALLET_CONFIRM_TRANSACTION = 'WALLET_CONFIRM_TRANSACTION';
+const WALLET_EXPORT_ACTION_TAG = 'WALLET_EXPORT';
+const WALLET_IMPORT_ACTION_TAG = 'WALLET_IMPORT';
 const CONVERSION_TRANSACTIONS_SIZE_ACTION_TAG = 'CONVERSION_TRANSACTIONS_SIZE';
 const CONVERSION_TRANSACTIONS_GET_ACTION_TAG = 'CONVERSION_TRANSACTIONS_GET';
 const CONVERSION_IGNORED_GET_ACTION_TAG = 'CONVERSION_IGNORED';
@@ -64,12 +66,15 @@ var plugin = {
 
     /**
      * @param {string} mnemonics a string with the mnemonic phrase
+     * @param {Uint8Array} password the password protecting the mnemonics, empty for no password. The Daedalus wallet is not recovered when a password is given
      * @param {pointerCallback} successCallback on success returns a pointer to a Wallet object
      * @param {errorCallback} errorCallback this function can fail if the mnemonics are invalid
      */
-    walletRestore: function (mnemonics, successCallback, errorCallback) {
-        argscheck.checkArgs('sff', 'walletRestore', arguments);
-        exec(successCallback, errorCallback, NATIVE_CLASS_NAME, WALLET_RESTORE_ACTION_TAG, [mnemonics]);
+    walletRestore: function (mnemonics, password, successCallback, errorCallback) {
+        argscheck.checkArgs('s*ff', 'walletRestore', arguments);
+        checkUint8Array({ name: 'password', testee: password });
+
+        exec(successCallback, errorCallback, NATIVE_CLASS_NAME, WALLET_RESTORE_ACTION_TAG, [mnemonics, password.buffer]);
     },
 
     /**
@@ -232,6 +237,38 @@ var plugin = {
         exec(successCallback, errorCallback, NATIVE_CLASS_NAME, WALLET_CONFIRM_TRANSACTION, [walletPtr, transactionId.buffer]);
     },
 
+    /**
+     * export the whole state of the wallet (keys, UTxOs, account state and
+     * pending transactions) so it can be restored later with walletImport
+     *
+     * @param {string} walletPtr a pointer to a wallet obtained with walletRestore
+     * @param {Uint8Array} password the password to encrypt the exported wallet with, empty for no encryption
+     * @param {function} successCallback returns an ArrayBuffer with the exported wallet
+     * @param {errorCallback} errorCallback this function can fail if the encryption failed
+     */
+    walletExport: function (walletPtr, password, successCallback, errorCallback) {
+        argscheck.checkArgs('s*ff', 'walletExport', arguments);
+        checkUint8Array({ name: 'password', testee: password });
+
+        exec(successCallback, errorCallback, NATIVE_CLASS_NAME, WALLET_EXPORT_ACTION_TAG, [walletPtr, password.buffer]);
+    },
+
+    /**
+     * import a wallet exported with walletExport
+     *
+     * @param {Uint8Array} bytes the exported wallet
+     * @param {Uint8Array} password the password the wallet was exported with, empty if none
+     * @param {pointerCallback} successCallback on success returns a pointer to a Wallet object
+     * @param {errorCallback} errorCallback this function can fail if the password is invalid or the exported wallet is malformed
+     */
+    walletImport: function (bytes, password, successCallback, errorCallback) {
+        argscheck.checkArgs('**ff', 'walletImport', arguments);
+        checkUint8Array({ name: 'bytes', testee: bytes });
+        checkUint8Array({ name: 'password', testee: password });
+
+        exec(successCallback, errorCallback, NATIVE_CLASS_NAME, WALLET_IMPORT_ACTION_TAG, [bytes.buffer, password.buffer]);
+    },
+
     /**
      * @param {string} ptr a pointer to a Conversion object obtained with walletConvert
      * @param {function} successCallback returns a number representing the number of transactions produced by the conversion
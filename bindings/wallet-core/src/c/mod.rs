@@ -4,8 +4,13 @@
 mod macros;
 pub mod vote;
 
-use crate::{Conversion, Error, Proposal, Result, Wallet};
-use chain_impl_mockchain::{transaction::Input, value::Value, vote::Choice};
+use crate::{
+    BlockUpdate, Conversion, Error, MnemonicsEntry, Proposal, Reconciliation, Result, SyncStatus,
+    Wallet,
+};
+use chain_impl_mockchain::{
+    block::BlockDate, certificate::PoolId, transaction::Input, value::Value, vote::Choice,
+};
 use std::convert::TryInto;
 
 use thiserror::Error;
@@ -17,6 +22,9 @@ pub type ConversionPtr = *mut Conversion;
 pub type ProposalPtr = *mut Proposal;
 pub type ErrorPtr = *mut Error;
 pub type PendingTransactionsPtr = *mut PendingTransactions;
+pub type BlockUpdatePtr = *mut BlockUpdate;
+pub type ReconciliationPtr = *mut Reconciliation;
+pub type MnemonicsEntryPtr = *mut MnemonicsEntry;
 
 #[derive(Debug, Error)]
 #[error("null pointer")]
@@ -32,6 +40,8 @@ pub struct PendingTransactions {
 }
 
 pub const FRAGMENT_ID_LENGTH: usize = 32;
+pub const HEADER_ID_LENGTH: usize = 32;
+pub const POOL_ID_LENGTH: usize = 32;
 
 /// retrieve a wallet from the given mnemonics, password and protocol magic
 ///
@@ -45,9 +55,11 @@ pub const FRAGMENT_ID_LENGTH: usize = 32;
 ///
 /// # parameters
 ///
-/// * mnemonics: a null terminated utf8 string (already normalized NFKD) in english;
+/// * mnemonics: a null terminated utf8 string (normalized to NFKD by the function) in
+///   any of the supported languages, the language is detected from the words;
 /// * password: pointer to the password (in bytes, can be UTF8 string or a bytes of anything);
-///   this value is optional and passing a null pointer will result in no password;
+///   this value is optional and passing a null pointer will result in no password.
+///   The Daedalus wallet is not recovered when a password is given;
 /// * password_length: the length of the password;
 /// * wallet_out: a pointer to a pointer. The recovered wallet will be allocated on this pointer;
 ///
@@ -62,6 +74,7 @@ pub const FRAGMENT_ID_LENGTH: usize = 32;
 /// The function may fail if:
 ///
 /// * the mnemonics are not valid (invalid length or checksum);
+/// * the mnemonics are valid in more than one language;
 /// * the `wallet_out` is null pointer
 ///
 pub unsafe fn wallet_recover(
@@ -77,7 +90,8 @@ pub unsafe fn wallet_recover(
     };
 
     let result = if !password.is_null() && password_length > 0 {
-        todo!()
+        let password = std::slice::from_raw_parts(password, password_length);
+        Wallet::recover(mnemonics, password)
     } else {
         Wallet::recover(mnemonics, &[])
     };
@@ -364,6 +378,199 @@ pub unsafe fn wallet_confirm_transaction(wallet: WalletPtr, fragment_id: *const
     Result::success()
 }
 
+/// set how many blocks deep a transaction needs to be to be confirmed by
+/// `wallet_apply_block` and after how many blocks a pending transaction that
+/// did not appear in a block is reported as expired
+///
+/// This resets the blocks applied so far.
+///
+/// # Safety
+///
+/// This function dereference raw pointers (wallet). Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors.
+///
+pub unsafe fn wallet_set_confirmation_settings(
+    wallet: WalletPtr,
+    depth: u32,
+    deadline: u32,
+) -> Result {
+    let wallet = non_null_mut!(wallet);
+
+    wallet.set_confirmation_settings(depth, deadline);
+
+    Result::success()
+}
+
+/// apply a new block of the blockchain to the wallet
+///
+/// The pending transactions of the wallet that are in the block are
+/// confirmed once the block is deep enough (see
+/// `wallet_set_confirmation_settings`). If the parent of the block is not
+/// the previous block applied, the blocks of the previous branch are
+/// considered rolled back.
+///
+/// # Parameters
+///
+/// * `header_id`: the identifier of the block, `HEADER_ID_LENGTH` bytes;
+/// * `parent_id`: the identifier of the parent of the block, `HEADER_ID_LENGTH` bytes;
+/// * `chain_length`: the chain length of the block;
+/// * `epoch` and `slot`: the date of the block;
+/// * `fragment_ids`: the identifiers of the fragments of the block,
+///   `fragment_ids_length` identifiers of `FRAGMENT_ID_LENGTH` bytes each;
+/// * `update_out`: the transactions confirmed and the pending transactions
+///   that expired with this block, see `block_update_confirmed_get` and
+///   `block_update_expired_get`. Don't forget to delete it with
+///   `wallet_delete_block_update`;
+///
+/// # Errors
+///
+/// This function may fail upon receiving a null pointer, if the parent of
+/// the block has not been applied or if the chain length does not follow
+/// the chain length of the parent.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn wallet_apply_block(
+    wallet: WalletPtr,
+    header_id: *const u8,
+    parent_id: *const u8,
+    chain_length: u32,
+    epoch: u32,
+    slot: u32,
+    fragment_ids: *const u8,
+    fragment_ids_length: usize,
+    update_out: *mut BlockUpdatePtr,
+) -> Result {
+    let wallet = non_null_mut!(wallet);
+    let header_id = non_null_array!(header_id, HEADER_ID_LENGTH);
+    let parent_id = non_null_array!(parent_id, HEADER_ID_LENGTH);
+    let update_out = non_null_mut!(update_out);
+
+    let fragment_ids = if fragment_ids_length == 0 {
+        &[]
+    } else {
+        non_null_array!(fragment_ids, fragment_ids_length * FRAGMENT_ID_LENGTH)
+    };
+
+    let header_id: [u8; HEADER_ID_LENGTH] = header_id.try_into().unwrap();
+    let parent_id: [u8; HEADER_ID_LENGTH] = parent_id.try_into().unwrap();
+    let fragment_ids = fragment_ids.chunks(FRAGMENT_ID_LENGTH).map(|id| {
+        let id: [u8; FRAGMENT_ID_LENGTH] = id.try_into().unwrap();
+        id.into()
+    });
+
+    match wallet.apply_block(
+        header_id.into(),
+        parent_id.into(),
+        chain_length,
+        BlockDate {
+            epoch,
+            slot_id: slot,
+        },
+        fragment_ids,
+    ) {
+        Ok(update) => {
+            *update_out = Box::into_raw(Box::new(update));
+            Result::success()
+        }
+        Err(err) => err.into(),
+    }
+}
+
+/// the number of transactions confirmed by the block update
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn block_update_confirmed_size(update: BlockUpdatePtr) -> usize {
+    update
+        .as_ref()
+        .map(|u| u.confirmed().len())
+        .unwrap_or_default()
+}
+
+/// retrieve the index-nth transaction id confirmed by the block update,
+/// `FRAGMENT_ID_LENGTH` bytes
+///
+/// the memory returned is not owned and should not be kept for longer
+/// than potential call to `wallet_delete_block_update`
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn block_update_confirmed_get(
+    update: BlockUpdatePtr,
+    index: usize,
+    id_out: *mut *const u8,
+) -> Result {
+    let update = non_null!(update);
+
+    fragment_id_get(update.confirmed(), index, id_out)
+}
+
+/// the number of pending transactions that expired with the block update
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn block_update_expired_size(update: BlockUpdatePtr) -> usize {
+    update
+        .as_ref()
+        .map(|u| u.expired().len())
+        .unwrap_or_default()
+}
+
+/// retrieve the index-nth pending transaction id that expired with the
+/// block update, `FRAGMENT_ID_LENGTH` bytes
+///
+/// the memory returned is not owned and should not be kept for longer
+/// than potential call to `wallet_delete_block_update`
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn block_update_expired_get(
+    update: BlockUpdatePtr,
+    index: usize,
+    id_out: *mut *const u8,
+) -> Result {
+    let update = non_null!(update);
+
+    fragment_id_get(update.expired(), index, id_out)
+}
+
+unsafe fn fragment_id_get(
+    fragment_ids: &[chain_impl_mockchain::fragment::FragmentId],
+    index: usize,
+    id_out: *mut *const u8,
+) -> Result {
+    let id_out = non_null_mut!(id_out);
+
+    if let Some(id) = fragment_ids.get(index) {
+        *id_out = id.as_ref().as_ptr();
+        Result::success()
+    } else {
+        Error::invalid_input("index").with(OutOfBound).into()
+    }
+}
+
 /// once funds have been retrieved with `iohk_jormungandr_wallet_retrieve_funds`
 /// it is possible to convert all existing funds to the new wallet.
 ///
@@ -508,6 +715,40 @@ pub unsafe fn wallet_convert_ignored(
     }
 }
 
+/// the legacy wallet schemes recovered along with the account, the funds
+/// of the schemes that were not recovered are not retrieved
+///
+/// `daedalus_out` is set to `true` if the Daedalus wallet was recovered,
+/// it is not recovered when the mnemonics are protected with a password.
+/// `yoroi_out` is set to `true` if the Yoroi wallet was recovered.
+///
+/// # Errors
+///
+/// * this function may fail if the wallet pointer is null;
+/// * this function may fail if `daedalus_out` or `yoroi_out` is null;
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn wallet_recovered_schemes(
+    wallet: WalletPtr,
+    daedalus_out: *mut bool,
+    yoroi_out: *mut bool,
+) -> Result {
+    let wallet = non_null!(wallet);
+    let daedalus_out = non_null_mut!(daedalus_out);
+    let yoroi_out = non_null_mut!(yoroi_out);
+
+    let schemes = wallet.recovered_schemes();
+    *daedalus_out = schemes.daedalus;
+    *yoroi_out = schemes.yoroi;
+
+    Result::success()
+}
+
 /// get the total value in the wallet
 ///
 /// make sure to call `retrieve_funds` prior to calling this function
@@ -553,6 +794,9 @@ pub unsafe fn wallet_total_value(wallet: WalletPtr, total_out: *mut u64) -> Resu
 /// transactions otherwise future transactions may fail to be accepted by any
 /// nodes of the blockchain because of invalid signature state.
 ///
+/// The pending transactions that are still valid with the given counter are
+/// kept, the ones using an older spending counter are dropped.
+///
 /// # Errors
 ///
 /// * this function may fail if the wallet pointer is null;
@@ -570,6 +814,294 @@ pub fn wallet_set_state(wallet: WalletPtr, value: u64, counter: u32) -> Result {
     Result::success()
 }
 
+/// synchronise the wallet account state with the value and the spending
+/// counter of the account as reported by the node
+///
+/// Same as `wallet_set_state` but the reconciliation report is returned in
+/// `reconciliation_out`: how the node's state compares to the local state,
+/// the pending transactions that have been dropped and the transactions
+/// confirmed locally that are part of the node's state. Don't forget to
+/// delete it with `wallet_delete_reconciliation`.
+///
+/// # Errors
+///
+/// * this function may fail if the wallet pointer or `reconciliation_out` is null;
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors.
+///
+pub unsafe fn wallet_reconcile_state(
+    wallet: WalletPtr,
+    value: u64,
+    counter: u32,
+    reconciliation_out: *mut ReconciliationPtr,
+) -> Result {
+    let wallet = non_null_mut!(wallet);
+    let reconciliation_out = non_null_mut!(reconciliation_out);
+
+    let reconciliation = wallet.set_state(Value(value), counter);
+    *reconciliation_out = Box::into_raw(Box::new(reconciliation));
+
+    Result::success()
+}
+
+/// how the node's spending counter of the account compares to the local
+/// confirmed spending counter
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn reconciliation_status(
+    reconciliation: ReconciliationPtr,
+    status_out: *mut SyncStatus,
+) -> Result {
+    let reconciliation = non_null!(reconciliation);
+    let status_out = non_null_mut!(status_out);
+
+    *status_out = reconciliation.status();
+
+    Result::success()
+}
+
+/// the number of pending transactions dropped by the reconciliation
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn reconciliation_dropped_size(reconciliation: ReconciliationPtr) -> usize {
+    reconciliation
+        .as_ref()
+        .map(|r| r.dropped().len())
+        .unwrap_or_default()
+}
+
+/// retrieve the index-nth transaction id dropped by the reconciliation,
+/// `FRAGMENT_ID_LENGTH` bytes
+///
+/// the memory returned is not owned and should not be kept for longer
+/// than potential call to `wallet_delete_reconciliation`
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn reconciliation_dropped_get(
+    reconciliation: ReconciliationPtr,
+    index: usize,
+    id_out: *mut *const u8,
+) -> Result {
+    let reconciliation = non_null!(reconciliation);
+
+    fragment_id_get(reconciliation.dropped(), index, id_out)
+}
+
+/// the number of transactions confirmed locally that are part of the
+/// node's state
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn reconciliation_confirmed_size(reconciliation: ReconciliationPtr) -> usize {
+    reconciliation
+        .as_ref()
+        .map(|r| r.confirmed().len())
+        .unwrap_or_default()
+}
+
+/// retrieve the index-nth transaction id confirmed locally that is part of
+/// the node's state, `FRAGMENT_ID_LENGTH` bytes
+///
+/// the memory returned is not owned and should not be kept for longer
+/// than potential call to `wallet_delete_reconciliation`
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn reconciliation_confirmed_get(
+    reconciliation: ReconciliationPtr,
+    index: usize,
+    id_out: *mut *const u8,
+) -> Result {
+    let reconciliation = non_null!(reconciliation);
+
+    fragment_id_get(reconciliation.confirmed(), index, id_out)
+}
+
+/// check the mnemonics entered so far by the user, see `check_mnemonics`
+///
+/// The words followed by a space (or an ideographic space) are complete and
+/// may be given by their prefix, the last word, if not followed by a space,
+/// is the word being typed. The completions of a single word are then the
+/// completions of the entry of its prefix, and the word a prefix stands for
+/// is the only word of the entry of the prefix followed by a space.
+///
+/// The entry is returned in `entry_out`, delete it with
+/// `wallet_delete_mnemonics_entry`.
+///
+/// # parameters
+///
+/// * language: the name of one of `bip39::dictionary::DEFAULT_DICTIONARIES`
+///   (`english`, `japanese`, `chinese-simplified`...);
+/// * mnemonics: a utf8 string (normalized to NFKD by the function);
+/// * entry_out: a pointer to a pointer, the entry will be allocated on this pointer;
+///
+/// # errors
+///
+/// * the language is not known;
+/// * a complete word does not resolve to exactly one word of the dictionary;
+/// * no word completes the word being typed;
+/// * there are more words than the longest mnemonics (24 words);
+/// * the `entry_out` is null pointer;
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn wallet_check_mnemonics(
+    language: &str,
+    mnemonics: &str,
+    entry_out: *mut MnemonicsEntryPtr,
+) -> Result {
+    let entry_out = non_null_mut!(entry_out);
+
+    match crate::check_mnemonics(language, mnemonics) {
+        Ok(entry) => {
+            *entry_out = Box::into_raw(Box::new(entry));
+            Result::success()
+        }
+        Err(err) => err.into(),
+    }
+}
+
+/// `true` if the complete words of the entry are mnemonics of a supported
+/// length with a valid checksum, they can be used to recover a wallet
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn mnemonics_entry_is_valid(entry: MnemonicsEntryPtr, valid_out: *mut bool) -> Result {
+    let entry = non_null!(entry);
+    let valid_out = non_null_mut!(valid_out);
+
+    *valid_out = entry.is_valid();
+
+    Result::success()
+}
+
+/// the number of complete words of the entry
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn mnemonics_entry_words_size(entry: MnemonicsEntryPtr) -> usize {
+    entry
+        .as_ref()
+        .map(|entry| entry.words().len())
+        .unwrap_or_default()
+}
+
+/// retrieve the index-nth complete word of the entry, resolved from its
+/// prefix, as a utf8 string of `word_length_out` bytes (not null terminated)
+///
+/// the memory returned is the dictionary's, it is not owned and must not be
+/// freed
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn mnemonics_entry_words_get(
+    entry: MnemonicsEntryPtr,
+    index: usize,
+    word_out: *mut *const u8,
+    word_length_out: *mut usize,
+) -> Result {
+    let entry = non_null!(entry);
+
+    word_get(entry.words(), index, word_out, word_length_out)
+}
+
+/// the number of words completing the word being typed, 0 if no word is
+/// being typed
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn mnemonics_entry_completions_size(entry: MnemonicsEntryPtr) -> usize {
+    entry
+        .as_ref()
+        .map(|entry| entry.completions().len())
+        .unwrap_or_default()
+}
+
+/// retrieve the index-nth word completing the word being typed, in the
+/// order of the dictionary, see `mnemonics_entry_words_get`
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+pub unsafe fn mnemonics_entry_completions_get(
+    entry: MnemonicsEntryPtr,
+    index: usize,
+    word_out: *mut *const u8,
+    word_length_out: *mut usize,
+) -> Result {
+    let entry = non_null!(entry);
+
+    word_get(entry.completions(), index, word_out, word_length_out)
+}
+
+unsafe fn word_get(
+    words: &[&'static str],
+    index: usize,
+    word_out: *mut *const u8,
+    word_length_out: *mut usize,
+) -> Result {
+    let word_out = non_null_mut!(word_out);
+    let word_length_out = non_null_mut!(word_length_out);
+
+    if let Some(word) = words.get(index) {
+        *word_out = word.as_ptr();
+        *word_length_out = word.len();
+        Result::success()
+    } else {
+        Error::invalid_input("index").with(OutOfBound).into()
+    }
+}
+
 /// build the vote cast transaction
 ///
 /// # Errors
@@ -628,6 +1160,237 @@ pub unsafe fn wallet_vote_cast(
     Result::success()
 }
 
+/// build a transaction sending `value` to the given `address` from the
+/// wallet's account
+///
+/// The fees are paid by the account on top of the `value`. The returned
+/// transaction is added to the pending transactions of the wallet.
+///
+/// # Parameters
+///
+/// * `address`: the binary encoded address to send the funds to;
+/// * `address_length`: the length of the `address` buffer;
+/// * `value`: the amount of lovelace to send;
+///
+/// # Errors
+///
+/// This function may fail upon receiving a null pointer, an invalid address
+/// or if the account does not have enough funds to pay for the `value` and
+/// the fees.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+pub unsafe fn wallet_send(
+    wallet: WalletPtr,
+    settings: SettingsPtr,
+    address: *const u8,
+    address_length: usize,
+    value: u64,
+    transaction_out: *mut *const u8,
+    len_out: *mut usize,
+) -> Result {
+    let wallet = non_null_mut!(wallet);
+    let settings = non_null!(settings).clone();
+    let address = non_null_array!(address, address_length);
+
+    if transaction_out.is_null() {
+        return Error::invalid_input("transaction_out").with(NulPtr).into();
+    }
+    if len_out.is_null() {
+        return Error::invalid_input("len_out").with(NulPtr).into();
+    }
+
+    let address = match chain_addr::Address::from_bytes(address) {
+        Ok(address) => address,
+        Err(err) => return Error::invalid_input("address").with(err).into(),
+    };
+
+    let transaction = match wallet.send(settings, address, Value(value)) {
+        Ok(transaction) => Box::leak(transaction),
+        Err(err) => return err.into(),
+    };
+
+    *transaction_out = transaction.as_ptr();
+    *len_out = transaction.len();
+
+    Result::success()
+}
+
+/// delegate the stake of the wallet's account to the given pools
+///
+/// The fees are paid by the account. The returned transaction is added to
+/// the pending transactions of the wallet.
+///
+/// # Parameters
+///
+/// * `pools`: the identifiers of the pools, `pools_length` identifiers of
+///   `POOL_ID_LENGTH` bytes each;
+/// * `ratios`: the ratio of the stake to delegate to each of the pools,
+///   `pools_length` bytes;
+/// * `pools_length`: the number of pools, `0` to remove the delegation (the
+///   `pools` and `ratios` pointers are then ignored). If there is only one
+///   pool all the stake is delegated to it;
+/// * `owner`: build an owner stake delegation certificate if `true`, a stake
+///   delegation certificate signed by the account otherwise;
+///
+/// # Errors
+///
+/// This function may fail upon receiving a null pointer, invalid ratios
+/// or if the account does not have enough funds to pay for the fees.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn wallet_delegate(
+    wallet: WalletPtr,
+    settings: SettingsPtr,
+    pools: *const u8,
+    ratios: *const u8,
+    pools_length: usize,
+    owner: bool,
+    transaction_out: *mut *const u8,
+    len_out: *mut usize,
+) -> Result {
+    let wallet = non_null_mut!(wallet);
+    let settings = non_null!(settings).clone();
+
+    if transaction_out.is_null() {
+        return Error::invalid_input("transaction_out").with(NulPtr).into();
+    }
+    if len_out.is_null() {
+        return Error::invalid_input("len_out").with(NulPtr).into();
+    }
+
+    let pools: Vec<(PoolId, u8)> = if pools_length == 0 {
+        Vec::new()
+    } else {
+        let pools = non_null_array!(pools, pools_length * POOL_ID_LENGTH);
+        let ratios = non_null_array!(ratios, pools_length);
+
+        pools
+            .chunks(POOL_ID_LENGTH)
+            .zip(ratios)
+            .map(|(pool, ratio)| {
+                let pool: [u8; POOL_ID_LENGTH] = pool.try_into().unwrap();
+                (pool.into(), *ratio)
+            })
+            .collect()
+    };
+
+    let transaction = match wallet.delegate(settings, &pools, owner) {
+        Ok(transaction) => Box::leak(transaction),
+        Err(err) => return err.into(),
+    };
+
+    *transaction_out = transaction.as_ptr();
+    *len_out = transaction.len();
+
+    Result::success()
+}
+
+/// export the whole state of the wallet (keys, UTxOs, account state and
+/// pending transactions) so it can be restored later with `wallet_import`
+///
+/// # Parameters
+///
+/// * `password`: optional password to encrypt the exported wallet with,
+///   passing a null pointer will result in no encryption;
+/// * `password_length`: the length of the `password`;
+/// * `bytes_out`: used to return a pointer to the exported wallet;
+/// * `len_out`: used to return the length of the exported wallet;
+///
+/// The returned buffer is in the heap, so make sure to call the delete_buffer function
+///
+/// # Errors
+///
+/// This function may fail upon receiving a null pointer or if the
+/// encryption failed.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+pub unsafe fn wallet_export(
+    wallet: WalletPtr,
+    password: *const u8,
+    password_length: usize,
+    bytes_out: *mut *const u8,
+    len_out: *mut usize,
+) -> Result {
+    let wallet = non_null!(wallet);
+    let bytes_out = non_null_mut!(bytes_out);
+    let len_out = non_null_mut!(len_out);
+
+    let password = if !password.is_null() && password_length > 0 {
+        Some(std::slice::from_raw_parts(password, password_length))
+    } else {
+        None
+    };
+
+    let bytes = match wallet.export(password) {
+        Ok(bytes) => Box::leak(bytes),
+        Err(err) => return err.into(),
+    };
+
+    *bytes_out = bytes.as_ptr();
+    *len_out = bytes.len();
+
+    Result::success()
+}
+
+/// import a wallet exported with `wallet_export`
+///
+/// # Parameters
+///
+/// * `bytes`: the exported wallet;
+/// * `bytes_length`: the length of the exported wallet;
+/// * `password`: the password the wallet was exported with, if any.
+///   Passing a null pointer will result in no password;
+/// * `password_length`: the length of the `password`;
+/// * `wallet_out`: a pointer to a pointer. The imported wallet will be allocated on this pointer;
+///
+/// # Errors
+///
+/// This function may fail upon receiving a null pointer, if the password is
+/// missing or invalid or if the exported wallet is malformed.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though the function checks if
+/// the pointers are null. Mind not to put random values in or you may see
+/// unexpected behaviors.
+pub unsafe fn wallet_import(
+    bytes: *const u8,
+    bytes_length: usize,
+    password: *const u8,
+    password_length: usize,
+    wallet_out: *mut WalletPtr,
+) -> Result {
+    let bytes = non_null_array!(bytes, bytes_length);
+    let wallet_out = non_null_mut!(wallet_out);
+
+    let password = if !password.is_null() && password_length > 0 {
+        Some(std::slice::from_raw_parts(password, password_length))
+    } else {
+        None
+    };
+
+    match Wallet::import(bytes, password) {
+        Ok(wallet) => {
+            *wallet_out = Box::into_raw(Box::new(wallet));
+            Result::success()
+        }
+        Err(err) => err.into(),
+    }
+}
+
 /// decrypt payload of the wallet transfer protocol
 ///
 /// Parameters
@@ -713,6 +1476,33 @@ pub fn wallet_delete_conversion(conversion: ConversionPtr) {
     }
 }
 
+/// delete the pointer
+pub fn wallet_delete_block_update(update: BlockUpdatePtr) {
+    if !update.is_null() {
+        let boxed = unsafe { Box::from_raw(update) };
+
+        std::mem::drop(boxed);
+    }
+}
+
+/// delete the pointer
+pub fn wallet_delete_reconciliation(reconciliation: ReconciliationPtr) {
+    if !reconciliation.is_null() {
+        let boxed = unsafe { Box::from_raw(reconciliation) };
+
+        std::mem::drop(boxed);
+    }
+}
+
+/// delete the pointer
+pub fn wallet_delete_mnemonics_entry(entry: MnemonicsEntryPtr) {
+    if !entry.is_null() {
+        let boxed = unsafe { Box::from_raw(entry) };
+
+        std::mem::drop(boxed);
+    }
+}
+
 /// delete the pointer
 pub fn wallet_delete_proposal(proposal: ProposalPtr) {
     if !proposal.is_null() {
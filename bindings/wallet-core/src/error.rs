@@ -57,6 +57,14 @@ pub enum ErrorCode {
 
     /// authentication failed
     SymmetricCipherInvalidPassword = 7,
+
+    /// the exported wallet cannot be imported, it is malformed or
+    /// of an unsupported version
+    WalletPersistence = 8,
+
+    /// the mnemonics are valid in more than one language, see the details
+    /// for the candidate languages
+    AmbiguousMnemonicLanguage = 9,
 }
 
 #[derive(Debug)]
@@ -86,6 +94,13 @@ pub enum ErrorKind {
 
     /// authentication failed
     SymmetricCipherInvalidPassword,
+
+    /// the exported wallet cannot be imported
+    WalletPersistence,
+
+    /// the language of the mnemonics cannot be detected, they are valid in
+    /// more than one language
+    AmbiguousMnemonicLanguage,
 }
 
 impl ErrorKind {
@@ -102,6 +117,8 @@ impl ErrorKind {
             Self::WalletTransactionBuilding => ErrorCode::WalletTransactionBuilding,
             Self::SymmetricCipherError => ErrorCode::SymmetricCipherError,
             Self::SymmetricCipherInvalidPassword => ErrorCode::SymmetricCipherInvalidPassword,
+            Self::WalletPersistence => ErrorCode::WalletPersistence,
+            Self::AmbiguousMnemonicLanguage => ErrorCode::AmbiguousMnemonicLanguage,
         }
     }
 }
@@ -182,6 +199,20 @@ impl Error {
         }
     }
 
+    pub fn wallet_persistence() -> Self {
+        Self {
+            kind: ErrorKind::WalletPersistence,
+            details: None,
+        }
+    }
+
+    pub fn ambiguous_mnemonic_language() -> Self {
+        Self {
+            kind: ErrorKind::AmbiguousMnemonicLanguage,
+            details: None,
+        }
+    }
+
     pub fn symmetric_cipher_error(err: symmetric_cipher::Error) -> Self {
         let kind = match err {
             symmetric_cipher::Error::AuthenticationFailed => {
@@ -337,6 +368,9 @@ impl Display for ErrorKind {
             ),
             Self::SymmetricCipherError => f.write_str("malformed encryption or decryption payload"),
             Self::SymmetricCipherInvalidPassword => f.write_str("invalid decryption password"),
+            Self::WalletPersistence => {
+                f.write_str("The exported wallet is malformed or of an unsupported version")
+            }
         }
     }
 }
@@ -1,17 +1,25 @@
 pub mod c;
 mod conversion;
 mod error;
+mod mnemonics;
+mod persistence;
 mod vote;
 mod wallet;
+mod watch_only;
 
 pub use self::{
     conversion::Conversion,
     error::{Error, ErrorCode, ErrorKind, Result},
+    mnemonics::{check_mnemonics, mnemonic_completions, resolve_mnemonic_prefix, MnemonicsEntry},
     vote::Proposal,
-    wallet::Wallet,
+    wallet::{RecoveredSchemes, Wallet},
+    watch_only::WatchOnlyWallet,
 };
-pub use ::wallet::Settings;
+pub use ::wallet::{BlockUpdate, Reconciliation, Settings, SyncStatus};
+pub use chain_addr::Address;
 pub use chain_impl_mockchain::{
+    block::{BlockDate, HeaderId},
+    certificate::PoolId,
     fragment::FragmentId,
     value::Value,
     vote::{Choice, Options, PayloadType},
@@ -0,0 +1,160 @@
+use crate::Error;
+use bip39::{
+    completion,
+    dictionary::{self, DefaultDictionary, Language as _},
+    MnemonicIndex,
+};
+
+/// the mnemonics entered so far by the user, see `check_mnemonics`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MnemonicsEntry {
+    words: Vec<&'static str>,
+    completions: Vec<&'static str>,
+    valid: bool,
+}
+
+impl MnemonicsEntry {
+    /// the complete words, resolved from their prefix
+    pub fn words(&self) -> &[&'static str] {
+        &self.words
+    }
+
+    /// the words completing the word being typed, empty if no word is
+    /// being typed
+    pub fn completions(&self) -> &[&'static str] {
+        &self.completions
+    }
+
+    /// `true` if the complete words are mnemonics of a supported length
+    /// with a valid checksum, they can be used to recover a wallet
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+}
+
+/// the words of the dictionary of the given language starting with the
+/// given prefix, in the order of the dictionary
+///
+/// # parameters
+///
+/// * language: the name of one of `bip39::dictionary::DEFAULT_DICTIONARIES`
+///   (`english`, `japanese`, `chinese-simplified`...);
+/// * prefix: a utf8 string (normalized to NFKD by the function);
+///
+/// # errors
+///
+/// * the language is not known;
+///
+pub fn mnemonic_completions(language: &str, prefix: &str) -> Result<Vec<&'static str>, Error> {
+    let dic = dictionary(language)?;
+
+    Ok(words(dic, &dic.lookup_prefix(prefix)))
+}
+
+/// the word of the dictionary of the given language the prefix stands for:
+/// the word equal to the prefix or else the only word starting with it
+///
+/// see `mnemonic_completions` for the parameters
+///
+/// # errors
+///
+/// * the language is not known;
+/// * no word, or more than one word, starts with the prefix;
+///
+pub fn resolve_mnemonic_prefix(language: &str, prefix: &str) -> Result<&'static str, Error> {
+    let dic = dictionary(language)?;
+    let index = dic
+        .resolve_prefix(prefix)
+        .map_err(|err| Error::invalid_input("prefix").with(err))?;
+
+    Ok(dic.words[index.0 as usize])
+}
+
+/// check the mnemonics entered so far by the user
+///
+/// The words followed by a space (or an ideographic space) are complete and
+/// may be given by their prefix. The last word, if not followed by a space,
+/// is the word being typed and is completed.
+///
+/// # parameters
+///
+/// * language: the name of one of `bip39::dictionary::DEFAULT_DICTIONARIES`;
+/// * mnemonics: a utf8 string (normalized to NFKD by the function);
+///
+/// # errors
+///
+/// * the language is not known;
+/// * a complete word does not resolve to exactly one word of the dictionary;
+/// * no word completes the word being typed;
+/// * there are more words than the longest mnemonics (24 words);
+///
+pub fn check_mnemonics(language: &str, mnemonics: &str) -> Result<MnemonicsEntry, Error> {
+    let dic = dictionary(language)?;
+    let partial = completion::check_partial(dic, mnemonics)
+        .map_err(|err| Error::invalid_input("mnemonics").with(err))?;
+
+    Ok(MnemonicsEntry {
+        words: words(dic, partial.words()),
+        completions: words(dic, partial.completions()),
+        valid: partial.mnemonics().is_some(),
+    })
+}
+
+fn dictionary(language: &str) -> Result<&'static DefaultDictionary, Error> {
+    dictionary::DEFAULT_DICTIONARIES
+        .iter()
+        .copied()
+        .find(|dic| dic.name == language)
+        .ok_or_else(|| Error::invalid_input("language"))
+}
+
+fn words(dic: &'static DefaultDictionary, indices: &[MnemonicIndex]) -> Vec<&'static str> {
+    indices
+        .iter()
+        .map(|index| dic.words[index.0 as usize])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONICS: &str =
+        "legal winner thank year wave sausage worth useful legal winner thank yellow";
+
+    #[test]
+    fn complete_words() {
+        assert_eq!(
+            mnemonic_completions("english", "abo").unwrap(),
+            vec!["about", "above"]
+        );
+        assert!(mnemonic_completions("english", "xx").unwrap().is_empty());
+        assert!(mnemonic_completions("klingon", "abo").is_err());
+
+        assert_eq!(
+            resolve_mnemonic_prefix("english", "aban").unwrap(),
+            "abandon"
+        );
+        assert_eq!(resolve_mnemonic_prefix("english", "act").unwrap(), "act");
+        assert!(resolve_mnemonic_prefix("english", "ab").is_err());
+    }
+
+    #[test]
+    fn check_mnemonics_entry() {
+        let entry = check_mnemonics("english", "legal winn").unwrap();
+        assert_eq!(entry.words(), &["legal"]);
+        assert_eq!(entry.completions(), &["winner"]);
+        assert!(!entry.is_valid());
+
+        let prefixes: Vec<String> = MNEMONICS
+            .split(' ')
+            .map(|word| word.chars().take(4).collect())
+            .collect();
+        let entry = check_mnemonics("english", &(prefixes.join(" ") + " ")).unwrap();
+        assert_eq!(entry.words().join(" "), MNEMONICS);
+        assert!(entry.completions().is_empty());
+        assert!(entry.is_valid());
+
+        assert!(check_mnemonics("english", "legal xx").is_err());
+    }
+}
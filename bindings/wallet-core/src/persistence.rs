@@ -0,0 +1,243 @@
+//! container of the exported wallets
+//!
+//! The exported bytes start with the version of the container and a flag
+//! telling if the content is encrypted (with the `symmetric-cipher`). The
+//! content is the list of the exported sub-wallets (see the persistence of
+//! the `wallet` crate), each of them prefixed with its length.
+//!
+//! Before encryption the content is prefixed with its length and padded
+//! with zeros to a multiple of 64 bytes, as expected by the cipher.
+//!
+//! The version 2 adds the section of the confirmation settings after the
+//! sub-wallets, the wallets exported with the version 1 are still imported
+//! with the default settings.
+
+use crate::Error;
+use std::convert::TryInto;
+use thiserror::Error;
+
+const VERSION: u8 = 2;
+/// the version without the confirmation settings
+const VERSION_1: u8 = 1;
+const PLAIN: u8 = 0;
+const ENCRYPTED: u8 = 1;
+const BLOCK_SIZE: usize = 64;
+
+#[derive(Debug, Error)]
+enum ContainerError {
+    #[error("unsupported exported wallet version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("the exported wallet is encrypted, a password is required")]
+    MissingPassword,
+
+    #[error("the exported wallet is malformed")]
+    Malformed,
+}
+
+/// the exported sub-wallets
+pub(crate) struct Exported {
+    pub account: Vec<u8>,
+    pub daedalus: Option<Vec<u8>>,
+    pub icarus: Option<Vec<u8>>,
+    pub free_keys: Vec<u8>,
+    /// `None` for the wallets exported before the confirmation settings
+    pub confirmations: Option<Vec<u8>>,
+}
+
+impl Exported {
+    pub(crate) fn encode(&self, password: Option<&[u8]>) -> Result<Box<[u8]>, Error> {
+        let mut content = Vec::new();
+        write_section(&mut content, Some(&self.account));
+        write_section(&mut content, self.daedalus.as_deref());
+        write_section(&mut content, self.icarus.as_deref());
+        write_section(&mut content, Some(&self.free_keys));
+        write_section(&mut content, self.confirmations.as_deref());
+
+        let mut bytes = vec![VERSION];
+        if let Some(password) = password {
+            let mut padded = Vec::with_capacity(content.len() + 4 + BLOCK_SIZE);
+            padded.extend_from_slice(&(content.len() as u32).to_be_bytes());
+            padded.extend_from_slice(&content);
+            let padding = (BLOCK_SIZE - padded.len() % BLOCK_SIZE) % BLOCK_SIZE;
+            padded.resize(padded.len() + padding, 0);
+
+            let encrypted = symmetric_cipher::encrypt(password, &padded, rand::rngs::OsRng)
+                .map_err(Error::symmetric_cipher_error)?;
+
+            bytes.push(ENCRYPTED);
+            bytes.extend_from_slice(&encrypted);
+        } else {
+            bytes.push(PLAIN);
+            bytes.extend_from_slice(&content);
+        }
+
+        Ok(bytes.into_boxed_slice())
+    }
+
+    pub(crate) fn decode(bytes: &[u8], password: Option<&[u8]>) -> Result<Self, Error> {
+        let malformed = || Error::wallet_persistence().with(ContainerError::Malformed);
+
+        let (version, bytes) = bytes.split_first().ok_or_else(malformed)?;
+        if *version != VERSION && *version != VERSION_1 {
+            return Err(
+                Error::wallet_persistence().with(ContainerError::UnsupportedVersion(*version))
+            );
+        }
+
+        let decrypted;
+        let mut content = match bytes.split_first() {
+            Some((&PLAIN, content)) => content,
+            Some((&ENCRYPTED, encrypted)) => {
+                let password = password.ok_or_else(|| {
+                    Error::wallet_persistence().with(ContainerError::MissingPassword)
+                })?;
+                decrypted = symmetric_cipher::decrypt(password, encrypted)
+                    .map_err(Error::symmetric_cipher_error)?;
+
+                let (len, content) = split_length(&decrypted).ok_or_else(malformed)?;
+                content.get(..len).ok_or_else(malformed)?
+            }
+            _ => return Err(malformed()),
+        };
+
+        let account = read_section(&mut content)?.ok_or_else(malformed)?;
+        let daedalus = read_section(&mut content)?;
+        let icarus = read_section(&mut content)?;
+        let free_keys = read_section(&mut content)?.ok_or_else(malformed)?;
+        let confirmations = if *version == VERSION_1 {
+            None
+        } else {
+            Some(read_section(&mut content)?.ok_or_else(malformed)?)
+        };
+
+        if !content.is_empty() {
+            return Err(malformed());
+        }
+
+        Ok(Self {
+            account,
+            daedalus,
+            icarus,
+            free_keys,
+            confirmations,
+        })
+    }
+}
+
+fn write_section(content: &mut Vec<u8>, section: Option<&[u8]>) {
+    if let Some(section) = section {
+        content.push(1);
+        content.extend_from_slice(&(section.len() as u32).to_be_bytes());
+        content.extend_from_slice(section);
+    } else {
+        content.push(0);
+    }
+}
+
+fn read_section(content: &mut &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    let malformed = || Error::wallet_persistence().with(ContainerError::Malformed);
+
+    let (present, remaining) = content.split_first().ok_or_else(malformed)?;
+    match present {
+        0 => {
+            *content = remaining;
+            Ok(None)
+        }
+        1 => {
+            let (len, remaining) = split_length(remaining).ok_or_else(malformed)?;
+            if remaining.len() < len {
+                return Err(malformed());
+            }
+            let (section, remaining) = remaining.split_at(len);
+            *content = remaining;
+            Ok(Some(section.to_vec()))
+        }
+        _ => Err(malformed()),
+    }
+}
+
+fn split_length(bytes: &[u8]) -> Option<(usize, &[u8])> {
+    if bytes.len() < 4 {
+        return None;
+    }
+    let (len, remaining) = bytes.split_at(4);
+    let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+    Some((len, remaining))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    fn exported() -> Exported {
+        Exported {
+            account: vec![1, 2, 3],
+            daedalus: None,
+            icarus: Some(vec![4; 100]),
+            free_keys: vec![],
+            confirmations: Some(vec![5, 6]),
+        }
+    }
+
+    #[test]
+    fn encode_decode_plain() {
+        let bytes = exported().encode(None).unwrap();
+        let decoded = Exported::decode(&bytes, None).unwrap();
+
+        assert_eq!(decoded.account, vec![1, 2, 3]);
+        assert_eq!(decoded.daedalus, None);
+        assert_eq!(decoded.icarus, Some(vec![4; 100]));
+        assert!(decoded.free_keys.is_empty());
+        assert_eq!(decoded.confirmations, Some(vec![5, 6]));
+    }
+
+    #[test]
+    fn decode_version_1() {
+        let mut bytes = vec![VERSION_1, PLAIN];
+        write_section(&mut bytes, Some(&[1, 2, 3]));
+        write_section(&mut bytes, None);
+        write_section(&mut bytes, Some(&[4; 100]));
+        write_section(&mut bytes, Some(&[]));
+
+        let decoded = Exported::decode(&bytes, None).unwrap();
+        assert_eq!(decoded.account, vec![1, 2, 3]);
+        assert_eq!(decoded.icarus, Some(vec![4; 100]));
+        assert_eq!(decoded.confirmations, None);
+
+        // the version 2 requires the confirmation settings
+        bytes[0] = VERSION;
+        assert!(matches!(
+            Exported::decode(&bytes, None).err().unwrap().kind(),
+            ErrorKind::WalletPersistence
+        ));
+
+        bytes[0] = VERSION + 1;
+        assert!(matches!(
+            Exported::decode(&bytes, None).err().unwrap().kind(),
+            ErrorKind::WalletPersistence
+        ));
+    }
+
+    #[test]
+    fn encode_decode_encrypted() {
+        let bytes = exported().encode(Some(&b"password"[..])).unwrap();
+
+        assert!(matches!(
+            Exported::decode(&bytes, None).err().unwrap().kind(),
+            ErrorKind::WalletPersistence
+        ));
+        assert!(matches!(
+            Exported::decode(&bytes, Some(&b"wrong"[..]))
+                .err()
+                .unwrap()
+                .kind(),
+            ErrorKind::SymmetricCipherInvalidPassword
+        ));
+
+        let decoded = Exported::decode(&bytes, Some(&b"password"[..])).unwrap();
+        assert_eq!(decoded.account, vec![1, 2, 3]);
+        assert_eq!(decoded.icarus, Some(vec![4; 100]));
+    }
+}
@@ -1,8 +1,10 @@
-use crate::{Conversion, Error, Proposal};
+use crate::{persistence::Exported, Conversion, Error, Proposal};
+use bip39::dictionary::{self, Language as _};
 use chain_core::property::Serialize as _;
 use chain_crypto::SecretKey;
 use chain_impl_mockchain::{
-    block::Block,
+    block::{Block, BlockDate, HeaderId},
+    certificate::PoolId,
     fragment::{Fragment, FragmentId},
     legacy::OldAddress,
     transaction::Input,
@@ -10,7 +12,7 @@ use chain_impl_mockchain::{
     vote::Choice,
 };
 use chain_ser::mempack::{ReadBuf, Readable as _};
-use wallet::{AccountId, Settings};
+use wallet::{AccountId, Password, RecoveryError, Settings};
 
 /// the wallet
 ///
@@ -23,6 +25,18 @@ pub struct Wallet {
     daedalus: Option<wallet::scheme::rindex::Wallet>,
     icarus: Option<wallet::scheme::bip44::Wallet<OldAddress>>,
     free_keys: wallet::scheme::freeutxo::Wallet,
+    confirmations: wallet::Confirmations,
+}
+
+/// the legacy wallet schemes recovered along with the account, see
+/// `Wallet::recovered_schemes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveredSchemes {
+    /// the Daedalus (random indexes) wallet, not recovered when the
+    /// mnemonics are protected with a password
+    pub daedalus: bool,
+    /// the Yoroi (bip44) wallet
+    pub yoroi: bool,
 }
 
 impl Wallet {
@@ -46,32 +60,50 @@ impl Wallet {
     ///
     /// # parameters
     ///
-    /// * mnemonics: a null terminated utf8 string (already normalized NFKD) in english;
-    /// * password: pointer to the password (in bytes, can be UTF8 string or a bytes of anything);
-    ///   this value is optional and passing a null pointer will result in no password;
+    /// * mnemonics: a utf8 string (normalized to NFKD by the function) in any of the languages
+    ///   of `bip39::dictionary::DEFAULT_DICTIONARIES`, the language is detected from
+    ///   the words. The words are separated with spaces or with ideographic spaces;
+    /// * password: the password (in bytes, can be UTF8 string or a bytes of anything);
+    ///   an empty password means no password. The Yoroi wallet and the account are
+    ///   recovered with the password, the Daedalus wallets do not support passwords
+    ///   so it is not recovered (see `Wallet::recovered_schemes`);
     ///
     /// # errors
     ///
     /// The function may fail if:
     ///
     /// * the mnemonics are not valid (invalid length or checksum);
+    /// * the mnemonics are valid in more than one language (the error is then of
+    ///   kind `ErrorKind::AmbiguousMnemonicLanguage`);
     ///
     pub fn recover(mnemonics: &str, password: &[u8]) -> Result<Self, Error> {
+        let dic = dictionary::detect_language(mnemonics).map_err(|err| match err {
+            dictionary::Error::AmbiguousLanguage(_) => {
+                Error::ambiguous_mnemonic_language().with(err)
+            }
+            err => Error::invalid_input("mnemonics").with(err),
+        })?;
+        let mnemonics = dictionary::split_words(mnemonics)
+            .collect::<Vec<_>>()
+            .join(dic.separator());
+
         let builder = wallet::RecoveryBuilder::new();
 
         let builder = builder
-            .mnemonics(&bip39::dictionary::ENGLISH, mnemonics)
+            .mnemonics(dic, mnemonics)
             .map_err(|err| Error::invalid_input("mnemonics").with(err))?;
 
         let builder = if !password.is_empty() {
-            todo!()
+            builder.password(Password::from(password.to_vec()))
         } else {
             builder
         };
 
-        let daedalus = builder
-            .build_daedalus()
-            .map_err(|e| Error::wallet_recovering().with(e))?;
+        let daedalus = match builder.build_daedalus() {
+            Ok(daedalus) => Some(daedalus),
+            Err(RecoveryError::SchemeDoesNotRequirePassword) => None,
+            Err(e) => return Err(Error::wallet_recovering().with(e)),
+        };
 
         let icarus = builder
             .build_yoroi()
@@ -88,9 +120,10 @@ impl Wallet {
 
         Ok(Wallet {
             account,
-            daedalus: Some(daedalus),
+            daedalus,
             icarus: Some(icarus),
             free_keys,
+            confirmations: wallet::Confirmations::default(),
         })
     }
 
@@ -132,9 +165,19 @@ impl Wallet {
             daedalus: None,
             icarus: None,
             free_keys,
+            confirmations: wallet::Confirmations::default(),
         })
     }
 
+    /// the legacy wallet schemes recovered along with the account, the
+    /// funds of the schemes that were not recovered are not retrieved
+    pub fn recovered_schemes(&self) -> RecoveredSchemes {
+        RecoveredSchemes {
+            daedalus: self.daedalus.is_some(),
+            yoroi: self.icarus.is_some(),
+        }
+    }
+
     /// retrieve funds from daedalus or yoroi wallet in the given block0 (or
     /// any other blocks).
     ///
@@ -240,6 +283,79 @@ impl Wallet {
         self.account.confirm(&id);
     }
 
+    /// set how many blocks deep a transaction needs to be to be confirmed
+    /// by `apply_block`, and after how many blocks a pending transaction
+    /// that did not appear in a block is reported as expired.
+    ///
+    /// By default a transaction is confirmed once `10` blocks have been
+    /// applied on top of its block and expires after `100` blocks.
+    pub fn set_confirmation_settings(&mut self, depth: u32, deadline: u32) {
+        self.confirmations = wallet::Confirmations::new(depth, deadline);
+    }
+
+    /// apply a new block of the blockchain to the wallet
+    ///
+    /// `fragment_ids` are the identifiers of the fragments of the block. The
+    /// pending transactions of the wallet that are in the block are recorded,
+    /// with the date of the block in the history of the wallet, and they are
+    /// automatically confirmed once they are deep enough (see
+    /// `set_confirmation_settings`). If the parent of the block is not the
+    /// previous block applied, the blocks of the previous branch are
+    /// considered rolled back.
+    ///
+    /// The blocks are expected to be applied in order, each one after its
+    /// parent. The returned update lists the transactions that have been
+    /// confirmed and the pending transactions that expired: these may be
+    /// removed with `remove_pending_transaction`.
+    ///
+    /// # Errors
+    ///
+    /// * the parent of the block has not been applied, or it is too deep to
+    ///   switch to its branch;
+    /// * the chain length does not follow the parent's chain length;
+    ///
+    pub fn apply_block<I>(
+        &mut self,
+        header_id: HeaderId,
+        parent_id: HeaderId,
+        chain_length: u32,
+        block_date: BlockDate,
+        fragment_ids: I,
+    ) -> Result<wallet::BlockUpdate, Error>
+    where
+        I: IntoIterator<Item = FragmentId>,
+    {
+        let pending = self.pending_transactions();
+        let fragment_ids: Vec<FragmentId> = fragment_ids.into_iter().collect();
+        let update = self
+            .confirmations
+            .apply_block(
+                header_id,
+                parent_id,
+                chain_length,
+                fragment_ids.iter().copied(),
+                &pending,
+            )
+            .map_err(|e| Error::invalid_input("parent_id").with(e))?;
+
+        for id in fragment_ids.iter() {
+            if let Some(daedalus) = self.daedalus.as_mut() {
+                daedalus.set_block_date(id, block_date);
+            }
+            if let Some(icarus) = self.icarus.as_mut() {
+                icarus.set_block_date(id, block_date);
+            }
+            self.free_keys.set_block_date(id, block_date);
+            self.account.set_block_date(id, block_date);
+        }
+
+        for id in update.confirmed() {
+            self.confirm_transaction(*id);
+        }
+
+        Ok(update)
+    }
+
     /// get access to all the pending transaction
     ///
     /// TODO: this might need to be updated to have a more user friendly
@@ -269,12 +385,136 @@ impl Wallet {
         set
     }
 
+    /// get the history of all the transactions that touched the wallet
+    ///
+    /// The entries of the same transaction in the different parts of the wallet
+    /// (the account and the legacy wallets) are merged together. The entries are
+    /// sorted by block date, the transactions not yet in a block are last.
+    pub fn history(&self) -> Vec<wallet::HistoryEntry> {
+        let mut entries: Vec<wallet::HistoryEntry> = Vec::new();
+        let mut index = std::collections::HashMap::new();
+
+        let histories = std::iter::once(self.account.history())
+            .chain(self.daedalus.as_ref().map(|daedalus| daedalus.history()))
+            .chain(self.icarus.as_ref().map(|icarus| icarus.history()))
+            .chain(std::iter::once(self.free_keys.history()));
+
+        for entry in histories.flat_map(|history| history.iter()) {
+            if let Some(i) = index.get(entry.fragment_id()) {
+                entries[*i].merge(entry);
+            } else {
+                index.insert(*entry.fragment_id(), entries.len());
+                entries.push(entry.clone());
+            }
+        }
+
+        entries.sort_by_key(|entry| {
+            let date = entry.block_date();
+            (date.is_none(), date.map(|date| (date.epoch, date.slot_id)))
+        });
+
+        entries
+    }
+
     /// remove a given pending transaction returning the associated Inputs
     /// that were used for this transaction
-    pub fn remove_pending_transaction(&mut self, _id: &FragmentId) -> Option<Vec<Input>> {
-        // there are no cordova bindings for this, so this todo is not that important right now
-        // and I'm not that sure what's the best api here.
-        todo!("pending transactions");
+    ///
+    /// this is to use when a transaction has been rejected by the node. The
+    /// more recent pending transactions are kept and the state of the wallet
+    /// is recomputed without the removed transaction, except the ones that
+    /// spend from the account after it: they used the following spending
+    /// counters and are removed too, their inputs are returned as well.
+    ///
+    /// returns `None` if the transaction is not pending in any of the wallets
+    pub fn remove_pending_transaction(&mut self, id: &FragmentId) -> Option<Vec<Input>> {
+        let mut found = false;
+        let mut inputs = Vec::new();
+        let mut collect = |removed: Option<Vec<Input>>| {
+            if let Some(mut removed) = removed {
+                found = true;
+                inputs.append(&mut removed);
+            }
+        };
+
+        if let Some(daedalus) = self.daedalus.as_mut() {
+            collect(daedalus.remove_pending_transaction(id));
+        }
+        if let Some(icarus) = self.icarus.as_mut() {
+            collect(icarus.remove_pending_transaction(id));
+        }
+        collect(self.free_keys.remove_pending_transaction(id));
+        collect(self.account.remove_pending_transaction(id));
+
+        if found {
+            Some(inputs)
+        } else {
+            None
+        }
+    }
+
+    /// export the whole state of the wallet: the keys, the UTxOs, the
+    /// account state and the pending transactions.
+    ///
+    /// If a `password` is given the exported wallet is encrypted with it.
+    /// Use `Wallet::import` to restore the wallet.
+    ///
+    /// # Errors
+    ///
+    /// * the encryption failed
+    ///
+    pub fn export(&self, password: Option<&[u8]>) -> Result<Box<[u8]>, Error> {
+        let exported = Exported {
+            account: self.account.export(),
+            daedalus: self.daedalus.as_ref().map(|daedalus| daedalus.export()),
+            icarus: self.icarus.as_ref().map(|icarus| icarus.export()),
+            free_keys: self.free_keys.export(),
+            confirmations: Some(self.confirmations.export()),
+        };
+
+        exported.encode(password)
+    }
+
+    /// import a wallet exported with `Wallet::export`
+    ///
+    /// The `password` is required if the wallet has been exported with a
+    /// password.
+    ///
+    /// # Errors
+    ///
+    /// * the password is missing or invalid
+    /// * the exported wallet is malformed or of an unsupported version
+    ///
+    pub fn import(bytes: &[u8], password: Option<&[u8]>) -> Result<Self, Error> {
+        let exported = Exported::decode(bytes, password)?;
+        let persistence = |e| Error::wallet_persistence().with(e);
+
+        let account = wallet::Wallet::import(&exported.account).map_err(persistence)?;
+        let daedalus = exported
+            .daedalus
+            .map(|bytes| wallet::scheme::rindex::Wallet::import(&bytes))
+            .transpose()
+            .map_err(persistence)?;
+        let icarus = exported
+            .icarus
+            .map(|bytes| wallet::scheme::bip44::Wallet::<OldAddress>::import(&bytes))
+            .transpose()
+            .map_err(persistence)?;
+        let free_keys =
+            wallet::scheme::freeutxo::Wallet::import(&exported.free_keys).map_err(persistence)?;
+        let confirmations = exported
+            .confirmations
+            .map(|bytes| wallet::Confirmations::import(&bytes))
+            .transpose()
+            .map_err(persistence)?
+            .unwrap_or_default();
+
+        Ok(Wallet {
+            account,
+            daedalus,
+            icarus,
+            free_keys,
+            confirmations,
+        })
     }
 
     /// get the total value in the wallet
@@ -310,8 +550,85 @@ impl Wallet {
     /// transactions otherwise future transactions may fail to be accepted by any
     /// nodes of the blockchain because of invalid signature state.
     ///
-    pub fn set_state(&mut self, value: Value, counter: u32) {
-        self.account.update_state(value, counter)
+    /// The pending transactions that are still valid with the given counter
+    /// are kept, the stale ones are dropped and returned in the
+    /// reconciliation report.
+    ///
+    pub fn set_state(&mut self, value: Value, counter: u32) -> wallet::Reconciliation {
+        self.account.reconcile_state(value, counter)
+    }
+
+    /// Send `value` to the given `address` from the wallet's account
+    ///
+    /// This function outputs a fragment containing the signed transaction.
+    /// The fees are paid by the account on top of the `value`, and the
+    /// transaction is added to the pending transactions of the wallet.
+    ///
+    /// # Parameters
+    ///
+    /// * `settings` - ledger settings.
+    /// * `address` - the address to send the funds to.
+    /// * `value` - the amount of lovelace to send.
+    ///
+    /// # Errors
+    ///
+    /// The error is returned when the account does not have enough funds
+    /// to pay for the `value` and the fees.
+    pub fn send(
+        &mut self,
+        settings: Settings,
+        address: chain_addr::Address,
+        value: Value,
+    ) -> Result<Box<[u8]>, Error> {
+        let fragment = self
+            .account
+            .send_to(&settings, address, value)
+            .map_err(|e| Error::wallet_transaction().with(e))?;
+
+        Ok(fragment
+            .to_raw()
+            .serialize_as_vec()
+            .unwrap()
+            .into_boxed_slice())
+    }
+
+    /// Delegate the stake of the wallet's account to the given `pools`
+    ///
+    /// This function outputs a fragment containing the signed delegation
+    /// certificate. The fees are paid by the account and the transaction is
+    /// added to the pending transactions of the wallet.
+    ///
+    /// # Parameters
+    ///
+    /// * `settings` - ledger settings.
+    /// * `pools` - the pools to delegate to with their ratio: no pools to
+    ///   remove the delegation, one pool to delegate all the stake to it, or
+    ///   several pools to split the stake following the ratios.
+    /// * `owner` - build an `OwnerStakeDelegation` certificate if `true`,
+    ///   a `StakeDelegation` certificate signed by the account otherwise.
+    ///
+    /// # Errors
+    ///
+    /// The error is returned when the ratios are invalid or when the account
+    /// does not have enough funds to pay for the fees.
+    pub fn delegate(
+        &mut self,
+        settings: Settings,
+        pools: &[(PoolId, u8)],
+        owner: bool,
+    ) -> Result<Box<[u8]>, Error> {
+        let fragment = if owner {
+            self.account.owner_stake_delegation(&settings, pools)
+        } else {
+            self.account.stake_delegation(&settings, pools)
+        }
+        .map_err(|e| Error::wallet_transaction().with(e))?;
+
+        Ok(fragment
+            .to_raw()
+            .serialize_as_vec()
+            .unwrap()
+            .into_boxed_slice())
     }
 
     /// Cast a vote
@@ -364,3 +681,112 @@ impl Wallet {
         Ok(raw.serialize_as_vec().unwrap().into_boxed_slice())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONICS: &str =
+        "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone";
+    const ACCOUNT: &str = "d06583fdcc3293e717271408a2283228cd63bf1a35da1d50b734567dee1bfae9";
+    const PASSWORD: &[u8] = b"password";
+    const ACCOUNT_PASSWORD: &str =
+        "7baab35876156f3119a6a49b3efa771f56e429dec5aee1f01cc7d4f834dcc044";
+
+    #[test]
+    fn recover_without_password() {
+        let wallet = Wallet::recover(MNEMONICS, &[]).unwrap();
+
+        assert_eq!(wallet.id().to_string(), ACCOUNT);
+        assert_eq!(
+            wallet.recovered_schemes(),
+            RecoveredSchemes {
+                daedalus: true,
+                yoroi: true,
+            }
+        );
+    }
+
+    #[test]
+    fn recover_in_any_language() {
+        let entropy = bip39::Entropy::Entropy15([0x7f; 20]);
+        let english =
+            Wallet::recover(&entropy.to_mnemonics().to_string(&dictionary::ENGLISH), &[]).unwrap();
+        let spanish =
+            Wallet::recover(&entropy.to_mnemonics().to_string(&dictionary::SPANISH), &[]).unwrap();
+        assert_eq!(spanish.id(), english.id());
+
+        let japanese = entropy
+            .to_mnemonics()
+            .to_string(&dictionary::JAPANESE)
+            .replace(' ', "\u{3000}");
+        let japanese = Wallet::recover(&japanese, &[]).unwrap();
+        assert_eq!(japanese.id(), english.id());
+    }
+
+    #[test]
+    fn recover_ambiguous_language() {
+        let mnemonics = bip39::Entropy::Entropy12([0; 16])
+            .to_mnemonics()
+            .to_string(&dictionary::CHINESE_SIMPLIFIED);
+
+        let error = Wallet::recover(&mnemonics, &[]).err().unwrap();
+        assert!(matches!(
+            error.kind(),
+            crate::ErrorKind::AmbiguousMnemonicLanguage
+        ));
+    }
+
+    #[test]
+    fn confirm_transactions_of_the_branch_of_the_tip() {
+        let mut wallet = Wallet::recover(MNEMONICS, &[]).unwrap();
+        let settings = wallet
+            .retrieve_funds(include_bytes!("../../../test-vectors/block0"))
+            .unwrap();
+        let _conversion = wallet.convert(settings);
+        let id = *wallet.pending_transactions().iter().next().unwrap();
+        wallet.set_confirmation_settings(2, 100);
+
+        let block = |n: u8| HeaderId::from([n; 32]);
+        let date = |slot_id| BlockDate { epoch: 0, slot_id };
+        let mut apply = |n, parent, chain_length, fragments: Vec<FragmentId>| {
+            wallet
+                .apply_block(
+                    block(n),
+                    block(parent),
+                    chain_length,
+                    date(chain_length),
+                    fragments,
+                )
+                .unwrap()
+        };
+
+        apply(0, 0, 0, vec![]);
+        apply(1, 0, 1, vec![id]);
+        // a block of the same chain length on another branch
+        apply(11, 0, 1, vec![]);
+        apply(12, 11, 2, vec![]);
+        let update = apply(13, 12, 3, vec![]);
+        assert!(update.confirmed().is_empty());
+
+        apply(14, 13, 4, vec![id]);
+        apply(15, 14, 5, vec![]);
+        let update = apply(16, 15, 6, vec![]);
+        assert_eq!(update.confirmed(), &[id]);
+        assert!(!wallet.pending_transactions().contains(&id));
+    }
+
+    #[test]
+    fn recover_with_password() {
+        let wallet = Wallet::recover(MNEMONICS, PASSWORD).unwrap();
+
+        assert_eq!(wallet.id().to_string(), ACCOUNT_PASSWORD);
+        assert_eq!(
+            wallet.recovered_schemes(),
+            RecoveredSchemes {
+                daedalus: false,
+                yoroi: true,
+            }
+        );
+    }
+}
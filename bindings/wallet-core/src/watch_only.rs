@@ -0,0 +1,246 @@
+use crate::Error;
+use chain_crypto::{Ed25519, PublicKey};
+use chain_impl_mockchain::{
+    block::{Block, BlockDate},
+    fragment::{Fragment, FragmentId},
+    legacy::OldAddress,
+    transaction::{Input, NoExtra, Output, UtxoPointer},
+    value::Value,
+};
+use chain_path_derivation::{
+    bip44::{self, Bip44},
+    Derivation, HardDerivation,
+};
+use chain_ser::mempack::{ReadBuf, Readable as _};
+use ed25519_bip32::{DerivationScheme, XPub};
+use hdkeygen::Key;
+use thiserror::Error;
+use wallet::{scheme::bip44::WatchOnlyWallet as Watched, PersistenceError, Settings};
+
+/// the coin type of cardano in the bip44 derivation path ('1815)
+const COIN_TYPE: HardDerivation = HardDerivation::new_unchecked(Derivation::new(0x8000_0717));
+
+/// the exported wallet is neither a bip44 nor a Yoroi watch-only wallet
+#[derive(Debug, Error)]
+#[error("cannot import the watch-only wallet as bip44 ({bip44}) nor as Yoroi ({yoroi})")]
+struct ImportError {
+    bip44: PersistenceError,
+    yoroi: PersistenceError,
+}
+
+/// a wallet watching bip44 accounts from their extended public keys
+///
+/// It tracks the UTxOs and the balance of the accounts without any of the
+/// private keys and builds unsigned transactions (see the
+/// `UnsignedTransaction` of the `wallet` crate) to be signed by the wallet
+/// holding the keys.
+///
+/// * use `bip44` to watch the single and group addresses of the accounts;
+/// * use `yoroi` to watch the legacy addresses of the accounts.
+///
+pub struct WatchOnlyWallet {
+    inner: Inner,
+}
+
+enum Inner {
+    Bip44(Watched<PublicKey<Ed25519>>),
+    Yoroi(Watched<OldAddress>),
+}
+
+macro_rules! with_watched {
+    ($inner:expr, $watched:ident => $e:expr) => {
+        match $inner {
+            Inner::Bip44($watched) => $e,
+            Inner::Yoroi($watched) => $e,
+        }
+    };
+}
+
+impl WatchOnlyWallet {
+    /// watch the single and group addresses of the given accounts
+    ///
+    /// # parameters
+    ///
+    /// * account_keys: the index of the account (without the hardened bit)
+    ///   with its extended public key (the public key and the chain code);
+    ///
+    /// # errors
+    ///
+    /// * an account index is too large or a public key is not valid;
+    ///
+    pub fn bip44(account_keys: &[(u32, [u8; 64])]) -> Result<Self, Error> {
+        let keys = account_public_keys(account_keys)?;
+        Ok(Self {
+            inner: Inner::Bip44(Watched::<PublicKey<Ed25519>>::from_account_keys(keys)),
+        })
+    }
+
+    /// watch the legacy (Yoroi) addresses of the given accounts
+    ///
+    /// see `WatchOnlyWallet::bip44` for the parameters and the errors
+    pub fn yoroi(account_keys: &[(u32, [u8; 64])]) -> Result<Self, Error> {
+        let keys = account_public_keys(account_keys)?;
+        Ok(Self {
+            inner: Inner::Yoroi(Watched::<OldAddress>::from_account_keys(keys)),
+        })
+    }
+
+    /// retrieve the funds of the accounts from the block0
+    ///
+    /// # Errors
+    ///
+    /// * the block is not valid (cannot be decoded)
+    ///
+    pub fn retrieve_funds(&mut self, block0_bytes: &[u8]) -> Result<Settings, Error> {
+        let mut block0_bytes = ReadBuf::from(block0_bytes);
+        let block0 =
+            Block::read(&mut block0_bytes).map_err(|e| Error::invalid_input("block0").with(e))?;
+
+        let settings = Settings::new(&block0).unwrap();
+        for fragment in block0.contents.iter() {
+            let id = fragment.hash();
+            with_watched!(&mut self.inner, watched => {
+                watched.check_fragment(&id, fragment);
+                watched.confirm(&id);
+            });
+        }
+
+        Ok(settings)
+    }
+
+    /// check a fragment seen on the blockchain, returns `true` if it
+    /// touched the watched accounts
+    ///
+    /// The fragment stays pending until it is confirmed with
+    /// `confirm_transaction`.
+    ///
+    /// # Errors
+    ///
+    /// * the fragment is not valid (cannot be decoded)
+    ///
+    pub fn check_fragment(&mut self, fragment_bytes: &[u8]) -> Result<bool, Error> {
+        let fragment = Fragment::read(&mut ReadBuf::from(fragment_bytes))
+            .map_err(|e| Error::invalid_input("fragment").with(e))?;
+        let id = fragment.hash();
+
+        Ok(with_watched!(&mut self.inner, watched => watched.check_fragment(&id, &fragment)))
+    }
+
+    /// confirm a transaction, it is now considered immutable
+    pub fn confirm_transaction(&mut self, id: FragmentId) {
+        with_watched!(&mut self.inner, watched => watched.confirm(&id))
+    }
+
+    /// set the date of the block the given fragment is in
+    pub fn set_block_date(&mut self, id: &FragmentId, block_date: BlockDate) {
+        with_watched!(&mut self.inner, watched => watched.set_block_date(id, block_date))
+    }
+
+    /// get the total value of the watched accounts, including the pending
+    /// transactions
+    pub fn total_value(&self) -> Value {
+        with_watched!(&self.inner, watched => watched.utxos().total_value())
+    }
+
+    /// get the value of the watched accounts from the confirmed
+    /// transactions only
+    pub fn confirmed_value(&self) -> Value {
+        with_watched!(&self.inner, watched => watched.confirmed_value())
+    }
+
+    /// get the UTxOs of the watched accounts
+    pub fn utxos(&self) -> Vec<UtxoPointer> {
+        with_watched!(&self.inner, watched => {
+            watched.utxos().utxos().map(|utxo| **utxo).collect()
+        })
+    }
+
+    /// get the pending transactions of the watched accounts
+    pub fn pending_transactions(&self) -> std::collections::HashSet<FragmentId> {
+        with_watched!(&self.inner, watched => watched.pending_transactions().copied().collect())
+    }
+
+    /// remove a given pending transaction returning the associated Inputs
+    /// that were used for this transaction
+    pub fn remove_pending_transaction(&mut self, id: &FragmentId) -> Option<Vec<Input>> {
+        with_watched!(&mut self.inner, watched => watched.remove_pending_transaction(id))
+    }
+
+    /// get the history of the transactions that touched the watched accounts
+    pub fn history(&self) -> Vec<wallet::HistoryEntry> {
+        with_watched!(&self.inner, watched => watched.history().iter().cloned().collect())
+    }
+
+    /// build an unsigned transaction sending `value` to the given `address`,
+    /// the change (if any) is sent to `change_address`
+    ///
+    /// The returned bytes are the exported `UnsignedTransaction`, to be
+    /// signed by the wallet holding the private keys of the accounts.
+    ///
+    /// # Errors
+    ///
+    /// The error is returned when the accounts do not have enough funds to
+    /// pay for the `value` and the fees.
+    pub fn unsigned_transaction(
+        &self,
+        settings: Settings,
+        address: chain_addr::Address,
+        value: Value,
+        change_address: chain_addr::Address,
+    ) -> Result<Box<[u8]>, Error> {
+        let outputs = vec![Output::from_address(address, value)];
+        let unsigned = with_watched!(&self.inner, watched => watched.unsigned_transaction(
+            &settings,
+            NoExtra,
+            outputs,
+            wallet::transaction::DEFAULT_STRATEGIES,
+            change_address,
+        ))
+        .map_err(|e| Error::wallet_transaction().with(e))?;
+
+        Ok(unsigned.export().into_boxed_slice())
+    }
+
+    /// export the state of the wallet: the account public keys, the UTxOs
+    /// and the pending transactions
+    ///
+    /// Use `WatchOnlyWallet::import` to restore the wallet.
+    pub fn export(&self) -> Box<[u8]> {
+        with_watched!(&self.inner, watched => watched.export()).into_boxed_slice()
+    }
+
+    /// import a wallet exported with `WatchOnlyWallet::export`
+    ///
+    /// # Errors
+    ///
+    /// * the exported wallet is malformed or of an unsupported version, the
+    ///   details tell why it is neither a bip44 nor a Yoroi wallet
+    ///
+    pub fn import(bytes: &[u8]) -> Result<Self, Error> {
+        let inner = match Watched::<PublicKey<Ed25519>>::import(bytes) {
+            Ok(wallet) => Inner::Bip44(wallet),
+            Err(bip44) => Watched::<OldAddress>::import(bytes)
+                .map(Inner::Yoroi)
+                .map_err(|yoroi| Error::wallet_persistence().with(ImportError { bip44, yoroi }))?,
+        };
+
+        Ok(Self { inner })
+    }
+}
+
+fn account_public_keys(
+    account_keys: &[(u32, [u8; 64])],
+) -> Result<Vec<Key<XPub, Bip44<bip44::Account>>>, Error> {
+    account_keys
+        .iter()
+        .map(|(index, key)| {
+            let id = HardDerivation::min_value()
+                .checked_add(*index)
+                .ok_or_else(|| Error::invalid_input("account_keys"))?;
+            let xpub = XPub::from_slice(key).map_err(|_| Error::invalid_input("account_keys"))?;
+            let path = bip44::new().bip44().coin_type(COIN_TYPE).account(id);
+
+            Ok(Key::new_unchecked(xpub, path, DerivationScheme::V2))
+        })
+        .collect()
+}
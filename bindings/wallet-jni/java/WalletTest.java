@@ -4,6 +4,9 @@ import com.iohk.jormungandrwallet.Conversion;
 import com.iohk.jormungandrwallet.Proposal;
 import com.iohk.jormungandrwallet.PendingTransactions;
 import com.iohk.jormungandrwallet.SymmetricCipher;
+import com.iohk.jormungandrwallet.BlockUpdate;
+import com.iohk.jormungandrwallet.Reconciliation;
+import com.iohk.jormungandrwallet.MnemonicsEntry;
 
 import java.util.Properties;
 import java.util.Enumeration;
@@ -12,14 +15,18 @@ import java.nio.file.Files;
 import java.nio.file.Paths;
 
 import org.junit.Test;
+import static org.junit.Assert.assertArrayEquals;
 import static org.junit.Assert.assertEquals;
 import static org.junit.Assert.assertNotEquals;
+import static org.junit.Assert.assertTrue;
+import static org.junit.Assert.assertFalse;
 
 public class WalletTest {
     @Test
     public void recoverWallet() throws IOException {
         final long walletPtr = Wallet.recover(
-                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone");
+                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone",
+                null);
 
         final byte[] block0 = Files.readAllBytes(Paths.get("../../../test-vectors/block0"));
 
@@ -33,6 +40,18 @@ public class WalletTest {
         Wallet.delete(walletPtr);
     }
 
+    @Test
+    public void recoverWalletWithPassword() throws IOException {
+        final long walletPtr = Wallet.recover(
+                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone",
+                "password".getBytes("UTF-8"));
+
+        assertArrayEquals(hexStringToByteArray("7baab35876156f3119a6a49b3efa771f56e429dec5aee1f01cc7d4f834dcc044"),
+                Wallet.id(walletPtr));
+
+        Wallet.delete(walletPtr);
+    }
+
     @Test
     public void importKeys() throws IOException {
         final byte[] accountKey = { -56, 101, -106, -62, -47, 32, -120, -123, -37, 31, -29, 101, -124, 6, -86, 15, 124,
@@ -65,7 +84,8 @@ public class WalletTest {
     @Test
     public void convertWallet() throws IOException {
         final long walletPtr = Wallet.recover(
-                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone");
+                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone",
+                null);
 
         final byte[] block0 = Files.readAllBytes(Paths.get("../../../test-vectors/block0"));
 
@@ -95,7 +115,8 @@ public class WalletTest {
     @Test(expected = IndexOutOfBoundsException.class)
     public void negativeIndexConversionTransaction() throws IOException {
         final long walletPtr = Wallet.recover(
-                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone");
+                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone",
+                null);
 
         final byte[] block0 = Files.readAllBytes(Paths.get("../../../test-vectors/block0"));
 
@@ -118,7 +139,8 @@ public class WalletTest {
     @Test
     public void voteCast() throws IOException {
         final long walletPtr = Wallet.recover(
-                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone");
+                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone",
+                null);
 
         final byte[] block0 = Files.readAllBytes(Paths.get("../../../test-vectors/block0"));
 
@@ -142,7 +164,8 @@ public class WalletTest {
     @Test
     public void confirmConversionTransaction() throws IOException {
         final long walletPtr = Wallet.recover(
-                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone");
+                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone",
+                null);
 
         final byte[] block0 = Files.readAllBytes(Paths.get("../../../test-vectors/block0"));
 
@@ -176,10 +199,111 @@ public class WalletTest {
         Wallet.delete(walletPtr);
     }
 
+    @Test
+    public void confirmTransactionInBlock() throws IOException {
+        final long walletPtr = Wallet.recover(
+                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone",
+                null);
+
+        final byte[] block0 = Files.readAllBytes(Paths.get("../../../test-vectors/block0"));
+
+        final long settingsPtr = Wallet.initialFunds(walletPtr, block0);
+
+        final long conversionPtr = Wallet.convert(walletPtr, settingsPtr);
+
+        final long pending = Wallet.pendingTransactions(walletPtr);
+
+        final byte[] fragmentId = PendingTransactions.get(pending, 0);
+
+        PendingTransactions.delete(pending);
+
+        Wallet.setConfirmationSettings(walletPtr, 1, 10);
+
+        final byte[] parentId = new byte[32];
+        final byte[] headerId1 = new byte[32];
+        headerId1[0] = 1;
+        final byte[] headerId2 = new byte[32];
+        headerId2[0] = 2;
+
+        final long update1 = Wallet.applyBlock(walletPtr, headerId1, parentId, 1, 0, 1, fragmentId);
+
+        assertEquals(BlockUpdate.confirmedSize(update1), 0);
+
+        BlockUpdate.delete(update1);
+
+        final long update2 = Wallet.applyBlock(walletPtr, headerId2, headerId1, 2, 0, 2, new byte[0]);
+
+        assertEquals(BlockUpdate.confirmedSize(update2), 1);
+        assertArrayEquals(BlockUpdate.confirmedGet(update2, 0), fragmentId);
+        assertEquals(BlockUpdate.expiredSize(update2), 0);
+
+        BlockUpdate.delete(update2);
+
+        final long after = Wallet.pendingTransactions(walletPtr);
+
+        assertEquals(PendingTransactions.len(after), Conversion.transactionsSize(conversionPtr) - 1);
+
+        PendingTransactions.delete(after);
+        Conversion.delete(conversionPtr);
+        Settings.delete(settingsPtr);
+        Wallet.delete(walletPtr);
+    }
+
+    @Test
+    public void reconcileState() throws IOException {
+        final long walletPtr = Wallet.recover(
+                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone",
+                null);
+
+        final long reconciliation = Wallet.reconcileState(walletPtr, 1000, 0);
+
+        assertEquals(Reconciliation.status(reconciliation), Reconciliation.CONSISTENT);
+        assertEquals(Reconciliation.droppedSize(reconciliation), 0);
+        assertEquals(Reconciliation.confirmedSize(reconciliation), 0);
+
+        Reconciliation.delete(reconciliation);
+
+        final long ahead = Wallet.reconcileState(walletPtr, 1000, 1);
+
+        assertEquals(Reconciliation.status(ahead), Reconciliation.AHEAD);
+
+        Reconciliation.delete(ahead);
+        Wallet.delete(walletPtr);
+    }
+
+    @Test
+    public void checkMnemonics() {
+        final long typing = MnemonicsEntry.check("english", "neck bul");
+
+        assertEquals(MnemonicsEntry.wordsSize(typing), 1);
+        assertEquals(MnemonicsEntry.wordsGet(typing, 0), "neck");
+        assertEquals(MnemonicsEntry.completionsSize(typing), 3);
+        assertEquals(MnemonicsEntry.completionsGet(typing, 0), "bulb");
+        assertFalse(MnemonicsEntry.isValid(typing));
+
+        MnemonicsEntry.delete(typing);
+
+        final long complete = MnemonicsEntry.check("english",
+                "neck bulb teac ille soul cry moni claw amou bori prov vill riva draf ston ");
+
+        assertEquals(MnemonicsEntry.wordsSize(complete), 15);
+        assertEquals(MnemonicsEntry.wordsGet(complete, 14), "stone");
+        assertEquals(MnemonicsEntry.completionsSize(complete), 0);
+        assertTrue(MnemonicsEntry.isValid(complete));
+
+        MnemonicsEntry.delete(complete);
+    }
+
+    @Test(expected = Exception.class)
+    public void checkMnemonicsUnknownWord() {
+        MnemonicsEntry.check("english", "neck xx");
+    }
+
     @Test
     public void confirmVoteCast() throws IOException {
         final long walletPtr = Wallet.recover(
-                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone");
+                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone",
+                null);
 
         final byte[] block0 = Files.readAllBytes(Paths.get("../../../test-vectors/block0"));
 
@@ -223,7 +347,8 @@ public class WalletTest {
     @Test
     public void privateVoteCast() throws IOException {
         final long walletPtr = Wallet.recover(
-                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone");
+                "neck bulb teach illegal soul cry monitor claw amount boring provide village rival draft stone",
+                null);
 
         final byte[] block0 = Files.readAllBytes(Paths.get("../../../test-vectors/block0"));
 
@@ -0,0 +1,17 @@
+package com.iohk.jormungandrwallet;
+
+public class BlockUpdate {
+    static {
+        System.loadLibrary("wallet_jni");
+    }
+
+    public native static void delete(long update);
+
+    public native static int confirmedSize(long update);
+
+    public native static byte[] confirmedGet(long update, int index);
+
+    public native static int expiredSize(long update);
+
+    public native static byte[] expiredGet(long update, int index);
+}
\ No newline at end of file
@@ -0,0 +1,21 @@
+package com.iohk.jormungandrwallet;
+
+public class MnemonicsEntry {
+    static {
+        System.loadLibrary("wallet_jni");
+    }
+
+    public native static long check(String language, String mnemonics);
+
+    public native static void delete(long entry);
+
+    public native static boolean isValid(long entry);
+
+    public native static int wordsSize(long entry);
+
+    public native static String wordsGet(long entry, int index);
+
+    public native static int completionsSize(long entry);
+
+    public native static String completionsGet(long entry, int index);
+}
@@ -0,0 +1,23 @@
+package com.iohk.jormungandrwallet;
+
+public class Reconciliation {
+    static {
+        System.loadLibrary("wallet_jni");
+    }
+
+    public static final int CONSISTENT = 0;
+    public static final int AHEAD = 1;
+    public static final int BEHIND = 2;
+
+    public native static void delete(long reconciliation);
+
+    public native static int status(long reconciliation);
+
+    public native static int droppedSize(long reconciliation);
+
+    public native static byte[] droppedGet(long reconciliation, int index);
+
+    public native static int confirmedSize(long reconciliation);
+
+    public native static byte[] confirmedGet(long reconciliation, int index);
+}
\ No newline at end of file
@@ -5,7 +5,7 @@ public class Wallet {
         System.loadLibrary("wallet_jni");
     }
 
-    public native static long recover(String mnemonics);
+    public native static long recover(String mnemonics, byte[] password);
 
     public native static long importKeys(byte[] accountKey, byte[] utxoKeys);
 
@@ -21,9 +21,24 @@ public class Wallet {
 
     public native static void setState(long wallet, long value, long counter);
 
+    public native static long reconcileState(long wallet, long value, long counter);
+
+    public native static byte[] send(long wallet, long settings, byte[] address, long value);
+
+    public native static byte[] delegate(long wallet, long settings, byte[] pools, byte[] ratios, boolean owner);
+
+    public native static byte[] exportWallet(long wallet, byte[] password);
+
+    public native static long importWallet(byte[] bytes, byte[] password);
+
     public native static byte[] voteCast(long wallet, long settings, long proposal, int choice);
 
     public native static void confirmTransaction(long wallet, byte[] fragmentId);
 
     public native static long pendingTransactions(long wallet);
+
+    public native static void setConfirmationSettings(long wallet, int depth, int deadline);
+
+    public native static long applyBlock(long wallet, byte[] headerId, byte[] parentId, int chainLength, int epoch,
+            int slot, byte[] fragmentIds);
 }
\ No newline at end of file
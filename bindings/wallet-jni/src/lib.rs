@@ -1,5 +1,5 @@
 use jni::objects::{JClass, JObject, JString, JValue};
-use jni::sys::{jbyte, jbyteArray, jint, jlong};
+use jni::sys::{jboolean, jbyte, jbyteArray, jint, jlong, jstring, JNI_FALSE, JNI_TRUE};
 use jni::JNIEnv;
 use std::convert::TryInto;
 use std::ptr::{null, null_mut};
@@ -17,14 +17,30 @@ pub unsafe extern "system" fn Java_com_iohk_jormungandrwallet_Wallet_recover(
     env: JNIEnv,
     _: JClass,
     mnemonics: JString,
+    password: jbyteArray,
 ) -> jlong {
     let mnemonics_j = env
         .get_string(mnemonics)
         .expect("Couldn't get mnemonics String");
 
+    let password_bytes = if password.is_null() {
+        Vec::new()
+    } else {
+        let size = env.get_array_length(password).expect("invalid array");
+        let mut bytes = vec![0i8; size as usize];
+        env.get_byte_array_region(password, 0, &mut bytes)
+            .expect("Couldn't copy password array");
+        bytes
+    };
+
     let mut wallet: WalletPtr = null_mut();
     let wallet_ptr: *mut WalletPtr = &mut wallet;
-    let result = wallet_recover(&mnemonics_j.to_string_lossy(), null(), 0, wallet_ptr);
+    let result = wallet_recover(
+        &mnemonics_j.to_string_lossy(),
+        password_bytes.as_ptr() as *const u8,
+        password_bytes.len(),
+        wallet_ptr,
+    );
 
     if let Some(error) = result.error() {
         let _ = env.throw(error.to_string());
@@ -615,6 +631,333 @@ pub extern "system" fn Java_com_iohk_jormungandrwallet_Proposal_delete(
     }
 }
 
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Wallet_send(
+    env: JNIEnv,
+    _: JClass,
+    wallet: jlong,
+    settings: jlong,
+    address: jbyteArray,
+    value: jlong,
+) -> jbyteArray {
+    let wallet_ptr = wallet as WalletPtr;
+    let settings_ptr = settings as SettingsPtr;
+
+    let size = env.get_array_length(address).expect("invalid array");
+    let mut address_bytes = vec![0i8; size as usize];
+    env.get_byte_array_region(address, 0, &mut address_bytes)
+        .expect("Couldn't copy address array");
+
+    let mut transaction_out: *const u8 = null();
+    let mut transaction_size: usize = 0;
+
+    let r = unsafe {
+        wallet_send(
+            wallet_ptr,
+            settings_ptr,
+            address_bytes.as_ptr() as *const u8,
+            address_bytes.len(),
+            value as u64,
+            &mut transaction_out as *mut *const u8,
+            &mut transaction_size as *mut usize,
+        )
+    };
+
+    if let Some(error) = r.error() {
+        let _ = env.throw(error.to_string());
+        return null_mut() as jbyteArray;
+    }
+
+    let array = env
+        .new_byte_array(transaction_size as jint)
+        .expect("Failed to create new byte array");
+
+    debug_assert!(!transaction_out.is_null());
+    let slice =
+        unsafe { std::slice::from_raw_parts(transaction_out as *const jbyte, transaction_size) };
+
+    env.set_byte_array_region(array, 0, slice)
+        .expect("Couldn't copy array to jvm");
+
+    // wallet_send leaks the buffer, set_byte_array_region does a *copy* of
+    // the buffer so we can release it now.
+    unsafe { delete_buffer(transaction_out as *mut u8, transaction_size) };
+
+    array
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Wallet_delegate(
+    env: JNIEnv,
+    _: JClass,
+    wallet: jlong,
+    settings: jlong,
+    pools: jbyteArray,
+    ratios: jbyteArray,
+    owner: jboolean,
+) -> jbyteArray {
+    let wallet_ptr = wallet as WalletPtr;
+    let settings_ptr = settings as SettingsPtr;
+
+    let pools_size = env.get_array_length(pools).expect("invalid array");
+    let ratios_size = env.get_array_length(ratios).expect("invalid array");
+    if pools_size != ratios_size * POOL_ID_LENGTH as jint {
+        let _ = env.throw("every pool needs a ratio");
+        return null_mut() as jbyteArray;
+    }
+
+    let mut pools_bytes = vec![0i8; pools_size as usize];
+    env.get_byte_array_region(pools, 0, &mut pools_bytes)
+        .expect("Couldn't copy pools array");
+    let mut ratios_bytes = vec![0i8; ratios_size as usize];
+    env.get_byte_array_region(ratios, 0, &mut ratios_bytes)
+        .expect("Couldn't copy ratios array");
+
+    let mut transaction_out: *const u8 = null();
+    let mut transaction_size: usize = 0;
+
+    let r = unsafe {
+        wallet_delegate(
+            wallet_ptr,
+            settings_ptr,
+            pools_bytes.as_ptr() as *const u8,
+            ratios_bytes.as_ptr() as *const u8,
+            ratios_bytes.len(),
+            owner != 0,
+            &mut transaction_out as *mut *const u8,
+            &mut transaction_size as *mut usize,
+        )
+    };
+
+    if let Some(error) = r.error() {
+        let _ = env.throw(error.to_string());
+        return null_mut() as jbyteArray;
+    }
+
+    let array = env
+        .new_byte_array(transaction_size as jint)
+        .expect("Failed to create new byte array");
+
+    debug_assert!(!transaction_out.is_null());
+    let slice =
+        unsafe { std::slice::from_raw_parts(transaction_out as *const jbyte, transaction_size) };
+
+    env.set_byte_array_region(array, 0, slice)
+        .expect("Couldn't copy array to jvm");
+
+    // wallet_delegate leaks the buffer, set_byte_array_region does a *copy*
+    // of the buffer so we can release it now.
+    unsafe { delete_buffer(transaction_out as *mut u8, transaction_size) };
+
+    array
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Wallet_exportWallet(
+    env: JNIEnv,
+    _: JClass,
+    wallet: jlong,
+    password: jbyteArray,
+) -> jbyteArray {
+    let wallet_ptr = wallet as WalletPtr;
+
+    let password_bytes = if password.is_null() {
+        Vec::new()
+    } else {
+        let size = env.get_array_length(password).expect("invalid array");
+        let mut bytes = vec![0i8; size as usize];
+        env.get_byte_array_region(password, 0, &mut bytes)
+            .expect("Couldn't copy password array");
+        bytes
+    };
+
+    let mut bytes_out: *const u8 = null();
+    let mut bytes_size: usize = 0;
+
+    let r = unsafe {
+        wallet_export(
+            wallet_ptr,
+            password_bytes.as_ptr() as *const u8,
+            password_bytes.len(),
+            &mut bytes_out as *mut *const u8,
+            &mut bytes_size as *mut usize,
+        )
+    };
+
+    if let Some(error) = r.error() {
+        let _ = env.throw(error.to_string());
+        return null_mut() as jbyteArray;
+    }
+
+    let array = env
+        .new_byte_array(bytes_size as jint)
+        .expect("Failed to create new byte array");
+
+    debug_assert!(!bytes_out.is_null());
+    let slice = unsafe { std::slice::from_raw_parts(bytes_out as *const jbyte, bytes_size) };
+
+    env.set_byte_array_region(array, 0, slice)
+        .expect("Couldn't copy array to jvm");
+
+    // wallet_export leaks the buffer, set_byte_array_region does a *copy* of
+    // the buffer so we can release it now.
+    unsafe { delete_buffer(bytes_out as *mut u8, bytes_size) };
+
+    array
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Wallet_importWallet(
+    env: JNIEnv,
+    _: JClass,
+    bytes: jbyteArray,
+    password: jbyteArray,
+) -> jlong {
+    let size = env.get_array_length(bytes).expect("invalid array");
+    let mut exported = vec![0i8; size as usize];
+    env.get_byte_array_region(bytes, 0, &mut exported)
+        .expect("Couldn't copy bytes array");
+
+    let password_bytes = if password.is_null() {
+        Vec::new()
+    } else {
+        let size = env.get_array_length(password).expect("invalid array");
+        let mut bytes = vec![0i8; size as usize];
+        env.get_byte_array_region(password, 0, &mut bytes)
+            .expect("Couldn't copy password array");
+        bytes
+    };
+
+    let mut wallet: WalletPtr = null_mut();
+    let wallet_ptr: *mut WalletPtr = &mut wallet;
+
+    let r = unsafe {
+        wallet_import(
+            exported.as_ptr() as *const u8,
+            exported.len(),
+            password_bytes.as_ptr() as *const u8,
+            password_bytes.len(),
+            wallet_ptr,
+        )
+    };
+
+    if let Some(error) = r.error() {
+        let _ = env.throw(error.to_string());
+        0
+    } else {
+        wallet as jlong
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Wallet_reconcileState(
+    env: JNIEnv,
+    _: JClass,
+    wallet: jlong,
+    value: jlong,
+    counter: jlong,
+) -> jlong {
+    let wallet = wallet as WalletPtr;
+    let mut reconciliation: ReconciliationPtr = null_mut();
+
+    let r = unsafe {
+        wallet_reconcile_state(
+            wallet,
+            value as u64,
+            counter as u32,
+            &mut reconciliation as *mut ReconciliationPtr,
+        )
+    };
+
+    if let Some(error) = r.error() {
+        let _ = env.throw(error.to_string());
+        0
+    } else {
+        reconciliation as jlong
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Wallet_setConfirmationSettings(
+    env: JNIEnv,
+    _: JClass,
+    wallet: jlong,
+    depth: jint,
+    deadline: jint,
+) {
+    let wallet = wallet as WalletPtr;
+    let r = unsafe { wallet_set_confirmation_settings(wallet, depth as u32, deadline as u32) };
+
+    if let Some(error) = r.error() {
+        let _ = env.throw(error.to_string());
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Wallet_applyBlock(
+    env: JNIEnv,
+    _: JClass,
+    wallet: jlong,
+    header_id: jbyteArray,
+    parent_id: jbyteArray,
+    chain_length: jint,
+    epoch: jint,
+    slot: jint,
+    fragment_ids: jbyteArray,
+) -> jlong {
+    let wallet_ptr = wallet as WalletPtr;
+
+    let mut header_id_bytes = [0i8; HEADER_ID_LENGTH];
+    if env
+        .get_byte_array_region(header_id, 0, &mut header_id_bytes)
+        .is_err()
+    {
+        let _ = env.throw("the header id needs to be 32 bytes");
+        return 0;
+    }
+    let mut parent_id_bytes = [0i8; HEADER_ID_LENGTH];
+    if env
+        .get_byte_array_region(parent_id, 0, &mut parent_id_bytes)
+        .is_err()
+    {
+        let _ = env.throw("the parent id needs to be 32 bytes");
+        return 0;
+    }
+
+    let size = env.get_array_length(fragment_ids).expect("invalid array");
+    if size as usize % FRAGMENT_ID_LENGTH != 0 {
+        let _ = env.throw("the fragment ids need to be 32 bytes each");
+        return 0;
+    }
+    let mut fragment_ids_bytes = vec![0i8; size as usize];
+    env.get_byte_array_region(fragment_ids, 0, &mut fragment_ids_bytes)
+        .expect("Couldn't copy fragment ids array");
+
+    let mut update: BlockUpdatePtr = null_mut();
+
+    let r = unsafe {
+        wallet_apply_block(
+            wallet_ptr,
+            header_id_bytes.as_ptr() as *const u8,
+            parent_id_bytes.as_ptr() as *const u8,
+            chain_length as u32,
+            epoch as u32,
+            slot as u32,
+            fragment_ids_bytes.as_ptr() as *const u8,
+            fragment_ids_bytes.len() / FRAGMENT_ID_LENGTH,
+            &mut update as *mut BlockUpdatePtr,
+        )
+    };
+
+    if let Some(error) = r.error() {
+        let _ = env.throw(error.to_string());
+        0
+    } else {
+        update as jlong
+    }
+}
+
 #[no_mangle]
 pub extern "system" fn Java_com_iohk_jormungandrwallet_Wallet_voteCast(
     env: JNIEnv,
@@ -754,6 +1097,346 @@ pub extern "system" fn Java_com_iohk_jormungandrwallet_PendingTransactions_delet
     }
 }
 
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_BlockUpdate_confirmedSize(
+    _: JNIEnv,
+    _: JClass,
+    update: jlong,
+) -> jint {
+    let update = update as BlockUpdatePtr;
+    unsafe { block_update_confirmed_size(update) as jint }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_BlockUpdate_confirmedGet(
+    env: JNIEnv,
+    _: JClass,
+    update: jlong,
+    index: jint,
+) -> jbyteArray {
+    let update = update as BlockUpdatePtr;
+    fragment_id_get(env, index, |index, id_out| unsafe {
+        block_update_confirmed_get(update, index, id_out)
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_BlockUpdate_expiredSize(
+    _: JNIEnv,
+    _: JClass,
+    update: jlong,
+) -> jint {
+    let update = update as BlockUpdatePtr;
+    unsafe { block_update_expired_size(update) as jint }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_BlockUpdate_expiredGet(
+    env: JNIEnv,
+    _: JClass,
+    update: jlong,
+    index: jint,
+) -> jbyteArray {
+    let update = update as BlockUpdatePtr;
+    fragment_id_get(env, index, |index, id_out| unsafe {
+        block_update_expired_get(update, index, id_out)
+    })
+}
+
+fn fragment_id_get<F>(env: JNIEnv, index: jint, get: F) -> jbyteArray
+where
+    F: FnOnce(usize, *mut *const u8) -> wallet_core::Result,
+{
+    if index.is_negative() {
+        let _ = env.throw_new(
+            "java/lang/IndexOutOfBoundsException",
+            "Fragment id index should be a positive number",
+        );
+        return null_mut();
+    }
+
+    let mut id_out: *const u8 = null();
+
+    let result = get(index as usize, (&mut id_out) as *mut *const u8);
+
+    match result.error() {
+        None => {
+            let slice =
+                unsafe { std::slice::from_raw_parts(id_out as *const jbyte, FRAGMENT_ID_LENGTH) };
+
+            let array = env
+                .new_byte_array(FRAGMENT_ID_LENGTH as jint)
+                .expect("Failed to create new byte array");
+
+            env.set_byte_array_region(array, 0, slice)
+                .expect("Couldn't copy array to jvm");
+
+            array
+        }
+        Some(error) => {
+            let _ = env.throw(error.to_string());
+            null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_BlockUpdate_delete(
+    _: JNIEnv,
+    _: JClass,
+    update: jlong,
+) {
+    let update = update as BlockUpdatePtr;
+    if !update.is_null() {
+        wallet_delete_block_update(update);
+    }
+}
+
+/// the status of the reconciliation: `0` if the node and the local state
+/// use the same spending counter, `1` if the node is ahead of the local
+/// state and `2` if it is behind
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Reconciliation_status(
+    env: JNIEnv,
+    _: JClass,
+    reconciliation: jlong,
+) -> jint {
+    let reconciliation = reconciliation as ReconciliationPtr;
+    let mut status = wallet_core::SyncStatus::Consistent;
+
+    let r = unsafe { reconciliation_status(reconciliation, &mut status) };
+
+    if let Some(error) = r.error() {
+        let _ = env.throw(error.to_string());
+    }
+
+    match status {
+        wallet_core::SyncStatus::Consistent => 0,
+        wallet_core::SyncStatus::Ahead => 1,
+        wallet_core::SyncStatus::Behind => 2,
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Reconciliation_droppedSize(
+    _: JNIEnv,
+    _: JClass,
+    reconciliation: jlong,
+) -> jint {
+    let reconciliation = reconciliation as ReconciliationPtr;
+    unsafe { reconciliation_dropped_size(reconciliation) as jint }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Reconciliation_droppedGet(
+    env: JNIEnv,
+    _: JClass,
+    reconciliation: jlong,
+    index: jint,
+) -> jbyteArray {
+    let reconciliation = reconciliation as ReconciliationPtr;
+    fragment_id_get(env, index, |index, id_out| unsafe {
+        reconciliation_dropped_get(reconciliation, index, id_out)
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Reconciliation_confirmedSize(
+    _: JNIEnv,
+    _: JClass,
+    reconciliation: jlong,
+) -> jint {
+    let reconciliation = reconciliation as ReconciliationPtr;
+    unsafe { reconciliation_confirmed_size(reconciliation) as jint }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Reconciliation_confirmedGet(
+    env: JNIEnv,
+    _: JClass,
+    reconciliation: jlong,
+    index: jint,
+) -> jbyteArray {
+    let reconciliation = reconciliation as ReconciliationPtr;
+    fragment_id_get(env, index, |index, id_out| unsafe {
+        reconciliation_confirmed_get(reconciliation, index, id_out)
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_Reconciliation_delete(
+    _: JNIEnv,
+    _: JClass,
+    reconciliation: jlong,
+) {
+    let reconciliation = reconciliation as ReconciliationPtr;
+    if !reconciliation.is_null() {
+        wallet_delete_reconciliation(reconciliation);
+    }
+}
+
+/// check the mnemonics entered so far by the user in the dictionary of the
+/// given language (`english`, `japanese`, `chinese-simplified`...), the
+/// returned entry must be deleted with `MnemonicsEntry.delete`
+///
+/// The words followed by a space are complete and may be given by their
+/// prefix, the last word, if not followed by a space, is the word being
+/// typed and is completed.
+///
+/// # Safety
+///
+/// This function dereference raw pointers. Even though
+/// the function checks if the pointers are null. Mind not to put random values
+/// in or you may see unexpected behaviors
+///
+#[no_mangle]
+pub unsafe extern "system" fn Java_com_iohk_jormungandrwallet_MnemonicsEntry_check(
+    env: JNIEnv,
+    _: JClass,
+    language: JString,
+    mnemonics: JString,
+) -> jlong {
+    let language_j = env
+        .get_string(language)
+        .expect("Couldn't get language String");
+    let mnemonics_j = env
+        .get_string(mnemonics)
+        .expect("Couldn't get mnemonics String");
+
+    let mut entry: MnemonicsEntryPtr = null_mut();
+    let result = wallet_check_mnemonics(
+        &language_j.to_string_lossy(),
+        &mnemonics_j.to_string_lossy(),
+        &mut entry as *mut MnemonicsEntryPtr,
+    );
+
+    if let Some(error) = result.error() {
+        let _ = env.throw(error.to_string());
+        0
+    } else {
+        entry as jlong
+    }
+}
+
+/// `true` if the complete words of the entry are mnemonics of a supported
+/// length with a valid checksum, they can then be given to `Wallet.recover`
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_MnemonicsEntry_isValid(
+    env: JNIEnv,
+    _: JClass,
+    entry: jlong,
+) -> jboolean {
+    let entry = entry as MnemonicsEntryPtr;
+    let mut valid = false;
+
+    let r = unsafe { mnemonics_entry_is_valid(entry, &mut valid) };
+
+    if let Some(error) = r.error() {
+        let _ = env.throw(error.to_string());
+    }
+
+    if valid {
+        JNI_TRUE
+    } else {
+        JNI_FALSE
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_MnemonicsEntry_wordsSize(
+    _: JNIEnv,
+    _: JClass,
+    entry: jlong,
+) -> jint {
+    let entry = entry as MnemonicsEntryPtr;
+    unsafe { mnemonics_entry_words_size(entry) as jint }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_MnemonicsEntry_wordsGet(
+    env: JNIEnv,
+    _: JClass,
+    entry: jlong,
+    index: jint,
+) -> jstring {
+    let entry = entry as MnemonicsEntryPtr;
+    word_get(env, index, |index, word_out, word_length_out| unsafe {
+        mnemonics_entry_words_get(entry, index, word_out, word_length_out)
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_MnemonicsEntry_completionsSize(
+    _: JNIEnv,
+    _: JClass,
+    entry: jlong,
+) -> jint {
+    let entry = entry as MnemonicsEntryPtr;
+    unsafe { mnemonics_entry_completions_size(entry) as jint }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_MnemonicsEntry_completionsGet(
+    env: JNIEnv,
+    _: JClass,
+    entry: jlong,
+    index: jint,
+) -> jstring {
+    let entry = entry as MnemonicsEntryPtr;
+    word_get(env, index, |index, word_out, word_length_out| unsafe {
+        mnemonics_entry_completions_get(entry, index, word_out, word_length_out)
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_iohk_jormungandrwallet_MnemonicsEntry_delete(
+    _: JNIEnv,
+    _: JClass,
+    entry: jlong,
+) {
+    let entry = entry as MnemonicsEntryPtr;
+    if !entry.is_null() {
+        wallet_delete_mnemonics_entry(entry);
+    }
+}
+
+fn word_get<F>(env: JNIEnv, index: jint, get: F) -> jstring
+where
+    F: FnOnce(usize, *mut *const u8, *mut usize) -> wallet_core::Result,
+{
+    if index.is_negative() {
+        let _ = env.throw_new(
+            "java/lang/IndexOutOfBoundsException",
+            "Word index should be a positive number",
+        );
+        return null_mut();
+    }
+
+    let mut word_out: *const u8 = null();
+    let mut word_length_out = 0usize;
+
+    let result = get(
+        index as usize,
+        (&mut word_out) as *mut *const u8,
+        (&mut word_length_out) as *mut usize,
+    );
+
+    match result.error() {
+        None => {
+            let bytes = unsafe { std::slice::from_raw_parts(word_out, word_length_out) };
+            let word = std::str::from_utf8(bytes).expect("dictionary words are utf8");
+
+            env.new_string(word)
+                .expect("Failed to create new string")
+                .into_inner()
+        }
+        Some(error) => {
+            let _ = env.throw(error.to_string());
+            null_mut()
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "system" fn Java_com_iohk_jormungandrwallet_SymmetricCipher_decrypt(
     env: JNIEnv,
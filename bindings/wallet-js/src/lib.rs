@@ -27,6 +27,35 @@ pub struct Settings(wallet_core::Settings);
 #[wasm_bindgen]
 pub struct Conversion(wallet_core::Conversion);
 
+#[wasm_bindgen]
+pub struct BlockUpdate(wallet_core::BlockUpdate);
+
+#[wasm_bindgen]
+pub struct Reconciliation(wallet_core::Reconciliation);
+
+/// the legacy wallet schemes recovered along with the account, see
+/// `Wallet::recovered_schemes`
+#[wasm_bindgen]
+pub struct RecoveredSchemes(wallet_core::RecoveredSchemes);
+
+/// how the node's spending counter of the account compares to the local
+/// confirmed spending counter
+#[wasm_bindgen]
+pub enum SyncStatus {
+    /// the node and the local state use the same spending counter
+    Consistent,
+    /// the node already applied transactions of the account that are not
+    /// confirmed in the local state
+    Ahead,
+    /// the node did not apply yet transactions of the account that are
+    /// confirmed in the local state
+    Behind,
+}
+
+/// the mnemonics entered so far by the user, see `MnemonicsEntry::check`
+#[wasm_bindgen]
+pub struct MnemonicsEntry(wallet_core::MnemonicsEntry);
+
 #[wasm_bindgen]
 pub struct Proposal(wallet_core::Proposal);
 
@@ -64,6 +93,9 @@ pub struct EncryptingVoteKey(chain_vote::EncryptingVoteKey);
 extern "C" {
     #[wasm_bindgen(typescript_type = "Array<FragmentId>")]
     pub type FragmentIds;
+
+    #[wasm_bindgen(typescript_type = "Array<string>")]
+    pub type Words;
 }
 
 #[wasm_bindgen]
@@ -76,7 +108,8 @@ impl Wallet {
     /// You can also use this function to recover a wallet even after you have
     /// transferred all the funds to the new format (see the _convert_ function)
     ///
-    /// the mnemonics should be in english
+    /// the mnemonics can be in any of the languages of the bip39 dictionaries,
+    /// the language is detected from the words
     pub fn recover(mnemonics: &str, password: &[u8]) -> Result<Wallet, JsValue> {
         wallet_core::Wallet::recover(mnemonics, password)
             .map_err(|e| JsValue::from(e.to_string()))
@@ -97,6 +130,46 @@ impl Wallet {
             .map(Wallet)
     }
 
+    /// the legacy wallet schemes recovered along with the account, the
+    /// funds of the schemes that were not recovered are not retrieved
+    pub fn recovered_schemes(&self) -> RecoveredSchemes {
+        RecoveredSchemes(self.0.recovered_schemes())
+    }
+
+    /// export the whole state of the wallet (keys, UTxOs, account state
+    /// and pending transactions) so it can be restored later with
+    /// `import_wallet`
+    ///
+    /// the exported wallet is encrypted with the `password`, unless it is
+    /// empty.
+    pub fn export_wallet(&self, password: &[u8]) -> Result<Box<[u8]>, JsValue> {
+        let password = if password.is_empty() {
+            None
+        } else {
+            Some(password)
+        };
+
+        self.0
+            .export(password)
+            .map_err(|e| JsValue::from(e.to_string()))
+    }
+
+    /// import a wallet exported with `export_wallet`
+    ///
+    /// the `password` is the one used to export the wallet, leave it empty
+    /// if the wallet was exported without password.
+    pub fn import_wallet(bytes: &[u8], password: &[u8]) -> Result<Wallet, JsValue> {
+        let password = if password.is_empty() {
+            None
+        } else {
+            Some(password)
+        };
+
+        wallet_core::Wallet::import(bytes, password)
+            .map_err(|e| JsValue::from(e.to_string()))
+            .map(Wallet)
+    }
+
     pub fn convert(&mut self, settings: &Settings) -> Conversion {
         Conversion(self.0.convert(settings.0.clone()))
     }
@@ -148,6 +221,88 @@ impl Wallet {
         self.0.set_state(wallet_core::Value(value), counter);
     }
 
+    /// same as `set_state` but returns the reconciliation report: how the
+    /// node's state compares to the local state, the pending transactions
+    /// that have been dropped and the transactions confirmed locally that
+    /// are part of the node's state.
+    pub fn reconcile_state(&mut self, value: u64, counter: u32) -> Reconciliation {
+        Reconciliation(self.0.set_state(wallet_core::Value(value), counter))
+    }
+
+    /// Send `value` to the given `address` from the wallet's account
+    ///
+    /// This function outputs a fragment containing the signed transaction.
+    /// The fees are paid by the account on top of the `value`.
+    ///
+    /// # Parameters
+    ///
+    /// * `settings` - ledger settings.
+    /// * `address` - the binary encoded address to send the funds to.
+    /// * `value` - the amount of lovelace to send.
+    ///
+    /// # Errors
+    ///
+    /// The error is returned when the address is not valid or when the
+    /// account does not have enough funds to pay for the `value` and the fees.
+    pub fn send(
+        &mut self,
+        settings: &Settings,
+        address: &[u8],
+        value: u64,
+    ) -> Result<Box<[u8]>, JsValue> {
+        let address =
+            wallet_core::Address::from_bytes(address).map_err(|e| JsValue::from(e.to_string()))?;
+
+        self.0
+            .send(settings.0.clone(), address, wallet_core::Value(value))
+            .map_err(|e| JsValue::from(e.to_string()))
+    }
+
+    /// Delegate the stake of the account to the given pools
+    ///
+    /// This function outputs a fragment containing a certificate transaction,
+    /// the fees are paid by the account.
+    ///
+    /// # Parameters
+    ///
+    /// * `settings` - ledger settings.
+    /// * `pools` - the identifiers of the pools, 32 bytes each. Empty to
+    ///   remove the delegation.
+    /// * `ratios` - the ratio of the stake to delegate to each pool, one byte
+    ///   per pool.
+    /// * `owner` - build an owner stake delegation certificate instead of a
+    ///   stake delegation certificate signed by the account.
+    ///
+    /// # Errors
+    ///
+    /// The error is returned when there is not a ratio per pool, when the
+    /// ratios are invalid or when the account does not have enough funds to
+    /// pay for the fees.
+    pub fn delegate(
+        &mut self,
+        settings: &Settings,
+        pools: &[u8],
+        ratios: &[u8],
+        owner: bool,
+    ) -> Result<Box<[u8]>, JsValue> {
+        if pools.len() != ratios.len() * 32 {
+            return Err(JsValue::from_str("every pool needs a ratio"));
+        }
+
+        let pools: Vec<(wallet_core::PoolId, u8)> = pools
+            .chunks(32)
+            .zip(ratios)
+            .map(|(pool, ratio)| {
+                let pool: [u8; 32] = pool.try_into().unwrap();
+                (pool.into(), *ratio)
+            })
+            .collect();
+
+        self.0
+            .delegate(settings.0.clone(), &pools, owner)
+            .map_err(|e| JsValue::from(e.to_string()))
+    }
+
     /// Cast a vote
     ///
     /// This function outputs a fragment containing a voting transaction.
@@ -199,6 +354,164 @@ impl Wallet {
             .collect::<Array>()
             .unchecked_into::<FragmentIds>()
     }
+
+    /// set how many blocks deep a transaction needs to be to be confirmed
+    /// by `apply_block`, and after how many blocks a pending transaction
+    /// that did not appear in a block is reported as expired.
+    ///
+    /// This resets the blocks applied so far.
+    pub fn set_confirmation_settings(&mut self, depth: u32, deadline: u32) {
+        self.0.set_confirmation_settings(depth, deadline);
+    }
+
+    /// apply a new block of the blockchain to the wallet
+    ///
+    /// `fragment_ids` are the identifiers of the fragments of the block,
+    /// concatenated. The pending transactions of the wallet that are in the
+    /// block are confirmed once the block is deep enough (see
+    /// `set_confirmation_settings`). If the parent of the block is not the
+    /// previous block applied, the blocks of the previous branch are
+    /// considered rolled back.
+    pub fn apply_block(
+        &mut self,
+        header_id: &[u8],
+        parent_id: &[u8],
+        chain_length: u32,
+        epoch: u32,
+        slot: u32,
+        fragment_ids: &[u8],
+    ) -> Result<BlockUpdate, JsValue> {
+        let header_id: [u8; 32] = header_id
+            .try_into()
+            .map_err(|_| JsValue::from_str("Invalid header id"))?;
+        let parent_id: [u8; 32] = parent_id
+            .try_into()
+            .map_err(|_| JsValue::from_str("Invalid parent id"))?;
+
+        let fragment_id_length = std::mem::size_of::<wallet_core::FragmentId>();
+        if fragment_ids.len() % fragment_id_length != 0 {
+            return Err(JsValue::from_str("Invalid fragment ids"));
+        }
+        let fragment_ids = fragment_ids.chunks(fragment_id_length).map(|id| {
+            let id: [u8; 32] = id.try_into().unwrap();
+            wallet_core::FragmentId::from(id)
+        });
+
+        self.0
+            .apply_block(
+                header_id.into(),
+                parent_id.into(),
+                chain_length,
+                wallet_core::BlockDate {
+                    epoch,
+                    slot_id: slot,
+                },
+                fragment_ids,
+            )
+            .map_err(|e| JsValue::from(e.to_string()))
+            .map(BlockUpdate)
+    }
+}
+
+#[wasm_bindgen]
+impl BlockUpdate {
+    /// the transactions that are now deep enough in the blockchain
+    pub fn confirmed(&self) -> FragmentIds {
+        fragment_ids(self.0.confirmed())
+    }
+
+    /// the pending transactions that did not appear in a block before the
+    /// deadline
+    pub fn expired(&self) -> FragmentIds {
+        fragment_ids(self.0.expired())
+    }
+}
+
+#[wasm_bindgen]
+impl RecoveredSchemes {
+    /// `true` if the Daedalus wallet was recovered, it is not recovered
+    /// when the mnemonics are protected with a password
+    pub fn daedalus(&self) -> bool {
+        self.0.daedalus
+    }
+
+    /// `true` if the Yoroi wallet was recovered
+    pub fn yoroi(&self) -> bool {
+        self.0.yoroi
+    }
+}
+
+#[wasm_bindgen]
+impl Reconciliation {
+    pub fn status(&self) -> SyncStatus {
+        match self.0.status() {
+            wallet_core::SyncStatus::Consistent => SyncStatus::Consistent,
+            wallet_core::SyncStatus::Ahead => SyncStatus::Ahead,
+            wallet_core::SyncStatus::Behind => SyncStatus::Behind,
+        }
+    }
+
+    /// the pending transactions that have been dropped because they use a
+    /// spending counter the node already used
+    pub fn dropped(&self) -> FragmentIds {
+        fragment_ids(self.0.dropped())
+    }
+
+    /// the transactions that were confirmed locally while an older
+    /// transaction was still pending, they are part of the node's state
+    pub fn confirmed(&self) -> FragmentIds {
+        fragment_ids(self.0.confirmed())
+    }
+}
+
+#[wasm_bindgen]
+impl MnemonicsEntry {
+    /// check the mnemonics entered so far by the user in the dictionary of
+    /// the given language (`english`, `japanese`, `chinese-simplified`...)
+    ///
+    /// The words followed by a space (or an ideographic space) are complete
+    /// and may be given by their prefix, the last word, if not followed by a
+    /// space, is the word being typed and is completed.
+    pub fn check(language: &str, mnemonics: &str) -> Result<MnemonicsEntry, JsValue> {
+        wallet_core::check_mnemonics(language, mnemonics)
+            .map_err(|e| JsValue::from(e.to_string()))
+            .map(MnemonicsEntry)
+    }
+
+    /// the complete words, resolved from their prefix
+    pub fn words(&self) -> Words {
+        words(self.0.words())
+    }
+
+    /// the words completing the word being typed, in the order of the
+    /// dictionary, empty if no word is being typed
+    pub fn completions(&self) -> Words {
+        words(self.0.completions())
+    }
+
+    /// `true` if the complete words are mnemonics of a supported length
+    /// with a valid checksum, they can then be given to `Wallet::recover`
+    pub fn is_valid(&self) -> bool {
+        self.0.is_valid()
+    }
+}
+
+fn words(words: &[&'static str]) -> Words {
+    words
+        .iter()
+        .copied()
+        .map(JsValue::from_str)
+        .collect::<Array>()
+        .unchecked_into::<Words>()
+}
+
+fn fragment_ids(ids: &[wallet_core::FragmentId]) -> FragmentIds {
+    ids.iter()
+        .cloned()
+        .map(FragmentId)
+        .map(JsValue::from)
+        .collect::<Array>()
+        .unchecked_into::<FragmentIds>()
 }
 
 #[wasm_bindgen]
@@ -30,6 +30,33 @@ fn yoroi1() {
         .expect("to get the only transaction present in the conversion");
 }
 
+#[wasm_bindgen_test]
+fn check_mnemonics() {
+    let entry = MnemonicsEntry::check("english", "neck bul").unwrap();
+    let words = js_sys::Array::from(&entry.words());
+    let completions = js_sys::Array::from(&entry.completions());
+
+    assert_eq!(words.length(), 1);
+    assert_eq!(words.get(0).as_string().unwrap(), "neck");
+    assert_eq!(completions.length(), 3);
+    assert_eq!(completions.get(0).as_string().unwrap(), "bulb");
+    assert!(!entry.is_valid());
+
+    let entry = MnemonicsEntry::check(
+        "english",
+        "neck bulb teac ille soul cry moni claw amou bori prov vill riva draf ston ",
+    )
+    .unwrap();
+    let words = js_sys::Array::from(&entry.words());
+
+    assert_eq!(words.length(), 15);
+    assert_eq!(words.get(14).as_string().unwrap(), "stone");
+    assert_eq!(js_sys::Array::from(&entry.completions()).length(), 0);
+    assert!(entry.is_valid());
+
+    assert!(MnemonicsEntry::check("english", "neck xx").is_err());
+}
+
 #[wasm_bindgen_test]
 fn gen_key() {
     // just test that the random generator works
@@ -0,0 +1,203 @@
+//! Help the users to enter their mnemonics word by word.
+//!
+//! The words of the dictionaries are unique by their first letters (the
+//! first four for the english dictionary), a user interface can complete the
+//! word being typed and accept the prefixes of the words.
+//!
+//! * [`Language::lookup_prefix`] lists the words starting with a prefix;
+//! * [`Language::resolve_prefix`] finds the word a prefix stands for;
+//! * [`check_partial`] checks the mnemonics entered so far.
+//!
+//! [`Language::lookup_prefix`]: ../dictionary/trait.Language.html#method.lookup_prefix
+//! [`Language::resolve_prefix`]: ../dictionary/trait.Language.html#method.resolve_prefix
+//! [`check_partial`]: ./fn.check_partial.html
+
+use crate::{
+    dictionary::{self, Language, IDEOGRAPHIC_SPACE},
+    normalization, Entropy, Error, MnemonicIndex, Mnemonics, Result, Type,
+};
+
+/// the mnemonics entered so far, see [`check_partial`](./fn.check_partial.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialMnemonics {
+    words: Vec<MnemonicIndex>,
+    completions: Vec<MnemonicIndex>,
+}
+
+impl PartialMnemonics {
+    /// the complete words, each one resolved from its prefix
+    pub fn words(&self) -> &[MnemonicIndex] {
+        &self.words
+    }
+
+    /// the words completing the word being typed, in the order of the
+    /// dictionary, empty if no word is being typed
+    pub fn completions(&self) -> &[MnemonicIndex] {
+        &self.completions
+    }
+
+    /// the mnemonics if the complete words are of a supported
+    /// [`Type`](../enum.Type.html) and have a valid checksum
+    pub fn mnemonics(&self) -> Option<Mnemonics> {
+        let mnemonics = Mnemonics::from_mnemonics(self.words.clone()).ok()?;
+        Entropy::from_mnemonics(&mnemonics).ok().map(|_| mnemonics)
+    }
+}
+
+/// check the mnemonics entered so far
+///
+/// The words are separated with spaces or with ideographic spaces (see
+/// [`split_words`]). The words followed by a separator are complete, they may be given by their prefix
+/// (see [`Language::resolve_prefix`]). The last word, if it is not followed
+/// by a separator, is the word being typed and is completed.
+///
+/// # Error
+///
+/// * `MnemonicWordNotFoundInDictionary` if a complete word is not in the
+///   dictionary or if no word completes the word being typed;
+/// * `AmbiguousPrefix` if a complete word is the prefix of more than one
+///   word;
+/// * `WrongNumberOfWords` if there are more complete words than the
+///   largest supported mnemonics.
+///
+/// # Example
+///
+/// ```
+/// # use bip39::*;
+///
+/// let partial = completion::check_partial(&dictionary::ENGLISH, "aban abandon abo").unwrap();
+///
+/// assert_eq!(partial.words().len(), 2);
+/// assert_eq!(partial.completions().len(), 2); // about, above
+/// assert!(partial.mnemonics().is_none());
+/// ```
+///
+/// [`split_words`]: ../dictionary/fn.split_words.html
+/// [`Language::resolve_prefix`]: ../dictionary/trait.Language.html#method.resolve_prefix
+pub fn check_partial<D>(dic: &D, input: &str) -> Result<PartialMnemonics>
+where
+    D: Language,
+{
+    let input = normalization::nfkd(input);
+    let mut pieces: Vec<&str> = input
+        .split(|c| c == ' ' || c == IDEOGRAPHIC_SPACE)
+        .collect();
+    // the last piece is the word being typed, empty if the input ends
+    // with a separator
+    let typed = pieces.pop().unwrap_or("");
+
+    let words = pieces
+        .into_iter()
+        .filter(|word| !word.is_empty())
+        .map(|word| dic.resolve_prefix(word))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let max = Type::Type24Words.mnemonic_count();
+    if words.len() > max {
+        return Err(Error::WrongNumberOfWords(words.len()));
+    }
+
+    let completions = if typed.is_empty() {
+        Vec::new()
+    } else {
+        let completions = dic.lookup_prefix(typed);
+        if completions.is_empty() {
+            return Err(
+                dictionary::Error::MnemonicWordNotFoundInDictionary(typed.to_owned()).into(),
+            );
+        }
+        completions
+    };
+
+    Ok(PartialMnemonics { words, completions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::Error as DictionaryError;
+
+    const MNEMONICS: &str =
+        "legal winner thank year wave sausage worth useful legal winner thank yellow";
+
+    fn words(indices: &[MnemonicIndex]) -> Vec<String> {
+        indices
+            .iter()
+            .map(|index| dictionary::ENGLISH.lookup_word(*index).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn lookup_prefix() {
+        assert_eq!(
+            words(&dictionary::ENGLISH.lookup_prefix("abo")),
+            vec!["about", "above"]
+        );
+        assert_eq!(
+            words(&dictionary::ENGLISH.lookup_prefix("zoo")),
+            vec!["zoo"]
+        );
+        assert!(dictionary::ENGLISH.lookup_prefix("xx").is_empty());
+        assert_eq!(dictionary::ENGLISH.lookup_prefix("").len(), 2048);
+    }
+
+    #[test]
+    fn resolve_prefix() {
+        let word = |prefix| {
+            dictionary::ENGLISH
+                .resolve_prefix(prefix)
+                .map(|index| dictionary::ENGLISH.lookup_word(index).unwrap())
+        };
+
+        assert_eq!(word("aban").unwrap(), "abandon");
+        assert_eq!(word("abandon").unwrap(), "abandon");
+        // the word itself, even though "action" starts with it
+        assert_eq!(word("act").unwrap(), "act");
+        assert!(matches!(
+            word("ab"),
+            Err(DictionaryError::AmbiguousPrefix(_))
+        ));
+        assert!(matches!(
+            word("xx"),
+            Err(DictionaryError::MnemonicWordNotFoundInDictionary(_))
+        ));
+    }
+
+    #[test]
+    fn check_prefixes() {
+        let prefixes: Vec<String> = MNEMONICS
+            .split(' ')
+            .map(|word| word.chars().take(4).collect())
+            .collect();
+        let partial = check_partial(&dictionary::ENGLISH, &(prefixes.join(" ") + " ")).unwrap();
+
+        assert_eq!(words(partial.words()).join(" "), MNEMONICS);
+        assert!(partial.completions().is_empty());
+        assert!(partial.mnemonics().is_some());
+    }
+
+    #[test]
+    fn check_word_being_typed() {
+        let partial = check_partial(&dictionary::ENGLISH, "legal winn").unwrap();
+        assert_eq!(words(partial.words()), vec!["legal"]);
+        assert_eq!(words(partial.completions()), vec!["winner"]);
+        assert!(partial.mnemonics().is_none());
+
+        let partial = check_partial(&dictionary::ENGLISH, "").unwrap();
+        assert!(partial.words().is_empty());
+        assert!(partial.completions().is_empty());
+
+        assert!(check_partial(&dictionary::ENGLISH, "legal xx").is_err());
+    }
+
+    #[test]
+    fn check_invalid_mnemonics() {
+        let invalid = MNEMONICS.replacen("legal", "year", 1) + " ";
+        let partial = check_partial(&dictionary::ENGLISH, &invalid).unwrap();
+        assert_eq!(partial.words().len(), 12);
+        assert!(partial.mnemonics().is_none());
+
+        assert!(check_partial(&dictionary::ENGLISH, "ab abandon ").is_err());
+        assert!(check_partial(&dictionary::ENGLISH, &"abandon ".repeat(25)).is_err());
+    }
+}
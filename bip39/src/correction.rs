@@ -0,0 +1,243 @@
+//! Help the users to correct the mnemonics they entered.
+//!
+//! * [`closest_words`] suggests the words of the dictionary close to a
+//!   mistyped word (one not in the dictionary);
+//! * [`missing_word`] lists the mnemonics with a valid checksum once the
+//!   word that was forgotten is inserted back;
+//! * [`swapped_words`] lists the mnemonics with a valid checksum once two
+//!   words entered in the wrong order are swapped back.
+//!
+//! The checksum only protects a few bits of the mnemonics (see
+//! [`Entropy::checksum`]), there are often many candidates and only the
+//! user can tell which one is theirs.
+//!
+//! [`closest_words`]: ./fn.closest_words.html
+//! [`missing_word`]: ./fn.missing_word.html
+//! [`swapped_words`]: ./fn.swapped_words.html
+//! [`Entropy::checksum`]: ../enum.Entropy.html#method.checksum
+
+use crate::{
+    dictionary::DefaultDictionary, normalization, Entropy, MnemonicIndex, Mnemonics, Result, Type,
+};
+
+/// the words of the dictionary at most `max_distance` edits (insertion,
+/// deletion or substitution of a character) away from the given word, the
+/// closest first
+///
+/// # Example
+///
+/// ```
+/// # use bip39::*;
+///
+/// let words = correction::closest_words(&dictionary::ENGLISH, "abandn", 1);
+///
+/// assert_eq!(words, vec!["abandon"]);
+/// ```
+///
+pub fn closest_words(
+    dic: &DefaultDictionary,
+    word: &str,
+    max_distance: usize,
+) -> Vec<&'static str> {
+    let word: Vec<char> = normalization::nfkd(word).chars().collect();
+
+    let mut candidates: Vec<(usize, &'static str)> = dic
+        .words
+        .iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(&word, candidate);
+            if distance <= max_distance {
+                Some((distance, *candidate))
+            } else {
+                None
+            }
+        })
+        .collect();
+    // the sort is stable: the words at the same distance stay in the
+    // order of the dictionary
+    candidates.sort_by_key(|(distance, _)| *distance);
+
+    candidates.into_iter().map(|(_, word)| word).collect()
+}
+
+/// the mnemonics with a valid checksum obtained by inserting one word
+/// anywhere in the given mnemonics, one word short of a supported
+/// [`Type`](../enum.Type.html)
+///
+/// Every candidate is listed once: inserting a word next to the same word
+/// gives the same mnemonics before or after it.
+///
+/// # Error
+///
+/// fails with [`WrongNumberOfWords`](../enum.Error.html#variant.WrongNumberOfWords)
+/// if the mnemonics with one more word are not of a supported size.
+///
+pub fn missing_word(mnemonics: &[MnemonicIndex]) -> Result<Vec<Mnemonics>> {
+    let _ = Type::from_word_count(mnemonics.len() + 1)?;
+
+    let mut candidates = Vec::new();
+    for position in 0..=mnemonics.len() {
+        for word in 0..=crate::MAX_MNEMONIC_VALUE {
+            // already a candidate with the word inserted before its equal
+            if position > 0 && mnemonics[position - 1] == MnemonicIndex(word) {
+                continue;
+            }
+
+            let mut candidate = Vec::with_capacity(mnemonics.len() + 1);
+            candidate.extend_from_slice(&mnemonics[..position]);
+            candidate.push(MnemonicIndex(word));
+            candidate.extend_from_slice(&mnemonics[position..]);
+
+            if let Some(candidate) = with_valid_checksum(candidate) {
+                candidates.push(candidate);
+            }
+        }
+    }
+
+    Ok(candidates)
+}
+
+/// the mnemonics with a valid checksum obtained by swapping two different
+/// words of the given mnemonics
+///
+/// The given mnemonics are expected to have an invalid checksum, they are
+/// not part of the candidates.
+///
+pub fn swapped_words(mnemonics: &Mnemonics) -> Vec<Mnemonics> {
+    let words = mnemonics.as_ref();
+
+    let mut candidates = Vec::new();
+    for i in 0..words.len() {
+        for j in (i + 1)..words.len() {
+            if words[i] == words[j] {
+                continue;
+            }
+
+            let mut candidate = words.to_vec();
+            candidate.swap(i, j);
+
+            if let Some(candidate) = with_valid_checksum(candidate) {
+                candidates.push(candidate);
+            }
+        }
+    }
+
+    candidates
+}
+
+fn with_valid_checksum(words: Vec<MnemonicIndex>) -> Option<Mnemonics> {
+    let mnemonics = Mnemonics::from_mnemonics(words).ok()?;
+    Entropy::from_mnemonics(&mnemonics).ok().map(|_| mnemonics)
+}
+
+/// the Levenshtein distance between the two words
+fn edit_distance(word: &[char], candidate: &str) -> usize {
+    let mut previous: Vec<usize> = (0..=word.len()).collect();
+    let mut current = Vec::with_capacity(previous.len());
+
+    for (j, c) in candidate.chars().enumerate() {
+        current.clear();
+        current.push(j + 1);
+        for (i, w) in word.iter().enumerate() {
+            let substitution = previous[i] + if *w == c { 0 } else { 1 };
+            let deletion = previous[i + 1] + 1;
+            let insertion = current[i] + 1;
+            current.push(substitution.min(deletion).min(insertion));
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[word.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::{self, Language as _};
+
+    fn mnemonics(words: &str) -> Vec<MnemonicIndex> {
+        words
+            .split(' ')
+            .map(|word| dictionary::ENGLISH.lookup_mnemonic(word).unwrap())
+            .collect()
+    }
+
+    const MNEMONICS: &str =
+        "legal winner thank year wave sausage worth useful legal winner thank yellow";
+
+    const ABANDON: &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn edit_distances() {
+        let word: Vec<char> = "kitten".chars().collect();
+        assert_eq!(edit_distance(&word, "kitten"), 0);
+        assert_eq!(edit_distance(&word, "sitting"), 3);
+        assert_eq!(edit_distance(&word, ""), 6);
+        assert_eq!(edit_distance(&[], "kitten"), 6);
+    }
+
+    #[test]
+    fn closest_words_first() {
+        assert_eq!(
+            closest_words(&dictionary::ENGLISH, "abandn", 1),
+            vec!["abandon"]
+        );
+        assert_eq!(closest_words(&dictionary::ENGLISH, "zoo", 0), vec!["zoo"]);
+        assert_eq!(
+            closest_words(&dictionary::ENGLISH, "lettre", 2),
+            vec!["lecture", "letter", "little", "retire", "settle"]
+        );
+        assert!(closest_words(&dictionary::ENGLISH, "xxxxxxxxxx", 2).is_empty());
+    }
+
+    #[test]
+    fn recover_the_missing_word() {
+        let mut words = mnemonics(MNEMONICS);
+        words.remove(5);
+
+        let candidates = missing_word(&words).unwrap();
+        assert!(candidates
+            .iter()
+            .any(|candidate| candidate.as_ref() == mnemonics(MNEMONICS).as_slice()));
+        assert!(candidates
+            .iter()
+            .all(|candidate| Entropy::from_mnemonics(candidate).is_ok()));
+        let mut unique = candidates.clone();
+        unique.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+        unique.dedup();
+        assert_eq!(unique.len(), candidates.len());
+
+        // the missing word is the same as its neighbours: the mnemonics are
+        // the same wherever the word is inserted among them
+        let mut words = mnemonics(ABANDON);
+        words.remove(3);
+        let candidates = missing_word(&words).unwrap();
+        assert_eq!(
+            candidates
+                .iter()
+                .filter(|candidate| candidate.as_ref() == mnemonics(ABANDON).as_slice())
+                .count(),
+            1
+        );
+
+        words.remove(0);
+        assert!(missing_word(&words).is_err());
+    }
+
+    #[test]
+    fn recover_the_swapped_words() {
+        let mut words = mnemonics(MNEMONICS);
+        words.swap(2, 7);
+        let swapped = Mnemonics::from_mnemonics(words).unwrap();
+        assert!(Entropy::from_mnemonics(&swapped).is_err());
+
+        let candidates = swapped_words(&swapped);
+        assert!(candidates
+            .iter()
+            .any(|candidate| candidate.as_ref() == mnemonics(MNEMONICS).as_slice()));
+        assert!(candidates
+            .iter()
+            .all(|candidate| Entropy::from_mnemonics(candidate).is_ok()));
+    }
+}
@@ -7,12 +7,13 @@
 //! Because this module is part of the `chain_wallet` crate and that we
 //! need to keep the dependencies as small as possible we do not support
 //! UTF8 NFKD by default. Users must be sure to compose (or decompose)
-//! our output (or input) UTF8 strings.
+//! our output (or input) UTF8 strings, or enable the `nfkd` feature to
+//! normalize the inputs.
 //!
 
 use thiserror::Error;
 
-use crate::MnemonicIndex;
+use crate::{normalization, Entropy, MnemonicIndex, Mnemonics, MAX_MNEMONIC_VALUE};
 
 /// Errors associated to a given language/dictionary
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Error)]
@@ -20,6 +21,19 @@ pub enum Error {
     /// this means the given word is not in the Dictionary of the Language.
     #[error("Mnemonic word not found in dictionary \"{0}\"")]
     MnemonicWordNotFoundInDictionary(String),
+
+    /// none of the default dictionaries contains all the given words.
+    #[error("The mnemonic words are not all in one of the dictionaries")]
+    UnknownLanguage,
+
+    /// the given words are in more than one of the default dictionaries,
+    /// the parameter contains the names of these dictionaries.
+    #[error("The mnemonic words are in more than one dictionary: {0:?}")]
+    AmbiguousLanguage(Vec<&'static str>),
+
+    /// more than one word of the dictionary starts with the given prefix.
+    #[error("More than one mnemonic word starts with \"{0}\"")]
+    AmbiguousPrefix(String),
 }
 
 /// trait to represent the the properties that needs to be associated to
@@ -30,14 +44,53 @@ pub trait Language {
     fn separator(&self) -> &'static str;
     fn lookup_mnemonic(&self, word: &str) -> Result<MnemonicIndex, Error>;
     fn lookup_word(&self, mnemonic: MnemonicIndex) -> Result<String, Error>;
+
+    /// the words of the dictionary starting with the given prefix, in the
+    /// order of the dictionary
+    ///
+    /// the default implementation looks up every word of the dictionary
+    /// with `lookup_word`.
+    fn lookup_prefix(&self, prefix: &str) -> Vec<MnemonicIndex> {
+        (0..=MAX_MNEMONIC_VALUE)
+            .map(MnemonicIndex)
+            .filter(|index| {
+                self.lookup_word(*index)
+                    .map_or(false, |word| word.starts_with(prefix))
+            })
+            .collect()
+    }
+
+    /// the word the given prefix stands for: the word equal to the prefix
+    /// or else the only word starting with the prefix
+    ///
+    /// # Error
+    ///
+    /// * `MnemonicWordNotFoundInDictionary` if no word starts with the
+    ///   prefix;
+    /// * `AmbiguousPrefix` if more than one word starts with the prefix.
+    ///
+    fn resolve_prefix(&self, prefix: &str) -> Result<MnemonicIndex, Error> {
+        if let Ok(index) = self.lookup_mnemonic(prefix) {
+            return Ok(index);
+        }
+
+        match self.lookup_prefix(prefix).as_slice() {
+            [] => Err(Error::MnemonicWordNotFoundInDictionary(prefix.to_owned())),
+            [index] => Ok(*index),
+            _ => Err(Error::AmbiguousPrefix(prefix.to_owned())),
+        }
+    }
 }
 
 /// Default Dictionary basic support for the different main languages.
-/// This dictionary expect the inputs to have been normalized (UTF-8 NFKD).
+/// This dictionary expect the inputs to have been normalized (UTF-8 NFKD),
+/// unless the `nfkd` feature is enabled. The words of the dictionary are
+/// always normalized.
 ///
 /// If you wish to implement support for non pre-normalized form you can
-/// create reuse this dictionary in a custom struct and implement support
-/// for [`Language`](./trait.Language.html) accordingly (_hint_: use
+/// enable the `nfkd` feature or reuse this dictionary in a custom struct
+/// and implement support for [`Language`](./trait.Language.html)
+/// accordingly (_hint_: use
 /// [`unicode-normalization`](https://crates.io/crates/unicode-normalization)).
 ///
 pub struct DefaultDictionary {
@@ -53,7 +106,8 @@ impl Language for DefaultDictionary {
         " "
     }
     fn lookup_mnemonic(&self, word: &str) -> Result<MnemonicIndex, Error> {
-        match self.words.iter().position(|x| x == &word) {
+        let word = normalization::nfkd(word);
+        match self.words.iter().position(|x| *x == word) {
             None => Err(Error::MnemonicWordNotFoundInDictionary(word.to_string())),
             Some(v) => {
                 Ok(
@@ -68,6 +122,15 @@ impl Language for DefaultDictionary {
     fn lookup_word(&self, mnemonic: MnemonicIndex) -> Result<String, Error> {
         Ok(unsafe { self.words.get_unchecked(mnemonic.0 as usize) }).map(|s| String::from(*s))
     }
+    fn lookup_prefix(&self, prefix: &str) -> Vec<MnemonicIndex> {
+        let prefix = normalization::nfkd(prefix);
+        self.words
+            .iter()
+            .enumerate()
+            .filter(|(_, word)| word.starts_with(&*prefix))
+            .map(|(index, _)| MnemonicIndex(index as u16))
+            .collect()
+    }
 }
 
 /// default English dictionary as provided by the
@@ -132,3 +195,91 @@ pub const SPANISH: DefaultDictionary = DefaultDictionary {
     words: include!("bip39_spanish.txt"),
     name: "spanish",
 };
+
+/// all the default dictionaries, the languages that can be recognised with
+/// [`detect_language`](./fn.detect_language.html)
+pub const DEFAULT_DICTIONARIES: [&DefaultDictionary; 8] = [
+    &ENGLISH,
+    &FRENCH,
+    &JAPANESE,
+    &KOREAN,
+    &CHINESE_SIMPLIFIED,
+    &CHINESE_TRADITIONAL,
+    &ITALIAN,
+    &SPANISH,
+];
+
+/// the separator of the Japanese mnemonic words, it becomes a simple space
+/// once normalized (UTF-8 NFKD)
+pub const IDEOGRAPHIC_SPACE: char = '\u{3000}';
+
+/// split the given mnemonic words separated with spaces or with ideographic
+/// spaces (Japanese mnemonics)
+pub fn split_words(mnemonics: &str) -> impl Iterator<Item = &str> {
+    mnemonics
+        .split(|c| c == ' ' || c == IDEOGRAPHIC_SPACE)
+        .filter(|word| !word.is_empty())
+}
+
+/// detect the language of the given mnemonic words among the
+/// [`DEFAULT_DICTIONARIES`](./constant.DEFAULT_DICTIONARIES.html)
+///
+/// The words are split with [`split_words`](./fn.split_words.html). Some
+/// words are in more than one dictionary (English and French, or the
+/// Chinese dictionaries), in this case only the dictionaries in which the
+/// checksum of the mnemonics is valid are kept.
+///
+/// # Error
+///
+/// * [`UnknownLanguage`](enum.Error.html#variant.UnknownLanguage) if none
+///   of the dictionaries contains all the words;
+/// * [`AmbiguousLanguage`](enum.Error.html#variant.AmbiguousLanguage) if
+///   the mnemonics are valid in more than one dictionary.
+///
+pub fn detect_language(mnemonics: &str) -> Result<&'static DefaultDictionary, Error> {
+    let words: Vec<&str> = split_words(mnemonics).collect();
+    if words.is_empty() {
+        return Err(Error::UnknownLanguage);
+    }
+
+    let candidates: Vec<&'static DefaultDictionary> = DEFAULT_DICTIONARIES
+        .iter()
+        .copied()
+        .filter(|dic| words.iter().all(|word| dic.lookup_mnemonic(word).is_ok()))
+        .collect();
+
+    let candidates = if candidates.len() > 1 {
+        let valid: Vec<&'static DefaultDictionary> = candidates
+            .iter()
+            .copied()
+            .filter(|dic| has_valid_checksum(*dic, &words))
+            .collect();
+        if valid.is_empty() {
+            candidates
+        } else {
+            valid
+        }
+    } else {
+        candidates
+    };
+
+    match candidates.as_slice() {
+        [] => Err(Error::UnknownLanguage),
+        [dic] => Ok(*dic),
+        _ => Err(Error::AmbiguousLanguage(
+            candidates.iter().map(|dic| dic.name).collect(),
+        )),
+    }
+}
+
+fn has_valid_checksum(dic: &DefaultDictionary, words: &[&str]) -> bool {
+    words
+        .iter()
+        .map(|word| dic.lookup_mnemonic(word))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()
+        .and_then(|indices| Mnemonics::from_mnemonics(indices).ok())
+        .map_or(false, |mnemonics| {
+            Entropy::from_mnemonics(&mnemonics).is_ok()
+        })
+}
@@ -57,9 +57,12 @@ mod bits;
 mod entropy;
 mod error;
 mod mnemonic;
+mod normalization;
 mod seed;
 mod types;
 
+pub mod completion;
+pub mod correction;
 pub mod dictionary;
 
 pub use self::{
@@ -121,6 +124,69 @@ mod test {
         assert_eq!(entropy.as_ref(), entropy2.as_ref());
     }
 
+    #[test]
+    fn detect_language() {
+        let english = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        assert_eq!(
+            dictionary::detect_language(english).unwrap().name,
+            "english"
+        );
+
+        let french = Entropy::Entropy12([0x7f; 16])
+            .to_mnemonics()
+            .to_string(&dictionary::FRENCH);
+        assert_eq!(dictionary::detect_language(&french).unwrap().name, "french");
+
+        assert_eq!(
+            dictionary::detect_language("abandon zzz"),
+            Err(dictionary::Error::UnknownLanguage)
+        );
+        assert_eq!(
+            dictionary::detect_language(""),
+            Err(dictionary::Error::UnknownLanguage)
+        );
+    }
+
+    #[test]
+    fn detect_japanese_with_ideographic_spaces() {
+        for test in TEST_VECTORS_JAPANESE {
+            let mnemonics: String = test
+                .mnemonics
+                .nfkd()
+                .map(|c| {
+                    if c == ' ' {
+                        dictionary::IDEOGRAPHIC_SPACE
+                    } else {
+                        c
+                    }
+                })
+                .collect();
+
+            let dic = dictionary::detect_language(&mnemonics).expect("japanese mnemonics");
+            assert_eq!(dic.name, "japanese");
+            assert_eq!(
+                dictionary::split_words(&mnemonics).count(),
+                test.mnemonics.split(dictionary::IDEOGRAPHIC_SPACE).count()
+            );
+        }
+    }
+
+    #[test]
+    fn detect_ambiguous_language() {
+        // the words 0 and 3 are the same in both Chinese dictionaries
+        let mnemonics = Entropy::Entropy12([0; 16])
+            .to_mnemonics()
+            .to_string(&dictionary::CHINESE_SIMPLIFIED);
+
+        assert_eq!(
+            dictionary::detect_language(&mnemonics),
+            Err(dictionary::Error::AmbiguousLanguage(vec![
+                "chinese-simplified",
+                "chinese-traditional"
+            ]))
+        );
+    }
+
     #[derive(Debug)]
     struct TestVector {
         entropy: &'static str,
@@ -130,9 +196,15 @@ mod test {
     }
 
     fn mk_test<D: dictionary::Language>(test: &TestVector, dic: &D) {
-        // decompose the UTF8 inputs before processing:
-        let mnemonics: String = test.mnemonics.nfkd().collect();
-        let passphrase: String = test.passphrase.nfkd().collect();
+        // decompose the UTF8 inputs before processing, unless it is done
+        // by the `nfkd` feature:
+        #[cfg(not(feature = "nfkd"))]
+        let (mnemonics, passphrase): (String, String) = (
+            test.mnemonics.nfkd().collect(),
+            test.passphrase.nfkd().collect(),
+        );
+        #[cfg(feature = "nfkd")]
+        let (mnemonics, passphrase) = (test.mnemonics.to_owned(), test.passphrase.to_owned());
 
         let mnemonics_ref = Mnemonics::from_string(dic, &mnemonics).expect("valid mnemonics");
         let mnemonics_str = MnemonicString::new(dic, mnemonics).expect("valid mnemonics string");
@@ -170,7 +242,63 @@ mod test {
     fn test_vectors_japanese() {
         mk_tests(TEST_VECTORS_JAPANESE, &dictionary::JAPANESE)
     }
+    #[test]
+    fn test_vectors_french() {
+        mk_tests(TEST_VECTORS_FRENCH, &dictionary::FRENCH)
+    }
+    #[test]
+    fn test_vectors_spanish() {
+        mk_tests(TEST_VECTORS_SPANISH, &dictionary::SPANISH)
+    }
+    #[test]
+    fn test_vectors_italian() {
+        mk_tests(TEST_VECTORS_ITALIAN, &dictionary::ITALIAN)
+    }
+    #[test]
+    fn test_vectors_korean() {
+        mk_tests(TEST_VECTORS_KOREAN, &dictionary::KOREAN)
+    }
+    #[test]
+    fn test_vectors_chinese_simplified() {
+        mk_tests(
+            TEST_VECTORS_CHINESE_SIMPLIFIED,
+            &dictionary::CHINESE_SIMPLIFIED,
+        )
+    }
+    #[test]
+    fn test_vectors_chinese_traditional() {
+        mk_tests(
+            TEST_VECTORS_CHINESE_TRADITIONAL,
+            &dictionary::CHINESE_TRADITIONAL,
+        )
+    }
+
+    #[cfg(feature = "nfkd")]
+    #[test]
+    fn lookup_composed_words() {
+        // "é" composed (U+00E9) while the dictionary is decomposed
+        let word = "\u{e9}cluse";
+        let index = dictionary::FRENCH.lookup_mnemonic(word).unwrap();
+        assert_eq!(
+            dictionary::FRENCH.lookup_word(index).unwrap(),
+            "e\u{301}cluse"
+        );
+    }
 
     const TEST_VECTORS_ENGLISH: &[TestVector] = &include!("test_vectors/bip39_english.txt");
     const TEST_VECTORS_JAPANESE: &[TestVector] = &include!("test_vectors/bip39_japanese.txt");
+    // there are no published vectors for the other languages: they are
+    // generated by `test_vectors/generate.py`, written from the BIP39
+    // specification with the python standard library and checked against
+    // the english and japanese vectors above, from the entropies of the
+    // english vectors. The mnemonics are composed (UTF-8 NFC). See the
+    // script for how to reproduce them with python-mnemonic.
+    const TEST_VECTORS_FRENCH: &[TestVector] = &include!("test_vectors/bip39_french.txt");
+    const TEST_VECTORS_SPANISH: &[TestVector] = &include!("test_vectors/bip39_spanish.txt");
+    const TEST_VECTORS_ITALIAN: &[TestVector] = &include!("test_vectors/bip39_italian.txt");
+    const TEST_VECTORS_KOREAN: &[TestVector] = &include!("test_vectors/bip39_korean.txt");
+    const TEST_VECTORS_CHINESE_SIMPLIFIED: &[TestVector] =
+        &include!("test_vectors/bip39_chinese_simplified.txt");
+    const TEST_VECTORS_CHINESE_TRADITIONAL: &[TestVector] =
+        &include!("test_vectors/bip39_chinese_traditional.txt");
 }
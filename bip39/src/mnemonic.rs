@@ -1,4 +1,4 @@
-use crate::{dictionary, Error, Result, Type};
+use crate::{dictionary, normalization, Error, Result, Type};
 use std::{fmt, ops::Deref, str};
 
 /// the maximum authorized value for a mnemonic. i.e. 2047
@@ -40,6 +40,9 @@ impl MnemonicString {
     /// create a `MnemonicString` from the given `String`. This function
     /// will validate the mnemonic phrase against the given [`Language`]
     ///
+    /// With the `nfkd` feature the string is normalized (UTF-8 NFKD) first,
+    /// as it is used to generate the [`Seed`](./struct.Seed.html).
+    ///
     /// [`Language`]: ./dictionary/trait.Language.html
     ///
     /// # Example
@@ -61,6 +64,7 @@ impl MnemonicString {
     where
         D: dictionary::Language,
     {
+        let s = normalization::nfkd_string(s);
         let _ = Mnemonics::from_string(dic, &s)?;
 
         Ok(MnemonicString(s))
@@ -165,6 +169,10 @@ impl Mnemonics {
     /// Construct the `Mnemonics` from its string representation in the given
     /// [`Language`](./dictionary/trait.Language.html).
     ///
+    /// With the `nfkd` feature the string is normalized (UTF-8 NFKD) first,
+    /// so the ideographic spaces separating the Japanese words become
+    /// simple spaces.
+    ///
     /// # Error
     ///
     /// May fail with a [`LanguageError`](enum.Error.html#variant.LanguageError)
@@ -175,6 +183,7 @@ impl Mnemonics {
     where
         D: dictionary::Language,
     {
+        let mnemonics = normalization::nfkd(mnemonics);
         let mut vec = vec![];
         for word in mnemonics.split(dic.separator()) {
             vec.push(MnemonicIndex::from_word(dic, word)?);
@@ -0,0 +1,58 @@
+//! UTF-8 NFKD normalization of the mnemonic words and of the passphrases
+//!
+//! The normalization is only done with the `nfkd` feature, otherwise the
+//! inputs are expected to be normalized already and are left untouched.
+
+use std::borrow::Cow;
+
+#[cfg(feature = "nfkd")]
+fn is_nfkd(s: &str) -> bool {
+    use unicode_normalization::{is_nfkd_quick, IsNormalized};
+
+    is_nfkd_quick(s.chars()) == IsNormalized::Yes
+}
+
+#[cfg(feature = "nfkd")]
+pub(crate) fn nfkd(s: &str) -> Cow<'_, str> {
+    use unicode_normalization::UnicodeNormalization as _;
+
+    if is_nfkd(s) {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(s.nfkd().collect())
+    }
+}
+
+#[cfg(not(feature = "nfkd"))]
+pub(crate) fn nfkd(s: &str) -> Cow<'_, str> {
+    Cow::Borrowed(s)
+}
+
+/// normalize the given string, the original string is erased from memory
+/// if it needed to be normalized
+#[cfg(feature = "nfkd")]
+pub(crate) fn nfkd_string(mut s: String) -> String {
+    use unicode_normalization::UnicodeNormalization as _;
+
+    if is_nfkd(&s) {
+        return s;
+    }
+
+    let normalized = s.nfkd().collect();
+    unsafe { cryptoxide::util::secure_memset(s.as_mut_vec(), 0) };
+    normalized
+}
+
+#[cfg(not(feature = "nfkd"))]
+pub(crate) fn nfkd_string(s: String) -> String {
+    s
+}
+
+/// normalize the given bytes if they are a UTF-8 string, any other bytes
+/// are left untouched
+pub(crate) fn nfkd_bytes(bytes: &[u8]) -> Cow<'_, [u8]> {
+    match std::str::from_utf8(bytes).map(nfkd) {
+        Ok(Cow::Owned(normalized)) => Cow::Owned(normalized.into_bytes()),
+        _ => Cow::Borrowed(bytes),
+    }
+}
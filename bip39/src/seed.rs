@@ -1,4 +1,4 @@
-use crate::{Error, MnemonicString, Result};
+use crate::{normalization, Error, MnemonicString, Result};
 use cryptoxide::hmac::Hmac;
 use cryptoxide::pbkdf2::pbkdf2;
 use cryptoxide::sha2::Sha512;
@@ -66,6 +66,9 @@ impl Seed {
     /// Note that the `Seed` is not generated from the `Entropy` directly. It is a
     /// design choice of Bip39.
     ///
+    /// With the `nfkd` feature a UTF-8 password is normalized (UTF-8 NFKD)
+    /// as required by Bip39, any other password is used as it is.
+    ///
     /// # Safety
     ///
     /// The password is meant to allow plausible deniability. While it is possible
@@ -85,7 +88,7 @@ impl Seed {
     ///
     pub fn from_mnemonic_string(mnemonics: &MnemonicString, password: &[u8]) -> Self {
         let mut salt = Vec::from("mnemonic");
-        salt.extend_from_slice(password);
+        salt.extend_from_slice(&normalization::nfkd_bytes(password));
         let mut mac = Hmac::new(Sha512::new(), mnemonics.as_bytes());
         let mut result = [0; SEED_SIZE];
         pbkdf2(&mut mac, &salt, 2048, &mut result);
@@ -0,0 +1,147 @@
+#!/usr/bin/env python3
+
+# Generate the test vectors of the languages without published vectors.
+#
+# The mnemonics and the seeds are computed as described in the BIP39
+# specification (https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki),
+# with the python standard library only, from the entropies of the official
+# english vectors (https://github.com/trezor/python-mnemonic/blob/master/vectors.json)
+# and the word lists of the specification (bip-0039/<language>.txt in the
+# bitcoin/bips repository, copied in `bip39/src/bip39_<language>.txt`).
+#
+# Before writing anything, the script checks it computes the same mnemonics
+# and seeds as the official english vectors and as the japanese vectors
+# (https://github.com/bip32JP/bip32JP.github.io/blob/master/test_JP_BIP39.json).
+# The values can be reproduced independently with python-mnemonic:
+#
+#     m = Mnemonic("french")
+#     mnemonics = m.to_mnemonic(bytes.fromhex(entropy))
+#     seed = Mnemonic.to_seed(mnemonics, "TREZOR").hex()
+#
+# usage: ./generate.py [--check]
+#
+# with `--check` the vector files are compared to the generated vectors
+# instead of being written.
+
+from pathlib import Path
+import argparse
+import hashlib
+import json
+import re
+import sys
+import unicodedata
+
+script_directory = Path(__file__).parent
+dictionary_directory = script_directory.parent
+
+LANGUAGES = [
+    "french",
+    "spanish",
+    "italian",
+    "korean",
+    "chinese_simplified",
+    "chinese_traditional",
+]
+PASSPHRASE = "TREZOR"
+
+
+def word_list(language):
+    text = (dictionary_directory / f"bip39_{language}.txt").read_text(
+        encoding="utf-8-sig"
+    )
+    words = json.loads(text)
+    assert len(words) == 2048
+    return words
+
+
+def to_mnemonics(entropy, words, separator):
+    checksum_length = len(entropy) * 8 // 32
+    checksum = hashlib.sha256(entropy).digest()
+    bits = int.from_bytes(entropy, "big") << checksum_length
+    bits |= checksum[0] >> (8 - checksum_length)
+    count = (len(entropy) * 8 + checksum_length) // 11
+    indices = [(bits >> (11 * (count - 1 - i))) & 0x7FF for i in range(count)]
+    return separator.join(words[index] for index in indices)
+
+
+def to_seed(mnemonics, passphrase):
+    mnemonics = unicodedata.normalize("NFKD", mnemonics).encode("utf-8")
+    salt = unicodedata.normalize("NFKD", "mnemonic" + passphrase).encode("utf-8")
+    return hashlib.pbkdf2_hmac("sha512", mnemonics, salt, 2048)
+
+
+def read_vectors(language):
+    text = (script_directory / f"bip39_{language}.txt").read_text(encoding="utf-8")
+    vectors = []
+    for block in text.split("TestVector")[1:]:
+        field = lambda name: re.search(name + r': "([^"]*)"', block).group(1)
+        vectors.append(
+            {
+                "entropy": field("entropy"),
+                "mnemonics": field("mnemonics"),
+                "passphrase": field("passphrase"),
+                "seed": field("seed"),
+            }
+        )
+    return vectors
+
+
+def check_official(language, separator):
+    words = word_list(language)
+    for vector in read_vectors(language):
+        entropy = bytes.fromhex(vector["entropy"])
+        mnemonics = to_mnemonics(entropy, words, separator)
+        nfkd = lambda text: unicodedata.normalize("NFKD", text)
+        if nfkd(vector["mnemonics"]) != nfkd(mnemonics):
+            sys.exit(f"{language}: mnemonics mismatch for {vector['entropy']}")
+        if to_seed(mnemonics, vector["passphrase"]).hex() != vector["seed"]:
+            sys.exit(f"{language}: seed mismatch for {vector['entropy']}")
+
+
+def generate(language, entropies):
+    words = word_list(language)
+    lines = []
+    for entropy in entropies:
+        mnemonics = to_mnemonics(bytes.fromhex(entropy), words, " ")
+        seed = to_seed(mnemonics, PASSPHRASE).hex()
+        # the mnemonics are composed (NFC), as entered by the users
+        mnemonics = unicodedata.normalize("NFC", mnemonics)
+        lines.append(
+            "  TestVector\n"
+            "    {\n"
+            f'       entropy: "{entropy}",\n'
+            f'      mnemonics: "{mnemonics}",\n'
+            f'    passphrase: "{PASSPHRASE}",\n'
+            f'          seed: "{seed}",\n'
+            "    },\n"
+        )
+    return "[\n" + "".join(lines) + "]\n"
+
+
+def run():
+    parser = argparse.ArgumentParser()
+    parser.add_argument("--check", action="store_true")
+    args = parser.parse_args()
+
+    check_official("english", " ")
+    check_official("japanese", "　")
+
+    entropies = [vector["entropy"] for vector in read_vectors("english")]
+
+    failed = False
+    for language in LANGUAGES:
+        path = script_directory / f"bip39_{language}.txt"
+        vectors = generate(language, entropies)
+        if args.check:
+            if path.read_text(encoding="utf-8") != vectors:
+                print(f"{path} differs from the generated vectors")
+                failed = True
+        else:
+            path.write_text(vectors, encoding="utf-8")
+
+    if failed:
+        sys.exit(1)
+
+
+if __name__ == "__main__":
+    run()
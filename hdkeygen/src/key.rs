@@ -1,5 +1,5 @@
 use chain_path_derivation::{
-    Derivation, DerivationPath, DerivationRange, SoftDerivation, SoftDerivationRange,
+    AnyScheme, Derivation, DerivationPath, DerivationRange, SoftDerivation, SoftDerivationRange,
 };
 use ed25519_bip32::{DerivationScheme, Signature, XPrv, XPub};
 use std::fmt::{self, Debug, Display};
@@ -112,6 +112,16 @@ impl<P> Key<XPrv, P> {
         }
     }
 
+    /// derive the private key along the given derivation path, relative
+    /// to this key
+    ///
+    /// the derivation path of the returned key is the path of this key
+    /// followed by the given derivation path.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn derive_path(&self, derivation_path: &DerivationPath<AnyScheme>) -> Key<XPrv, AnyScheme> {
+        self.derive_path_unchecked(derivation_path)
+    }
+
     /// derive the private key against the given derivation index and scheme
     ///
     #[must_use = "this returns the result of the operation, without modifying the original"]
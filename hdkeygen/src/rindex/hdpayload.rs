@@ -41,16 +41,11 @@ pub enum Error {
 const CBOR_INDEFINITE_ARRAY: u8 = 0x9F;
 const CBOR_BREAK: u8 = 0xFF;
 
-#[cfg(test)]
 const CBOR_MAX_INLINE_ENCODING: u32 = 23;
-#[cfg(test)]
 const CBOR_PAYLOAD_LENGTH_U8: u8 = 24;
-#[cfg(test)]
 const CBOR_PAYLOAD_LENGTH_U16: u8 = 25;
-#[cfg(test)]
 const CBOR_PAYLOAD_LENGTH_U32: u8 = 26;
 
-#[cfg(test)]
 fn encode_derivation(buf: &mut Vec<u8>, derivation: Derivation) {
     let value: u32 = *derivation;
 
@@ -106,8 +101,8 @@ fn decode_derivation(reader: &mut &[u8]) -> Option<Derivation> {
     Some(Derivation::from(v))
 }
 
-#[cfg(test)]
-fn encode_derivation_path<S>(derivation_path: &DerivationPath<S>) -> Vec<u8> {
+/// encode the derivation path in cbor, as expected in the HD payload
+pub fn encode_derivation_path<S>(derivation_path: &DerivationPath<S>) -> Vec<u8> {
     let mut buf = Vec::with_capacity(32);
 
     buf.push(CBOR_INDEFINITE_ARRAY);
@@ -156,7 +151,7 @@ impl HDKey {
         HDKey(result)
     }
 
-    #[cfg(test)]
+    /// encrypt the encoded derivation path, see `encode_derivation_path`
     pub fn encrypt(&self, input: &[u8]) -> Vec<u8> {
         let mut ctx = ChaCha20Poly1305::new(&self.0, &NONCE[..], &[]);
 
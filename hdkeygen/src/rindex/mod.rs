@@ -8,7 +8,7 @@ use chain_path_derivation::{
     DerivationPath,
 };
 use ed25519_bip32::XPrv;
-pub use hdpayload::{decode_derivation_path, HDKey};
+pub use hdpayload::{decode_derivation_path, encode_derivation_path, HDKey};
 
 impl Key<XPrv, Rindex<rindex::Root>> {
     pub fn key(
@@ -0,0 +1,203 @@
+#!/usr/bin/env python3
+
+# Compute the account identifier and the first Yoroi addresses of a wallet
+# recovered from mnemonics and an optional password, independently of the
+# rust crates, with the python standard library only:
+#
+# * the account key is the ed25519 key (RFC 8032) of the 32 first bytes of
+#   PBKDF2-HMAC-SHA512(password, entropy, 4096 iterations);
+# * the Yoroi root key is the Icarus master key of CIP-0003
+#   (https://github.com/cardano-foundation/CIPs/blob/master/CIP-0003/Icarus.md)
+#   derived with ed25519-bip32 (V2) along m/44'/1815'/0'/change/index;
+# * the addresses are the Byron bootstrap addresses of the mainnet.
+#
+# It gives the vectors without password used by the tests since the first
+# release (the account of `d06583fd...` and the addresses funded in
+# `test-vectors/block0`) and the vectors with a password of
+# `wallet/src/recovering/mod.rs`:
+#
+#     ./recover_with_password.py ../../bip39/src/bip39_english.txt "neck bulb ... stone" password
+#
+# usage: ./recover_with_password.py <word list> <mnemonics> [password]
+
+import hashlib
+import hmac
+import json
+import sys
+import zlib
+
+# ed25519 (RFC 8032)
+p = 2**255 - 19
+L = 2**252 + 27742317777372353535851937790883648493
+d = -121665 * pow(121666, p - 2, p) % p
+
+
+def inv(x):
+    return pow(x, p - 2, p)
+
+
+def recover_x(y, sign):
+    x2 = (y * y - 1) * inv(d * y * y + 1)
+    x = pow(x2, (p + 3) // 8, p)
+    if (x * x - x2) % p != 0:
+        x = x * pow(2, (p - 1) // 4, p) % p
+    if x & 1 != sign:
+        x = p - x
+    return x
+
+
+By = 4 * inv(5) % p
+B = (recover_x(By, 0), By, 1, recover_x(By, 0) * By % p)
+
+
+def add(P, Q):
+    A = (P[1] - P[0]) * (Q[1] - Q[0]) % p
+    Bv = (P[1] + P[0]) * (Q[1] + Q[0]) % p
+    C = 2 * P[3] * Q[3] * d % p
+    D = 2 * P[2] * Q[2] % p
+    E, F, G, H = Bv - A, D - C, D + C, Bv + A
+    return (E * F % p, G * H % p, F * G % p, E * H % p)
+
+
+def mul(s, P):
+    Q = (0, 1, 1, 0)
+    while s > 0:
+        if s & 1:
+            Q = add(Q, P)
+        P = add(P, P)
+        s >>= 1
+    return Q
+
+
+def encode(P):
+    zi = inv(P[2])
+    x, y = P[0] * zi % p, P[1] * zi % p
+    return int.to_bytes(y | ((x & 1) << 255), 32, "little")
+
+
+def decode(b):
+    y = int.from_bytes(b, "little")
+    sign = y >> 255
+    y &= (1 << 255) - 1
+    x = recover_x(y, sign)
+    return (x, y, 1, x * y % p)
+
+
+def ed25519_public(seed):
+    h = hashlib.sha512(seed).digest()
+    a = int.from_bytes(h[:32], "little")
+    a &= (1 << 254) - 8
+    a |= 1 << 254
+    return encode(mul(a, B))
+
+
+# bip39
+def entropy(mnemonics, words):
+    indices = [words.index(w) for w in mnemonics.split()]
+    bits = 0
+    for i in indices:
+        bits = (bits << 11) | i
+    total = len(indices) * 11
+    cs = total // 33
+    ent = bits >> cs
+    return ent.to_bytes((total - cs) // 8, "big")
+
+
+# ed25519-bip32, derivation scheme V2
+def derive(key, index):
+    kl, kr, cc = key
+    idx = index.to_bytes(4, "little")
+    if index >= 2**31:
+        data = kl + kr + idx
+        z = hmac.new(cc, b"\x00" + data, hashlib.sha512).digest()
+        i = hmac.new(cc, b"\x01" + data, hashlib.sha512).digest()
+    else:
+        a = public(key)
+        z = hmac.new(cc, b"\x02" + a + idx, hashlib.sha512).digest()
+        i = hmac.new(cc, b"\x03" + a + idx, hashlib.sha512).digest()
+    zl = int.from_bytes(z[:28], "little")
+    zr = int.from_bytes(z[32:], "little")
+    nkl = (int.from_bytes(kl, "little") + 8 * zl) % 2**256
+    nkr = (int.from_bytes(kr, "little") + zr) % 2**256
+    return (nkl.to_bytes(32, "little"), nkr.to_bytes(32, "little"), i[32:])
+
+
+def public(key):
+    return encode(mul(int.from_bytes(key[0], "little"), B))
+
+
+def icarus_root(ent, password):
+    k = bytearray(hashlib.pbkdf2_hmac("sha512", password, ent, 4096, 96))
+    k[0] &= 0b1111_1000
+    k[31] &= 0b0001_1111
+    k[31] |= 0b0100_0000
+    return (bytes(k[:32]), bytes(k[32:64]), bytes(k[64:]))
+
+
+# byron addresses
+def cbor_head(major, n):
+    if n < 24:
+        return bytes([major << 5 | n])
+    if n < 256:
+        return bytes([major << 5 | 24, n])
+    if n < 65536:
+        return bytes([major << 5 | 25]) + n.to_bytes(2, "big")
+    return bytes([major << 5 | 26]) + n.to_bytes(4, "big")
+
+
+def cbor_bytes(b):
+    return cbor_head(2, len(b)) + b
+
+
+ALPHABET = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz"
+
+
+def base58(b):
+    n = int.from_bytes(b, "big")
+    s = ""
+    while n:
+        n, r = divmod(n, 58)
+        s = ALPHABET[r] + s
+    return "1" * (len(b) - len(b.lstrip(b"\0"))) + s
+
+
+def byron_address(xpub):
+    spending = cbor_head(4, 2) + cbor_head(0, 0) + cbor_bytes(xpub)
+    root_data = cbor_head(4, 3) + cbor_head(0, 0) + spending + cbor_head(5, 0)
+    root = hashlib.blake2b(hashlib.sha3_256(root_data).digest(), digest_size=28).digest()
+    payload = cbor_head(4, 3) + cbor_bytes(root) + cbor_head(5, 0) + cbor_head(0, 0)
+    address = (
+        cbor_head(4, 2)
+        + cbor_head(6, 24)
+        + cbor_bytes(payload)
+        + cbor_head(0, zlib.crc32(payload))
+    )
+    return base58(address)
+
+
+def yoroi_addresses(ent, password):
+    key = icarus_root(ent, password)
+    for index in [44, 1815, 0]:
+        key = derive(key, index + 2**31)
+    result = []
+    for change in [0, 1]:
+        chain = derive(key, change)
+        for index in [0, 1]:
+            k = derive(chain, index)
+            result.append(byron_address(public(k) + k[2]))
+    return result
+
+
+def account_id(ent, password):
+    seed = hashlib.pbkdf2_hmac("sha512", password, ent, 4096, 32)
+    return ed25519_public(seed).hex()
+
+
+if __name__ == "__main__":
+    words = json.loads(open(sys.argv[1], encoding="utf-8-sig").read())
+    mnemonics = sys.argv[2]
+    password = sys.argv[3].encode() if len(sys.argv) > 3 else b""
+    ent = entropy(mnemonics, words)
+    print(account_id(ent, password))
+    for a in yoroi_addresses(ent, password):
+        print(a)
@@ -1,18 +1,94 @@
-use super::transaction::AccountWitnessBuilder;
-use crate::scheme::{on_tx_input_and_witnesses, on_tx_output};
+use super::transaction::{
+    AccountWitnessBuilder, FinalizeError, TransactionBuilder, WitnessBuilder as _,
+};
+use crate::history::{History, HistoryEntry};
+use crate::persistence::{read_states, write_states, Kind, PersistenceError, Reader, Writer};
+use crate::scheme::{on_tx_input_and_witnesses, on_tx_output, transaction_sign_data_hash};
 use crate::states::{States, Status};
+use crate::Settings;
+use chain_addr::Address;
 use chain_crypto::{Ed25519, Ed25519Extended, PublicKey, SecretKey};
 use chain_impl_mockchain::{
+    account::{DelegationRatio, DelegationType},
+    block::{BlockDate, HeaderId},
+    certificate::{OwnerStakeDelegation, PoolId, StakeDelegation},
     fragment::{Fragment, FragmentId},
-    transaction::{Input, InputEnum},
+    transaction::{
+        AccountBindingSignature, Input, InputEnum, NoExtra, Output, Payload, Transaction,
+        TransactionBindingAuthData, TransactionSignDataHash, UnspecifiedAccountIdentifier,
+        UtxoPointer, Witness,
+    },
     value::Value,
 };
 pub use hdkeygen::account::AccountId;
-use hdkeygen::account::{Account, SEED};
+use hdkeygen::account::{Account, SEED, SEED_LENGTH};
+use std::convert::TryInto as _;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PaymentError {
+    #[error("not enough funds: {needed} is required but only {available} is available")]
+    NotEnoughFunds { needed: Value, available: Value },
+
+    #[error("the value to send and the fees are overflowing")]
+    ValueOverflow,
+
+    #[error("invalid delegation: {0}")]
+    InvalidDelegation(&'static str),
+
+    #[error("cannot build the transaction")]
+    Balancing(
+        #[source]
+        #[from]
+        FinalizeError,
+    ),
+}
+
+/// number of spending counters tried before and after the expected one when
+/// looking for the spending counter used by a witness
+const SPENDING_COUNTER_SEARCH_WINDOW: u32 = 64;
+
+/// the spending counter used by a transaction of the account does not follow
+/// the local spending counter
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SpendingCounterError {
+    #[error("no spending counter close to {expected} matches the witness of input {index}")]
+    InvalidWitness { index: usize, expected: u32 },
+
+    #[error("spending counter {used} used instead of {expected}, some transactions are missing")]
+    Gap { expected: u32, used: u32 },
+
+    #[error("spending counter {used} used instead of {expected}, it was already used")]
+    Duplicate { expected: u32, used: u32 },
+}
+
+/// how the node's spending counter of the account compares to the local
+/// confirmed spending counter
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SyncStatus {
+    /// the node and the local state use the same spending counter
+    Consistent,
+    /// the node already applied transactions of the account that are not
+    /// confirmed in the local state
+    Ahead,
+    /// the node did not apply yet transactions of the account that are
+    /// confirmed in the local state
+    Behind,
+}
+
+/// the result of reconciling the local state with the node's state, see
+/// `Wallet::reconcile_state`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reconciliation {
+    status: SyncStatus,
+    dropped: Vec<FragmentId>,
+    confirmed: Vec<FragmentId>,
+}
 
 pub struct Wallet {
     account: EitherAccount,
     state: States<FragmentId, State>,
+    history: History,
 }
 
 pub struct State {
@@ -42,6 +118,7 @@ impl Wallet {
                     counter: 0,
                 },
             ),
+            history: History::new(),
         }
     }
 
@@ -55,7 +132,70 @@ impl Wallet {
                     counter: 0,
                 },
             ),
+            history: History::new(),
+        }
+    }
+
+    /// serialize the wallet: the secret key of the account and all the
+    /// states (value and spending counter) with their status, so the
+    /// pending transactions are kept pending, and the history.
+    ///
+    /// Use `Wallet::import` to restore the wallet.
+    pub fn export(&self) -> Vec<u8> {
+        let mut writer = Writer::new(Kind::Account);
+
+        match &self.account {
+            EitherAccount::Seed(account) => {
+                writer.u8(0);
+                writer.bytes(account.secret().clone().leak_secret().as_ref());
+            }
+            EitherAccount::Extended(account) => {
+                writer.u8(1);
+                writer.bytes(account.secret().clone().leak_secret().as_ref());
+            }
         }
+
+        write_states(&mut writer, &self.state, |writer, _, state| {
+            writer.u64(state.value.0);
+            writer.u32(state.counter);
+        });
+        self.history.write(&mut writer);
+
+        writer.finalize()
+    }
+
+    /// restore a wallet serialized with `Wallet::export`
+    pub fn import(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        let mut reader = Reader::new(bytes, Kind::Account)?;
+
+        let account = match reader.u8()? {
+            0 => {
+                let seed = reader.bytes(SEED_LENGTH)?.try_into().unwrap();
+                EitherAccount::Seed(Account::from_seed(seed))
+            }
+            1 => {
+                let key = SecretKey::from_binary(reader.bytes(64)?)
+                    .map_err(|_| PersistenceError::Invalid("account secret key"))?;
+                EitherAccount::Extended(Account::from_secret_key(key))
+            }
+            _ => return Err(PersistenceError::Invalid("account secret key")),
+        };
+
+        let state = read_states(&mut reader, |reader, _| {
+            Ok(State {
+                value: Value(reader.u64()?),
+                counter: reader.u32()?,
+            })
+        })?;
+        let history = History::read(&mut reader)?;
+
+        reader.finalize()?;
+
+        Ok(Wallet {
+            account,
+            state,
+            history,
+        })
     }
 
     pub fn account_id(&self) -> AccountId {
@@ -65,18 +205,75 @@ impl Wallet {
         }
     }
 
-    /// set the state counter so we can sync with the blockchain and the
-    /// local state
+    /// reset the state of the wallet to the given value and spending counter
     ///
-    /// TODO: some handling to provide information if needed:
+    /// All the pending transactions are discarded, use
+    /// `Wallet::reconcile_state` to keep the pending transactions that are
+    /// still valid.
+    pub fn update_state(&mut self, value: Value, counter: u32) {
+        self.state = States::new(FragmentId::zero_hash(), State { value, counter });
+    }
+
+    /// synchronise the local state with the value and the spending counter
+    /// of the account as reported by the node
     ///
-    /// - [ ] check the counter is not regressing?
-    /// - [ ] check that there is continuity?
+    /// The node's state becomes the new confirmed state. The pending
+    /// transactions that spend from the account with a spending counter
+    /// lower than the node's counter are stale (either already applied by
+    /// the node or never going to be) and are dropped, the other pending
+    /// transactions are kept and rebased on top of the node's state.
     ///
-    /// TODO: change to a constructor/initializator?, or just make it so it resets the state
+    /// The transactions confirmed while an older transaction was still
+    /// pending are part of the node's state: they are reported as confirmed
+    /// and marked as such in the history.
     ///
-    pub fn update_state(&mut self, value: Value, counter: u32) {
-        self.state = States::new(FragmentId::zero_hash(), State { value, counter });
+    /// The dropped transactions are marked as dropped in the history, call
+    /// `Wallet::confirm` for the ones known to be on chain.
+    pub fn reconcile_state(&mut self, value: Value, counter: u32) -> Reconciliation {
+        let confirmed_counter = self.state.confirmed_state().1.counter;
+        let status = match counter.cmp(&confirmed_counter) {
+            std::cmp::Ordering::Equal => SyncStatus::Consistent,
+            std::cmp::Ordering::Greater => SyncStatus::Ahead,
+            std::cmp::Ordering::Less => SyncStatus::Behind,
+        };
+
+        let mut state = States::new(FragmentId::zero_hash(), State { value, counter });
+        let mut dropped = Vec::new();
+        let mut confirmed = Vec::new();
+
+        let mut previous: Option<&State> = None;
+        for (fragment_id, current, fragment_status) in self.state.iter() {
+            // the first state is the confirmed state replaced by the node's
+            let before = match previous.replace(current) {
+                Some(before) => before,
+                None => continue,
+            };
+
+            if fragment_status == Status::Confirmed {
+                // confirmed after a transaction that is still pending, it
+                // could not become the confirmed state
+                confirmed.push(*fragment_id);
+            } else if before.counter != current.counter && before.counter < counter {
+                dropped.push(*fragment_id);
+            } else {
+                let rebased = state.last_state().1.rebase(before, current);
+                state.push(*fragment_id, rebased);
+            }
+        }
+
+        self.state = state;
+        for fragment_id in confirmed.iter() {
+            self.history.confirm(fragment_id);
+        }
+        for fragment_id in dropped.iter() {
+            self.history.mark_dropped(fragment_id);
+        }
+
+        Reconciliation {
+            status,
+            dropped,
+            confirmed,
+        }
     }
 
     pub fn value(&self) -> Value {
@@ -90,7 +287,18 @@ impl Wallet {
     /// as immutable
     ///
     pub fn confirm(&mut self, fragment_id: &FragmentId) {
-        self.state.confirm(fragment_id)
+        self.state.confirm(fragment_id);
+        self.history.confirm(fragment_id);
+    }
+
+    /// get the history of the fragments that touched the account
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// set the date of the block the given fragment is in
+    pub fn set_block_date(&mut self, fragment_id: &FragmentId, block_date: BlockDate) {
+        self.history.set_block_date(fragment_id, block_date)
     }
 
     /// get all the pending transactions of the wallet
@@ -130,6 +338,68 @@ impl Wallet {
         }
     }
 
+    /// remove a pending transaction, for example because it was rejected
+    /// by the node, and recompute the value and the spending counter of
+    /// the more recent pending states
+    ///
+    /// If the transaction spends from the account, the more recent pending
+    /// transactions spending from the account are removed as well: they
+    /// were signed with the following spending counters and the node
+    /// rejects them too. They are marked as rejected in the history.
+    ///
+    /// returns the account inputs used by the removed transactions (if
+    /// any), from the oldest to the most recent. `None` if the transaction
+    /// is not pending.
+    ///
+    pub fn remove_pending_transaction(&mut self, fragment_id: &FragmentId) -> Option<Vec<Input>> {
+        if !self.pending_transactions().any(|id| id == fragment_id) {
+            return None;
+        }
+
+        let mut removed = vec![*fragment_id];
+        if self.account_input(fragment_id).is_some() {
+            removed.extend(
+                self.state
+                    .iter()
+                    .skip_while(|(id, _, _)| *id != fragment_id)
+                    .skip(1)
+                    .filter(|(id, _, status)| {
+                        *status == Status::Pending && self.account_input(id).is_some()
+                    })
+                    .map(|(id, _, _)| *id),
+            );
+        }
+
+        // from the most recent so the inputs are computed on the states
+        // as they were pushed
+        let mut inputs = Vec::new();
+        for id in removed.iter().rev() {
+            inputs.extend(self.account_input(id));
+            self.state.reject(id, State::rebase)?;
+            self.history.reject(id);
+        }
+        inputs.reverse();
+
+        Some(inputs)
+    }
+
+    /// the account input of the given transaction, `None` if it does not
+    /// spend from the account
+    fn account_input(&self, fragment_id: &FragmentId) -> Option<Input> {
+        let (_, previous) = self.state.previous(fragment_id)?;
+        let (current, _) = self.state.get(fragment_id)?;
+
+        if current.counter == previous.counter {
+            return None;
+        }
+
+        let spent = previous.value.checked_sub(current.value).ok()?;
+        Some(Input::from_account_public_key(
+            self.account_id().into(),
+            spent,
+        ))
+    }
+
     pub fn new_transaction(&mut self, value: Value) -> WalletBuildTx {
         let (_, state, _) = self.state.last_state();
         let counter = state.counter;
@@ -140,68 +410,364 @@ impl Wallet {
         }
     }
 
+    /// build a transaction sending `value` to the given `address`
+    ///
+    /// The fees are computed from the `settings` and are paid by the account
+    /// on top of the `value`. On success the returned fragment is added to
+    /// the pending transactions of the wallet and the local state (value and
+    /// spending counter) is updated accordingly.
+    ///
+    /// # Errors
+    ///
+    /// * `PaymentError::NotEnoughFunds` if the account's value cannot cover
+    ///   the `value` and the fees;
+    ///
+    pub fn send_to(
+        &mut self,
+        settings: &Settings,
+        address: Address,
+        value: Value,
+    ) -> Result<Fragment, PaymentError> {
+        let mut builder = TransactionBuilder::new(settings, NoExtra);
+        builder.add_output(Output::from_address(address, value));
+
+        self.pay_with(builder, |_, _| (), Fragment::Transaction)
+    }
+
+    /// build a transaction delegating the stake of the account to the given
+    /// `pools`, with an `OwnerStakeDelegation` certificate
+    ///
+    /// See `delegation_type` for how the `pools` are interpreted. The fees
+    /// are paid by the account and the returned fragment is added to the
+    /// pending transactions of the wallet.
+    ///
+    /// # Errors
+    ///
+    /// * `PaymentError::InvalidDelegation` if the ratios are not valid;
+    /// * `PaymentError::NotEnoughFunds` if the account's value cannot cover
+    ///   the fees;
+    ///
+    pub fn owner_stake_delegation(
+        &mut self,
+        settings: &Settings,
+        pools: &[(PoolId, u8)],
+    ) -> Result<Fragment, PaymentError> {
+        let delegation = delegation_type(pools)?;
+        let builder = TransactionBuilder::new(settings, OwnerStakeDelegation { delegation });
+
+        self.pay_with(builder, |_, _| (), Fragment::OwnerStakeDelegation)
+    }
+
+    /// build a transaction delegating the stake of the account to the given
+    /// `pools`, with a `StakeDelegation` certificate signed by the account
+    ///
+    /// See `delegation_type` for how the `pools` are interpreted. The fees
+    /// are paid by the account and the returned fragment is added to the
+    /// pending transactions of the wallet.
+    ///
+    /// # Errors
+    ///
+    /// * `PaymentError::InvalidDelegation` if the ratios are not valid;
+    /// * `PaymentError::NotEnoughFunds` if the account's value cannot cover
+    ///   the fees;
+    ///
+    pub fn stake_delegation(
+        &mut self,
+        settings: &Settings,
+        pools: &[(PoolId, u8)],
+    ) -> Result<Fragment, PaymentError> {
+        let delegation = delegation_type(pools)?;
+        let account_id = UnspecifiedAccountIdentifier::from_single_account(
+            Into::<PublicKey<Ed25519>>::into(self.account_id()).into(),
+        );
+        let builder = TransactionBuilder::new(
+            settings,
+            StakeDelegation {
+                account_id,
+                delegation,
+            },
+        );
+
+        self.pay_with(
+            builder,
+            |account, auth_data| match account {
+                EitherAccount::Seed(account) => {
+                    AccountBindingSignature::new_single(auth_data, |data| {
+                        account.secret().sign_slice(data.0)
+                    })
+                }
+                EitherAccount::Extended(account) => {
+                    AccountBindingSignature::new_single(auth_data, |data| {
+                        account.secret().sign_slice(data.0)
+                    })
+                }
+            },
+            Fragment::StakeDelegation,
+        )
+    }
+
+    /// add the account input paying for the outputs and the fees of the
+    /// transaction, finalize it and add it to the pending transactions
+    fn pay_with<P, F>(
+        &mut self,
+        mut builder: TransactionBuilder<P>,
+        auth: F,
+        mk_fragment: fn(Transaction<P>) -> Fragment,
+    ) -> Result<Fragment, PaymentError>
+    where
+        P: Payload,
+        F: FnOnce(&EitherAccount, &TransactionBindingAuthData<'_>) -> P::Auth,
+    {
+        let fee = builder.estimate_fee_with(1, 0);
+        let needed = builder
+            .outputs_value()
+            .checked_add(fee)
+            .map_err(|_| PaymentError::ValueOverflow)?;
+        let available = self.value();
+
+        if needed > available {
+            return Err(PaymentError::NotEnoughFunds { needed, available });
+        }
+
+        let account_tx_builder = self.new_transaction(needed);
+        let input = account_tx_builder.input();
+        let witness_builder = account_tx_builder.witness_builder();
+
+        builder.add_input(input, witness_builder);
+
+        let account = &account_tx_builder.wallet.account;
+        let tx = builder.finalize_tx_with(|auth_data| auth(account, auth_data))?;
+
+        let fragment = mk_fragment(tx);
+        account_tx_builder.add_fragment(&fragment);
+
+        Ok(fragment)
+    }
+
     pub fn check_fragment(&mut self, fragment_id: &FragmentId, fragment: &Fragment) -> bool {
+        self.check(None, fragment_id, fragment)
+            .expect("the witnesses are not verified")
+    }
+
+    /// same as `check_fragment` but the witnesses of the account's inputs
+    /// are verified against the local spending counter
+    ///
+    /// The spending counter used by each witness is found by signing the
+    /// transaction with the counters around the expected one. If they do
+    /// not match, the fragment is not applied and the local state needs to
+    /// be updated from the node (see `Wallet::update_state`) before
+    /// building a new transaction, it would be rejected otherwise.
+    ///
+    /// # Errors
+    ///
+    /// * `SpendingCounterError::Gap` if the transaction used a spending
+    ///   counter more recent than the expected one, some transactions of
+    ///   the account have been missed;
+    /// * `SpendingCounterError::Duplicate` if the transaction used a
+    ///   spending counter that has already been used;
+    /// * `SpendingCounterError::InvalidWitness` if the spending counter of
+    ///   the witness could not be found, the transaction may not be for the
+    ///   given `block0` or the counter is too far from the local one.
+    ///
+    pub fn verify_fragment(
+        &mut self,
+        block0: &HeaderId,
+        fragment_id: &FragmentId,
+        fragment: &Fragment,
+    ) -> Result<bool, SpendingCounterError> {
+        self.check(Some(block0), fragment_id, fragment)
+    }
+
+    fn check(
+        &mut self,
+        block0: Option<&HeaderId>,
+        fragment_id: &FragmentId,
+        fragment: &Fragment,
+    ) -> Result<bool, SpendingCounterError> {
         if self.state.contains(fragment_id) {
-            return true;
+            return Ok(true);
         }
 
         let (_, state, _) = self.state.last_state();
-
         let mut new_value = state.value;
+        let counter = state.counter;
 
-        let mut increment_counter = false;
-        let mut at_least_one_output = false;
-
-        match fragment {
-            Fragment::Initial(_config_params) => {}
-            Fragment::UpdateProposal(_update_proposal) => {}
-            Fragment::UpdateVote(_signed_update) => {}
-            Fragment::OldUtxoDeclaration(_utxos) => {}
-            _ => {
-                on_tx_input_and_witnesses(fragment, |(input, _witness)| {
-                    if let InputEnum::AccountInput(id, value) = input.to_enum() {
-                        if self.account_id().as_ref() == id.as_ref() {
-                            new_value = value.checked_sub(new_value).expect("value overflow");
-                        }
-                        increment_counter = true;
-                    }
+        let mut index = 0;
+        let mut witnesses = Vec::new();
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
 
-                    // TODO: check monotonicity by signing and comparing
-                    // if let Witness::Account(witness) = witness {
-                    //
-                    // }
+        on_tx_input_and_witnesses(fragment, |(input, witness)| {
+            if let InputEnum::AccountInput(id, value) = input.to_enum() {
+                if self.account_id().as_ref() == id.as_ref() {
+                    new_value = new_value
+                        .checked_sub(value)
+                        .unwrap_or_else(|_| Value::zero());
+                    witnesses.push((index, witness));
+                    inputs.push(input);
+                }
+            }
+            index += 1;
+        });
+        on_tx_output(fragment, |(index, output)| {
+            if output
+                .address
+                .public_key()
+                .map(|pk| *pk == Into::<PublicKey<Ed25519>>::into(self.account_id()))
+                .unwrap_or(false)
+            {
+                new_value = new_value.checked_add(output.value).unwrap();
+                outputs.push(UtxoPointer {
+                    transaction_id: *fragment_id,
+                    output_index: index as u8,
+                    value: output.value,
                 });
-                on_tx_output(fragment, |(_, output)| {
-                    if output
-                        .address
-                        .public_key()
-                        .map(|pk| *pk == Into::<PublicKey<Ed25519>>::into(self.account_id()))
-                        .unwrap_or(false)
-                    {
-                        new_value = new_value.checked_add(output.value).unwrap();
-                        at_least_one_output = true;
-                    }
-                })
             }
-        };
+        });
 
-        let counter = if increment_counter {
-            state
-                .counter
-                .checked_add(1)
-                .expect("account counter overflow")
-        } else {
-            state.counter
-        };
+        if let (Some(block0), Some(sign_data_hash)) = (block0, transaction_sign_data_hash(fragment))
+        {
+            for (expected, (index, witness)) in (counter..).zip(witnesses.iter()) {
+                let used = self
+                    .spending_counter_of(block0, &sign_data_hash, witness, expected)
+                    .ok_or(SpendingCounterError::InvalidWitness {
+                        index: *index,
+                        expected,
+                    })?;
 
+                match used.cmp(&expected) {
+                    std::cmp::Ordering::Less => {
+                        return Err(SpendingCounterError::Duplicate { expected, used })
+                    }
+                    std::cmp::Ordering::Greater => {
+                        return Err(SpendingCounterError::Gap { expected, used })
+                    }
+                    std::cmp::Ordering::Equal => {}
+                }
+            }
+        }
+
+        // every input of the account uses one spending counter
         let new_state = State {
-            counter,
+            counter: counter
+                .checked_add(inputs.len() as u32)
+                .expect("account counter overflow"),
             value: new_value,
         };
 
         self.state.push(*fragment_id, new_state);
 
-        at_least_one_output || increment_counter
+        let matched = !inputs.is_empty() || !outputs.is_empty();
+        if matched {
+            self.history.record(HistoryEntry::new(
+                *fragment_id,
+                Some(fragment),
+                inputs,
+                outputs,
+            ));
+        }
+
+        Ok(matched)
+    }
+
+    /// find the spending counter the witness has been signed with, trying
+    /// the `expected` one first
+    fn spending_counter_of(
+        &self,
+        block0: &HeaderId,
+        sign_data_hash: &TransactionSignDataHash,
+        witness: &Witness,
+        expected: u32,
+    ) -> Option<u32> {
+        let lowest = expected.saturating_sub(SPENDING_COUNTER_SEARCH_WINDOW);
+        let highest = expected.saturating_add(SPENDING_COUNTER_SEARCH_WINDOW);
+
+        std::iter::once(expected)
+            .chain((lowest..=highest).filter(|counter| *counter != expected))
+            .find(|counter| {
+                self.witness_builder(*counter).build(block0, sign_data_hash) == *witness
+            })
+    }
+
+    pub(crate) fn witness_builder(&self, counter: u32) -> AccountWitnessBuilder {
+        match &self.account {
+            EitherAccount::Seed(account) => {
+                AccountWitnessBuilder::Ed25519(account.secret().clone(), counter.into())
+            }
+            EitherAccount::Extended(account) => {
+                AccountWitnessBuilder::Ed25519Extended(account.secret().clone(), counter.into())
+            }
+        }
+    }
+}
+
+/// get the delegation to the given pools
+///
+/// * no pools: the stake is not delegated anymore;
+/// * one pool: all the stake is delegated to this pool, the ratio is ignored;
+/// * several pools: the stake is split between the pools, each pool receives
+///   its ratio over the sum of all the ratios. The ratios cannot be zero and
+///   their sum cannot be more than 255.
+///
+pub fn delegation_type(pools: &[(PoolId, u8)]) -> Result<DelegationType, PaymentError> {
+    match pools {
+        [] => Ok(DelegationType::NonDelegated),
+        [(pool, _)] => Ok(DelegationType::Full(pool.clone())),
+        pools => {
+            if pools.iter().any(|(_, ratio)| *ratio == 0) {
+                return Err(PaymentError::InvalidDelegation("a ratio cannot be zero"));
+            }
+
+            let parts = pools
+                .iter()
+                .try_fold(0u8, |parts, (_, ratio)| parts.checked_add(*ratio))
+                .ok_or(PaymentError::InvalidDelegation(
+                    "the sum of the ratios cannot be more than 255",
+                ))?;
+
+            DelegationRatio::new(parts, pools.to_vec())
+                .map(DelegationType::Ratio)
+                .ok_or(PaymentError::InvalidDelegation("too many pools"))
+        }
+    }
+}
+
+impl Reconciliation {
+    /// how the node's state compares to the previous local state
+    pub fn status(&self) -> SyncStatus {
+        self.status
+    }
+
+    /// the pending transactions that have been dropped because they use
+    /// a spending counter the node already used
+    pub fn dropped(&self) -> &[FragmentId] {
+        &self.dropped
+    }
+
+    /// the transactions that were confirmed locally while an older
+    /// transaction was still pending, they are part of the node's state
+    pub fn confirmed(&self) -> &[FragmentId] {
+        &self.confirmed
+    }
+}
+
+impl State {
+    /// apply on top of `self` the changes that lead from `before` to `after`
+    fn rebase(&self, before: &State, after: &State) -> State {
+        let value = if after.value >= before.value {
+            self.value
+                .saturating_add(after.value.checked_sub(before.value).unwrap())
+        } else {
+            self.value
+                .checked_sub(before.value.checked_sub(after.value).unwrap())
+                .unwrap_or_else(|_| Value::zero())
+        };
+
+        let counter = self
+            .counter
+            .wrapping_add(after.counter.wrapping_sub(before.counter));
+
+        State { value, counter }
     }
 }
 
@@ -211,25 +777,46 @@ impl<'a> WalletBuildTx<'a> {
     }
 
     pub fn witness_builder(&self) -> AccountWitnessBuilder {
-        match &self.wallet.account {
-            EitherAccount::Seed(account) => crate::transaction::AccountWitnessBuilder::Ed25519(
-                account.secret().clone(),
-                self.counter.into(),
-            ),
-            EitherAccount::Extended(account) => {
-                crate::transaction::AccountWitnessBuilder::Ed25519Extended(
-                    account.secret().clone(),
-                    self.counter.into(),
-                )
-            }
-        }
+        self.wallet.witness_builder(self.counter)
+    }
+
+    pub fn add_fragment_id(mut self, fragment_id: FragmentId) {
+        self.push_state(fragment_id);
+
+        let input = self.input();
+        self.wallet.history.record(HistoryEntry::new(
+            fragment_id,
+            None,
+            vec![input],
+            Vec::new(),
+        ));
     }
 
-    pub fn add_fragment_id(self, fragment_id: FragmentId) {
+    /// same as `add_fragment_id` but the history entry is recorded with
+    /// the fragment
+    fn add_fragment(mut self, fragment: &Fragment) {
+        self.push_state(fragment.hash());
+
+        let input = self.input();
+        self.wallet.history.record(HistoryEntry::new(
+            fragment.hash(),
+            Some(fragment),
+            vec![input],
+            Vec::new(),
+        ));
+    }
+
+    fn push_state(&mut self, fragment_id: FragmentId) {
+        let value = self
+            .wallet
+            .value()
+            .checked_sub(self.value)
+            .unwrap_or_else(|_| Value::zero());
+
         self.wallet.state.push(
             fragment_id,
             State {
-                value: self.value,
+                value,
                 counter: self.counter.checked_add(1).unwrap(),
             },
         );
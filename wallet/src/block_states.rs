@@ -0,0 +1,371 @@
+use std::{collections::HashMap, hash::Hash};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BlockStatesError {
+    #[error("the parent block is unknown or has already been pruned")]
+    UnknownParent,
+
+    #[error("the block is unknown or has already been pruned")]
+    UnknownBlock,
+
+    #[error("the block is already known")]
+    AlreadyKnown,
+
+    #[error("invalid chain length {chain_length}, the parent's chain length is {parent}")]
+    InvalidChainLength { chain_length: u32, parent: u32 },
+}
+
+/// how the tip moved after a block has been pushed or after a rollback
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TipUpdate<K> {
+    /// the new tip is a descendant of the previous tip
+    Extended,
+    /// the new tip is on a different branch, the blocks of the previous
+    /// branch that are not on the new branch have been rolled back (most
+    /// recent first)
+    Switched { rolled_back: Vec<K> },
+}
+
+struct Block<K, S> {
+    parent: Option<K>,
+    chain_length: u32,
+    state: S,
+}
+
+/// keep track of a state per block, following the forks of the blockchain
+///
+/// Every block is recorded with its parent so the different branches of the
+/// blockchain can be followed. The tip is always the last block pushed (or
+/// the block the tracker has been rolled back to), so it is possible to
+/// follow the stream of blocks of a node, even through short forks.
+///
+/// Once a block is `stability_depth` blocks deep in the branch of the tip
+/// it becomes the stable block: the blocks before it and all the branches
+/// that are not descendant of it are pruned. It is not possible to rollback
+/// before the stable block.
+///
+/// The states are expected to be cheap to clone and to share their common
+/// data, like the `UtxoStore`.
+pub struct BlockStates<K, S> {
+    blocks: HashMap<K, Block<K, S>>,
+    stable: K,
+    tip: K,
+    stability_depth: u32,
+}
+
+impl<K, S> BlockStates<K, S>
+where
+    K: Hash + Eq + Clone,
+{
+    /// create a new tracker starting at the given block, this block is
+    /// considered stable
+    pub fn new(key: K, chain_length: u32, state: S, stability_depth: u32) -> Self {
+        let mut blocks = HashMap::with_capacity(stability_depth as usize + 1);
+        blocks.insert(
+            key.clone(),
+            Block {
+                parent: None,
+                chain_length,
+                state,
+            },
+        );
+
+        Self {
+            blocks,
+            stable: key.clone(),
+            tip: key,
+            stability_depth,
+        }
+    }
+
+    /// the number of blocks a block needs to be deep in the tip's branch
+    /// to be considered stable
+    pub fn stability_depth(&self) -> u32 {
+        self.stability_depth
+    }
+
+    /// check if the given block is tracked
+    pub fn contains(&self, key: &K) -> bool {
+        self.blocks.contains_key(key)
+    }
+
+    /// get the state associated to the given block
+    pub fn get(&self, key: &K) -> Option<&S> {
+        self.blocks.get(key).map(|block| &block.state)
+    }
+
+    /// get the chain length of the given block
+    pub fn chain_length(&self, key: &K) -> Option<u32> {
+        self.blocks.get(key).map(|block| block.chain_length)
+    }
+
+    /// get the tip and its associated state
+    pub fn tip(&self) -> (&K, &S) {
+        (&self.tip, &self.block(&self.tip).state)
+    }
+
+    /// get the stable block and its associated state
+    pub fn stable(&self) -> (&K, &S) {
+        (&self.stable, &self.block(&self.stable).state)
+    }
+
+    /// iterate through the blocks of the tip's branch, from the tip down
+    /// to the stable block
+    pub fn branch(&self) -> impl Iterator<Item = (&K, &S)> {
+        let mut next = Some(&self.tip);
+        std::iter::from_fn(move || {
+            let key = next?;
+            let block = self.block(key);
+            next = block.parent.as_ref();
+            Some((key, &block.state))
+        })
+    }
+
+    /// add a new block on top of its `parent` and make it the new tip
+    ///
+    /// If the parent is not the current tip, the tip switches to the branch
+    /// of the new block. The blocks that are now too deep are pruned.
+    ///
+    /// # Errors
+    ///
+    /// * `BlockStatesError::UnknownParent` if the parent is not tracked
+    ///   (it may have been pruned already);
+    /// * `BlockStatesError::AlreadyKnown` if the block is already tracked;
+    /// * `BlockStatesError::InvalidChainLength` if the chain length does not
+    ///   follow the parent's chain length.
+    pub fn push(
+        &mut self,
+        key: K,
+        parent: K,
+        chain_length: u32,
+        state: S,
+    ) -> Result<TipUpdate<K>, BlockStatesError> {
+        if self.contains(&key) {
+            return Err(BlockStatesError::AlreadyKnown);
+        }
+
+        let parent_length = self
+            .chain_length(&parent)
+            .ok_or(BlockStatesError::UnknownParent)?;
+        if parent_length.checked_add(1) != Some(chain_length) {
+            return Err(BlockStatesError::InvalidChainLength {
+                chain_length,
+                parent: parent_length,
+            });
+        }
+
+        self.blocks.insert(
+            key.clone(),
+            Block {
+                parent: Some(parent),
+                chain_length,
+                state,
+            },
+        );
+
+        Ok(self.set_tip(key))
+    }
+
+    /// move the tip back to the given block, for example when the node
+    /// rolled back to a different branch
+    ///
+    /// The blocks that are not on the branch of the new tip are kept until
+    /// they are pruned, so it is possible to switch back to them.
+    ///
+    /// # Errors
+    ///
+    /// * `BlockStatesError::UnknownBlock` if the block is not tracked (it
+    ///   may have been pruned already).
+    pub fn rollback(&mut self, key: &K) -> Result<TipUpdate<K>, BlockStatesError> {
+        if !self.contains(key) {
+            return Err(BlockStatesError::UnknownBlock);
+        }
+
+        Ok(self.set_tip(key.clone()))
+    }
+
+    /// iterate through all the tracked blocks with their parent (`None` for
+    /// the stable block), their chain length and their state, in no
+    /// particular order
+    pub(crate) fn blocks(&self) -> impl Iterator<Item = (&K, Option<&K>, u32, &S)> {
+        self.blocks
+            .iter()
+            .map(|(key, block)| (key, block.parent.as_ref(), block.chain_length, &block.state))
+    }
+
+    fn block(&self, key: &K) -> &Block<K, S> {
+        self.blocks
+            .get(key)
+            .expect("the tip's branch is always tracked")
+    }
+
+    /// get the ancestor of the given block at the given chain length
+    fn ancestor_at<'a>(&'a self, key: &'a K, chain_length: u32) -> Option<&'a K> {
+        let mut key = key;
+        loop {
+            let block = self.blocks.get(key)?;
+            if block.chain_length == chain_length {
+                return Some(key);
+            } else if block.chain_length < chain_length {
+                return None;
+            }
+            key = block.parent.as_ref()?;
+        }
+    }
+
+    fn set_tip(&mut self, tip: K) -> TipUpdate<K> {
+        let mut rolled_back = Vec::new();
+        let mut current = Some(&self.tip);
+        while let Some(key) = current {
+            let block = self.block(key);
+            if self.ancestor_at(&tip, block.chain_length) == Some(key) {
+                break;
+            }
+            rolled_back.push(key.clone());
+            current = block.parent.as_ref();
+        }
+
+        self.tip = tip;
+        self.prune();
+
+        if rolled_back.is_empty() {
+            TipUpdate::Extended
+        } else {
+            TipUpdate::Switched { rolled_back }
+        }
+    }
+
+    fn prune(&mut self) {
+        let tip_length = self.block(&self.tip).chain_length;
+        let stable_length = self.block(&self.stable).chain_length;
+        let new_stable_length = tip_length.saturating_sub(self.stability_depth);
+
+        if new_stable_length <= stable_length {
+            return;
+        }
+
+        let new_stable = self
+            .ancestor_at(&self.tip, new_stable_length)
+            .expect("the tip's branch is always tracked")
+            .clone();
+
+        let pruned: Vec<K> = self
+            .blocks
+            .keys()
+            .filter(|key| self.ancestor_at(key, new_stable_length) != Some(&new_stable))
+            .cloned()
+            .collect();
+        for key in pruned {
+            self.blocks.remove(&key);
+        }
+
+        if let Some(block) = self.blocks.get_mut(&new_stable) {
+            block.parent = None;
+        }
+        self.stable = new_stable;
+    }
+}
+
+impl<K, S> BlockStates<K, S> {
+    /// get the number of blocks tracked
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// always return false
+    pub fn is_empty(&self) -> bool {
+        debug_assert!(!self.blocks.is_empty());
+        self.blocks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follow_the_chain() {
+        let mut blocks = BlockStates::new(0u8, 0, "0", 2);
+
+        assert_eq!(blocks.push(1, 0, 1, "1"), Ok(TipUpdate::Extended));
+        assert_eq!(blocks.push(2, 1, 2, "2"), Ok(TipUpdate::Extended));
+
+        assert_eq!(blocks.tip(), (&2, &"2"));
+        assert_eq!(blocks.stable(), (&0, &"0"));
+        itertools::assert_equal(blocks.branch().map(|(k, _)| *k), vec![2, 1, 0]);
+
+        assert_eq!(blocks.push(3, 2, 3, "3"), Ok(TipUpdate::Extended));
+
+        // 0 is now too deep and has been pruned
+        assert_eq!(blocks.stable(), (&1, &"1"));
+        assert!(!blocks.contains(&0));
+        assert_eq!(blocks.len(), 3);
+    }
+
+    #[test]
+    fn switch_branch() {
+        let mut blocks = BlockStates::new(0u8, 0, "0", 3);
+
+        blocks.push(1, 0, 1, "1").unwrap();
+        blocks.push(2, 1, 2, "2").unwrap();
+        blocks.push(3, 2, 3, "3").unwrap();
+
+        // fork from 1
+        assert_eq!(
+            blocks.push(12, 1, 2, "12"),
+            Ok(TipUpdate::Switched {
+                rolled_back: vec![3, 2]
+            })
+        );
+        assert_eq!(blocks.tip(), (&12, &"12"));
+        itertools::assert_equal(blocks.branch().map(|(k, _)| *k), vec![12, 1, 0]);
+
+        // the previous branch is still known, we can switch back to it
+        assert_eq!(
+            blocks.rollback(&2),
+            Ok(TipUpdate::Switched {
+                rolled_back: vec![12]
+            })
+        );
+        assert_eq!(blocks.rollback(&3), Ok(TipUpdate::Extended));
+        assert_eq!(blocks.tip(), (&3, &"3"));
+    }
+
+    #[test]
+    fn prune_deep_branches() {
+        let mut blocks = BlockStates::new(0u8, 0, "0", 2);
+
+        blocks.push(1, 0, 1, "1").unwrap();
+        blocks.push(11, 0, 1, "11").unwrap();
+        blocks.push(12, 11, 2, "12").unwrap();
+        blocks.push(13, 12, 3, "13").unwrap();
+
+        // the branch of `1` forked before the new stable block
+        assert_eq!(blocks.stable(), (&11, &"11"));
+        assert!(!blocks.contains(&1));
+        assert_eq!(blocks.rollback(&1), Err(BlockStatesError::UnknownBlock));
+        assert_eq!(
+            blocks.push(2, 1, 2, "2"),
+            Err(BlockStatesError::UnknownParent)
+        );
+    }
+
+    #[test]
+    fn invalid_blocks() {
+        let mut blocks = BlockStates::new(0u8, 10, "0", 2);
+
+        assert_eq!(
+            blocks.push(1, 0, 12, "1"),
+            Err(BlockStatesError::InvalidChainLength {
+                chain_length: 12,
+                parent: 10
+            })
+        );
+        assert_eq!(
+            blocks.push(0, 0, 11, "0"),
+            Err(BlockStatesError::AlreadyKnown)
+        );
+        assert_eq!(blocks.len(), 1);
+    }
+}
@@ -0,0 +1,464 @@
+use crate::{
+    block_states::{BlockStates, BlockStatesError},
+    persistence::{Kind, PersistenceError, Reader, Writer},
+};
+use chain_impl_mockchain::{block::HeaderId, fragment::FragmentId};
+use std::collections::{HashMap, HashSet};
+
+/// number of blocks on top of the block containing a fragment before the
+/// fragment is considered confirmed
+pub const DEFAULT_CONFIRMATION_DEPTH: u32 = 10;
+
+/// number of blocks a pending fragment has to appear in a block before
+/// being reported as expired
+pub const DEFAULT_EXPIRATION_DEADLINE: u32 = 100;
+
+/// the block a fragment appeared in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Appearance {
+    pub block: HeaderId,
+    pub chain_length: u32,
+}
+
+/// what happened to the pending fragments after a block has been applied
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockUpdate {
+    confirmed: Vec<FragmentId>,
+    expired: Vec<FragmentId>,
+}
+
+struct Pending {
+    since: u32,
+    expired: bool,
+}
+
+/// follow the blocks of the blockchain to confirm the pending fragments
+/// of a wallet
+///
+/// For every block applied, the pending fragments of the wallet that are
+/// in the block are recorded with the block they appeared in. Once the
+/// block is `depth` blocks deep (`depth` blocks have been applied on top
+/// of it) the fragment is reported as confirmed.
+///
+/// The pending fragments that did not appear in a block after `deadline`
+/// blocks are reported as expired, only once. They are still tracked and
+/// will be confirmed if they appear in a block later on.
+///
+/// The blocks are tracked with their parent in [`BlockStates`], the state
+/// of a block being the pending fragments that appeared in it. A block
+/// whose parent is not the tip switches the tip to the branch of the block,
+/// even if the chain length did not change: the fragments that appeared in
+/// the blocks rolled back are pending again. The blocks more than `depth`
+/// blocks deep are pruned, it is not possible to rollback before them.
+pub struct Confirmations {
+    depth: u32,
+    deadline: u32,
+    blocks: Option<BlockStates<HeaderId, Vec<FragmentId>>>,
+    pending: HashMap<FragmentId, Pending>,
+}
+
+impl BlockUpdate {
+    /// the fragments that are now deep enough in the blockchain
+    pub fn confirmed(&self) -> &[FragmentId] {
+        &self.confirmed
+    }
+
+    /// the pending fragments that did not appear in a block before the
+    /// deadline
+    pub fn expired(&self) -> &[FragmentId] {
+        &self.expired
+    }
+}
+
+impl Confirmations {
+    pub fn new(depth: u32, deadline: u32) -> Self {
+        Self {
+            depth,
+            deadline,
+            blocks: None,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    pub fn deadline(&self) -> u32 {
+        self.deadline
+    }
+
+    /// the id and the chain length of the tip, the last block applied
+    pub fn tip(&self) -> Option<(HeaderId, u32)> {
+        let blocks = self.blocks.as_ref()?;
+        let (tip, _) = blocks.tip();
+        blocks
+            .chain_length(tip)
+            .map(|chain_length| (*tip, chain_length))
+    }
+
+    /// get the block of the tip's branch the given fragment appeared in
+    pub fn appearance(&self, fragment_id: &FragmentId) -> Option<Appearance> {
+        let blocks = self.blocks.as_ref()?;
+        blocks
+            .branch()
+            .find(|(_, fragments)| fragments.contains(fragment_id))
+            .and_then(|(block, _)| {
+                blocks.chain_length(block).map(|chain_length| Appearance {
+                    block: *block,
+                    chain_length,
+                })
+            })
+    }
+
+    /// apply a new block, on top of its `parent`, with the identifiers of
+    /// the fragments it contains
+    ///
+    /// `pending` are the pending fragments of the wallet, the fragments
+    /// that are no longer pending in the wallet (confirmed or removed) are
+    /// no longer tracked. The parent of the first block applied is not
+    /// checked. Applying a block that is already tracked moves the tip back
+    /// to it.
+    ///
+    /// The fragments reported as confirmed are no longer tracked, it is
+    /// expected they are confirmed in the wallet.
+    ///
+    /// # Errors
+    ///
+    /// * `BlockStatesError::UnknownParent` if the parent is not tracked,
+    ///   either because blocks are missing or because the block forks
+    ///   before the blocks that are `depth` blocks deep;
+    /// * `BlockStatesError::InvalidChainLength` if the chain length does not
+    ///   follow the parent's chain length.
+    pub fn apply_block<'a, I, P>(
+        &mut self,
+        block: HeaderId,
+        parent: HeaderId,
+        chain_length: u32,
+        fragments: I,
+        pending: P,
+    ) -> Result<BlockUpdate, BlockStatesError>
+    where
+        I: IntoIterator<Item = FragmentId>,
+        P: IntoIterator<Item = &'a FragmentId>,
+    {
+        let pending: HashSet<FragmentId> = pending.into_iter().copied().collect();
+        let appeared: Vec<FragmentId> = fragments
+            .into_iter()
+            .filter(|id| pending.contains(id))
+            .collect();
+
+        match self.blocks.as_mut() {
+            None => {
+                self.blocks = Some(BlockStates::new(block, chain_length, appeared, self.depth));
+            }
+            Some(blocks) if blocks.contains(&block) => {
+                blocks.rollback(&block)?;
+            }
+            Some(blocks) => {
+                blocks.push(block, parent, chain_length, appeared)?;
+            }
+        }
+
+        self.pending.retain(|id, _| pending.contains(id));
+        for id in pending.iter() {
+            self.pending.entry(*id).or_insert(Pending {
+                since: chain_length,
+                expired: false,
+            });
+        }
+
+        let blocks = self.blocks.as_ref().expect("a block has been applied");
+        let (tip, _) = blocks.tip();
+        let tip_length = blocks.chain_length(tip).expect("the tip is tracked");
+
+        let mut update = BlockUpdate::default();
+        let mut appeared = HashSet::new();
+        for (block, fragments) in blocks.branch() {
+            let block_length = blocks.chain_length(block).expect("the branch is tracked");
+            let confirmed = tip_length.saturating_sub(block_length) >= self.depth;
+            for id in fragments.iter().filter(|id| self.pending.contains_key(id)) {
+                if confirmed {
+                    update.confirmed.push(*id);
+                } else {
+                    appeared.insert(*id);
+                }
+            }
+        }
+        for id in update.confirmed.iter() {
+            self.pending.remove(id);
+        }
+
+        for (id, pending) in self.pending.iter_mut() {
+            if !pending.expired
+                && !appeared.contains(id)
+                && tip_length.saturating_sub(pending.since) >= self.deadline
+            {
+                pending.expired = true;
+                update.expired.push(*id);
+            }
+        }
+
+        Ok(update)
+    }
+
+    /// export the tracked blocks and pending fragments, see
+    /// `Confirmations::import`
+    pub fn export(&self) -> Vec<u8> {
+        let mut writer = Writer::new(Kind::Confirmations);
+        writer.u32(self.depth);
+        writer.u32(self.deadline);
+        match self.blocks.as_ref() {
+            None => writer.u8(0),
+            Some(blocks) => {
+                writer.u8(1);
+
+                // the parents are written before their children so the
+                // blocks can be pushed back in order
+                let mut tracked: Vec<_> = blocks.blocks().collect();
+                tracked.sort_by_key(|(_, _, chain_length, _)| *chain_length);
+
+                writer.count(tracked.len());
+                for (block, parent, chain_length, fragments) in tracked {
+                    writer.fragment_id(block);
+                    writer.fragment_id(parent.unwrap_or(block));
+                    writer.u32(chain_length);
+                    writer.count(fragments.len());
+                    for id in fragments.iter() {
+                        writer.fragment_id(id);
+                    }
+                }
+
+                writer.fragment_id(blocks.tip().0);
+            }
+        }
+
+        writer.count(self.pending.len());
+        for (id, pending) in self.pending.iter() {
+            writer.fragment_id(id);
+            writer.u32(pending.since);
+            writer.u8(pending.expired as u8);
+        }
+
+        writer.finalize()
+    }
+
+    pub fn import(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        let mut reader = Reader::new(bytes, Kind::Confirmations)?;
+        let depth = reader.u32()?;
+        let deadline = reader.u32()?;
+        let blocks = match reader.u8()? {
+            0 => None,
+            1 => Some(read_blocks(&mut reader, depth)?),
+            _ => return Err(PersistenceError::Invalid("blocks")),
+        };
+
+        let mut pending = HashMap::new();
+        for _ in 0..reader.count()? {
+            let id = reader.fragment_id()?;
+            let since = reader.u32()?;
+            let expired = match reader.u8()? {
+                0 => false,
+                1 => true,
+                _ => return Err(PersistenceError::Invalid("expiration")),
+            };
+            pending.insert(id, Pending { since, expired });
+        }
+
+        reader.finalize()?;
+
+        Ok(Self {
+            depth,
+            deadline,
+            blocks,
+            pending,
+        })
+    }
+}
+
+/// read the blocks written by `Confirmations::export`, the first one being
+/// the stable block
+fn read_blocks(
+    reader: &mut Reader,
+    depth: u32,
+) -> Result<BlockStates<HeaderId, Vec<FragmentId>>, PersistenceError> {
+    let count = reader.count()?;
+    if count == 0 {
+        return Err(PersistenceError::Invalid("blocks"));
+    }
+
+    let (stable, _, chain_length, fragments) = read_block(reader)?;
+    let mut blocks = BlockStates::new(stable, chain_length, fragments, depth);
+    for _ in 1..count {
+        let (block, parent, chain_length, fragments) = read_block(reader)?;
+        blocks
+            .push(block, parent, chain_length, fragments)
+            .map_err(|_| PersistenceError::Invalid("blocks"))?;
+    }
+
+    let tip = reader.fragment_id()?;
+    blocks
+        .rollback(&tip)
+        .map_err(|_| PersistenceError::Invalid("tip"))?;
+
+    Ok(blocks)
+}
+
+fn read_block(
+    reader: &mut Reader,
+) -> Result<(HeaderId, HeaderId, u32, Vec<FragmentId>), PersistenceError> {
+    let block = reader.fragment_id()?;
+    let parent = reader.fragment_id()?;
+    let chain_length = reader.u32()?;
+    let mut fragments = Vec::new();
+    for _ in 0..reader.count()? {
+        fragments.push(reader.fragment_id()?);
+    }
+    Ok((block, parent, chain_length, fragments))
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self::new(DEFAULT_CONFIRMATION_DEPTH, DEFAULT_EXPIRATION_DEADLINE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_impl_mockchain::key::Hash;
+
+    fn id(n: u8) -> FragmentId {
+        Hash::from_bytes([n; 32])
+    }
+
+    fn block(n: u8) -> HeaderId {
+        Hash::from_bytes([0x80 | n; 32])
+    }
+
+    /// apply the block `n` on top of the block `parent`
+    fn apply(
+        confirmations: &mut Confirmations,
+        n: u8,
+        parent: u8,
+        chain_length: u32,
+        fragments: Vec<FragmentId>,
+        pending: &[FragmentId],
+    ) -> BlockUpdate {
+        confirmations
+            .apply_block(block(n), block(parent), chain_length, fragments, pending)
+            .unwrap()
+    }
+
+    #[test]
+    fn confirm_when_deep_enough() {
+        let mut confirmations = Confirmations::new(2, 10);
+        let pending = vec![id(1), id(2)];
+
+        let update = apply(&mut confirmations, 1, 0, 1, vec![id(1), id(3)], &pending);
+        assert!(update.confirmed().is_empty());
+        assert_eq!(
+            confirmations.appearance(&id(1)),
+            Some(Appearance {
+                block: block(1),
+                chain_length: 1
+            })
+        );
+        // not one of the pending fragments
+        assert_eq!(confirmations.appearance(&id(3)), None);
+
+        let update = apply(&mut confirmations, 2, 1, 2, vec![], &pending);
+        assert!(update.confirmed().is_empty());
+
+        let update = apply(&mut confirmations, 3, 2, 3, vec![], &pending);
+        assert_eq!(update.confirmed(), &[id(1)]);
+        assert_eq!(confirmations.tip(), Some((block(3), 3)));
+    }
+
+    #[test]
+    fn rollback_forgets_the_appearances() {
+        let mut confirmations = Confirmations::new(2, 10);
+        let pending = vec![id(1)];
+
+        apply(&mut confirmations, 1, 0, 1, vec![], &pending);
+        apply(&mut confirmations, 2, 1, 2, vec![id(1)], &pending);
+
+        // a different block 2, without the fragment
+        apply(&mut confirmations, 12, 1, 2, vec![], &pending);
+        assert_eq!(confirmations.appearance(&id(1)), None);
+
+        let update = apply(&mut confirmations, 13, 12, 3, vec![id(1)], &pending);
+        assert!(update.confirmed().is_empty());
+        apply(&mut confirmations, 14, 13, 4, vec![], &pending);
+        let update = apply(&mut confirmations, 15, 14, 5, vec![], &pending);
+        assert_eq!(update.confirmed(), &[id(1)]);
+    }
+
+    #[test]
+    fn follow_the_branches_by_their_parent() {
+        let mut confirmations = Confirmations::new(2, 10);
+        let pending = vec![id(1), id(2)];
+
+        apply(&mut confirmations, 1, 0, 1, vec![], &pending);
+        apply(&mut confirmations, 2, 1, 2, vec![id(1)], &pending);
+        apply(&mut confirmations, 3, 2, 3, vec![], &pending);
+
+        assert_eq!(
+            confirmations.apply_block(block(13), block(12), 3, vec![], &pending),
+            Err(BlockStatesError::UnknownParent)
+        );
+
+        // the node switched to a different branch
+        apply(&mut confirmations, 12, 1, 2, vec![id(2)], &pending);
+        let update = apply(&mut confirmations, 13, 12, 3, vec![], &pending);
+        assert!(update.confirmed().is_empty());
+        assert_eq!(confirmations.tip(), Some((block(13), 3)));
+        assert_eq!(confirmations.appearance(&id(1)), None);
+
+        // and back to the first branch, with a longer chain
+        let update = apply(&mut confirmations, 4, 3, 4, vec![], &pending);
+        assert_eq!(update.confirmed(), &[id(1)]);
+        assert_eq!(confirmations.tip(), Some((block(4), 4)));
+        assert_eq!(confirmations.appearance(&id(2)), None);
+    }
+
+    #[test]
+    fn expire_once() {
+        let mut confirmations = Confirmations::new(0, 2);
+        let pending = vec![id(1)];
+
+        let update = apply(&mut confirmations, 1, 0, 1, vec![], &pending);
+        assert!(update.expired().is_empty());
+        apply(&mut confirmations, 2, 1, 2, vec![], &pending);
+        let update = apply(&mut confirmations, 3, 2, 3, vec![], &pending);
+        assert_eq!(update.expired(), &[id(1)]);
+        let update = apply(&mut confirmations, 4, 3, 4, vec![], &pending);
+        assert!(update.expired().is_empty());
+
+        // still confirmed if it appears late
+        let update = apply(&mut confirmations, 5, 4, 5, vec![id(1)], &pending);
+        assert_eq!(update.confirmed(), &[id(1)]);
+    }
+
+    #[test]
+    fn export_import() {
+        let mut confirmations = Confirmations::new(2, 3);
+        let pending = vec![id(1), id(2)];
+        apply(&mut confirmations, 1, 0, 1, vec![id(1)], &pending);
+        apply(&mut confirmations, 2, 1, 2, vec![], &pending);
+        apply(&mut confirmations, 12, 1, 2, vec![], &pending);
+
+        let mut imported = Confirmations::import(&confirmations.export()).unwrap();
+        assert_eq!(imported.tip(), Some((block(12), 2)));
+        assert_eq!(
+            imported.appearance(&id(1)),
+            confirmations.appearance(&id(1))
+        );
+
+        // the other branch has been imported too
+        let update = apply(&mut imported, 3, 2, 3, vec![], &pending);
+        assert_eq!(update.confirmed(), &[id(1)]);
+        let update = apply(&mut imported, 4, 3, 4, vec![], &pending);
+        assert_eq!(update.expired(), &[id(2)]);
+    }
+}
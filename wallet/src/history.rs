@@ -0,0 +1,484 @@
+use crate::persistence::{PersistenceError, Reader, Writer};
+use crate::scheme::{on_tx_input, on_tx_output};
+use chain_impl_mockchain::{
+    block::BlockDate,
+    fragment::{Fragment, FragmentId},
+    transaction::{Input, InputEnum, UtxoPointer},
+    value::Value,
+};
+use std::{collections::HashMap, convert::TryInto as _};
+
+/// how the funds moved for the wallet
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    /// the wallet received funds
+    Incoming,
+    /// the wallet spent funds
+    Outgoing,
+    /// the funds went from the wallet back to the wallet, only the fees
+    /// have been paid
+    Internal,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TransactionStatus {
+    Pending,
+    Confirmed,
+    Rejected,
+    /// no longer pending since the account's state was reconciled with the
+    /// node's: the node already used its spending counter, so it is either
+    /// on chain or never will be
+    Dropped,
+}
+
+/// a fragment that touched the wallet
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    fragment_id: FragmentId,
+    inputs: Vec<Input>,
+    outputs: Vec<UtxoPointer>,
+    fee: Option<Value>,
+    status: TransactionStatus,
+    block_date: Option<BlockDate>,
+}
+
+/// the history of all the fragments that touched the wallet, in the order
+/// the wallet has seen them
+#[derive(Default)]
+pub struct History {
+    entries: Vec<HistoryEntry>,
+    index: HashMap<FragmentId, usize>,
+}
+
+impl HistoryEntry {
+    /// create a new pending entry with the inputs and the outputs of the
+    /// fragment that belong to the wallet
+    ///
+    /// the fee is only known if the fragment is given and the wallet is
+    /// paying for it (at least one of the inputs belongs to the wallet).
+    pub(crate) fn new(
+        fragment_id: FragmentId,
+        fragment: Option<&Fragment>,
+        inputs: Vec<Input>,
+        outputs: Vec<UtxoPointer>,
+    ) -> Self {
+        let fee = if inputs.is_empty() {
+            None
+        } else {
+            fragment.and_then(transaction_fee)
+        };
+
+        Self {
+            fragment_id,
+            inputs,
+            outputs,
+            fee,
+            status: TransactionStatus::Pending,
+            block_date: None,
+        }
+    }
+
+    pub fn fragment_id(&self) -> &FragmentId {
+        &self.fragment_id
+    }
+
+    /// the inputs of the fragment that belong to the wallet
+    pub fn inputs(&self) -> &[Input] {
+        &self.inputs
+    }
+
+    /// the outputs of the fragment that belong to the wallet
+    pub fn outputs(&self) -> &[UtxoPointer] {
+        &self.outputs
+    }
+
+    /// the fee paid by the wallet, `None` if the wallet did not pay for
+    /// the fragment or if the fee is unknown
+    pub fn fee(&self) -> Option<Value> {
+        self.fee
+    }
+
+    pub fn status(&self) -> TransactionStatus {
+        self.status
+    }
+
+    /// the date of the block the fragment is in, if known
+    pub fn block_date(&self) -> Option<BlockDate> {
+        self.block_date
+    }
+
+    /// the total value of the inputs that belong to the wallet
+    pub fn spent(&self) -> Value {
+        self.inputs.iter().map(|input| input.value()).sum()
+    }
+
+    /// the total value of the outputs that belong to the wallet
+    pub fn received(&self) -> Value {
+        self.outputs.iter().map(|output| output.value).sum()
+    }
+
+    pub fn direction(&self) -> Direction {
+        let spent = self.spent();
+        let received = self.received();
+
+        if self.inputs.is_empty() || received > spent {
+            Direction::Incoming
+        } else if self.fee.is_some() && spent.checked_sub(received).ok() == self.fee {
+            Direction::Internal
+        } else {
+            Direction::Outgoing
+        }
+    }
+
+    /// merge the entry of the same fragment from another wallet, for example
+    /// to get the whole picture of a fragment moving funds between the
+    /// legacy wallets and the account
+    ///
+    /// the merged entry is rejected if any of the entries is rejected, is
+    /// confirmed if both are confirmed or if one is confirmed and the other
+    /// dropped, and is dropped if any of the other entries is dropped.
+    pub fn merge(&mut self, other: &HistoryEntry) {
+        debug_assert_eq!(self.fragment_id, other.fragment_id);
+
+        self.inputs.extend(other.inputs.iter().cloned());
+        self.outputs.extend(other.outputs.iter().copied());
+        self.fee = self.fee.or(other.fee);
+        self.block_date = self.block_date.or(other.block_date);
+        self.status = match (self.status, other.status) {
+            (TransactionStatus::Rejected, _) | (_, TransactionStatus::Rejected) => {
+                TransactionStatus::Rejected
+            }
+            (TransactionStatus::Confirmed, TransactionStatus::Confirmed)
+            | (TransactionStatus::Confirmed, TransactionStatus::Dropped)
+            | (TransactionStatus::Dropped, TransactionStatus::Confirmed) => {
+                TransactionStatus::Confirmed
+            }
+            (TransactionStatus::Dropped, _) | (_, TransactionStatus::Dropped) => {
+                TransactionStatus::Dropped
+            }
+            _ => TransactionStatus::Pending,
+        };
+    }
+
+    /// the net amount that went in (`Direction::Incoming`) or out (fees
+    /// included) of the wallet
+    pub fn net_value(&self) -> Value {
+        let spent = self.spent();
+        let received = self.received();
+
+        received
+            .checked_sub(spent)
+            .or_else(|_| spent.checked_sub(received))
+            .unwrap_or_else(|_| Value::zero())
+    }
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, fragment_id: &FragmentId) -> Option<&HistoryEntry> {
+        self.index
+            .get(fragment_id)
+            .map(|index| &self.entries[*index])
+    }
+
+    /// iterate through the entries, from the oldest to the most recent
+    pub fn iter(&self) -> std::slice::Iter<'_, HistoryEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// add the entry to the history, if there is already an entry for the
+    /// same fragment it is replaced but its status and block date are kept
+    pub(crate) fn record(&mut self, mut entry: HistoryEntry) {
+        if let Some(index) = self.index.get(&entry.fragment_id) {
+            let previous = &mut self.entries[*index];
+            entry.status = previous.status;
+            entry.block_date = previous.block_date;
+            *previous = entry;
+        } else {
+            self.index.insert(entry.fragment_id, self.entries.len());
+            self.entries.push(entry);
+        }
+    }
+
+    pub(crate) fn confirm(&mut self, fragment_id: &FragmentId) {
+        self.set_status(fragment_id, TransactionStatus::Confirmed)
+    }
+
+    pub(crate) fn reject(&mut self, fragment_id: &FragmentId) {
+        self.set_status(fragment_id, TransactionStatus::Rejected)
+    }
+
+    pub(crate) fn mark_dropped(&mut self, fragment_id: &FragmentId) {
+        self.set_status(fragment_id, TransactionStatus::Dropped)
+    }
+
+    pub(crate) fn set_block_date(&mut self, fragment_id: &FragmentId, block_date: BlockDate) {
+        if let Some(index) = self.index.get(fragment_id) {
+            self.entries[*index].block_date = Some(block_date);
+        }
+    }
+
+    fn set_status(&mut self, fragment_id: &FragmentId, status: TransactionStatus) {
+        if let Some(index) = self.index.get(fragment_id) {
+            let entry = &mut self.entries[*index];
+            // a dropped fragment may still be found on chain
+            let updatable = match entry.status {
+                TransactionStatus::Pending => true,
+                TransactionStatus::Dropped => status == TransactionStatus::Confirmed,
+                TransactionStatus::Confirmed | TransactionStatus::Rejected => false,
+            };
+            if updatable {
+                entry.status = status;
+            }
+        }
+    }
+
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        writer.count(self.entries.len());
+        for entry in self.entries.iter() {
+            writer.fragment_id(&entry.fragment_id);
+
+            writer.count(entry.inputs.len());
+            for input in entry.inputs.iter() {
+                match input.to_enum() {
+                    InputEnum::UtxoInput(utxo) => {
+                        writer.u8(0);
+                        writer.utxo(&utxo);
+                    }
+                    InputEnum::AccountInput(account, value) => {
+                        writer.u8(1);
+                        writer.bytes(account.as_ref());
+                        writer.u64(value.0);
+                    }
+                }
+            }
+
+            writer.count(entry.outputs.len());
+            for output in entry.outputs.iter() {
+                writer.utxo(output);
+            }
+
+            match entry.fee {
+                None => writer.u8(0),
+                Some(fee) => {
+                    writer.u8(1);
+                    writer.u64(fee.0);
+                }
+            }
+
+            writer.u8(match entry.status {
+                TransactionStatus::Pending => 0,
+                TransactionStatus::Confirmed => 1,
+                TransactionStatus::Rejected => 2,
+                TransactionStatus::Dropped => 3,
+            });
+
+            match entry.block_date {
+                None => writer.u8(0),
+                Some(date) => {
+                    writer.u8(1);
+                    writer.u32(date.epoch);
+                    writer.u32(date.slot_id);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn read(reader: &mut Reader) -> Result<Self, PersistenceError> {
+        let mut history = Self::new();
+
+        for _ in 0..reader.count()? {
+            let fragment_id = reader.fragment_id()?;
+
+            let mut inputs = Vec::new();
+            for _ in 0..reader.count()? {
+                let input = match reader.u8()? {
+                    0 => InputEnum::UtxoInput(reader.utxo()?),
+                    1 => {
+                        let account: [u8; 32] = reader.bytes(32)?.try_into().unwrap();
+                        InputEnum::AccountInput(account.into(), Value(reader.u64()?))
+                    }
+                    _ => return Err(PersistenceError::Invalid("history input")),
+                };
+                inputs.push(Input::from_enum(input));
+            }
+
+            let mut outputs = Vec::new();
+            for _ in 0..reader.count()? {
+                outputs.push(reader.utxo()?);
+            }
+
+            let fee = match reader.u8()? {
+                0 => None,
+                1 => Some(Value(reader.u64()?)),
+                _ => return Err(PersistenceError::Invalid("history fee")),
+            };
+
+            let status = match reader.u8()? {
+                0 => TransactionStatus::Pending,
+                1 => TransactionStatus::Confirmed,
+                2 => TransactionStatus::Rejected,
+                3 => TransactionStatus::Dropped,
+                _ => return Err(PersistenceError::Invalid("history status")),
+            };
+
+            let block_date = match reader.u8()? {
+                0 => None,
+                1 => Some(BlockDate {
+                    epoch: reader.u32()?,
+                    slot_id: reader.u32()?,
+                }),
+                _ => return Err(PersistenceError::Invalid("history block date")),
+            };
+
+            if history.get(&fragment_id).is_some() {
+                return Err(PersistenceError::Invalid("duplicated history entry"));
+            }
+
+            history.record(HistoryEntry {
+                fragment_id,
+                inputs,
+                outputs,
+                fee,
+                status,
+                block_date,
+            });
+        }
+
+        Ok(history)
+    }
+}
+
+/// the fee of the transaction: the value of the inputs that is not in the
+/// outputs
+fn transaction_fee(fragment: &Fragment) -> Option<Value> {
+    let mut inputs = Value::zero();
+    let mut outputs = Value::zero();
+
+    on_tx_input(fragment, |input| {
+        inputs = inputs.saturating_add(input.value())
+    });
+    on_tx_output(fragment, |(_, output)| {
+        outputs = outputs.saturating_add(output.value)
+    });
+
+    inputs.checked_sub(outputs).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_impl_mockchain::key::Hash;
+
+    fn utxo(n: u8, value: u64) -> UtxoPointer {
+        UtxoPointer {
+            transaction_id: Hash::from_bytes([n; 32]),
+            output_index: 0,
+            value: Value(value),
+        }
+    }
+
+    fn entry(inputs: Vec<UtxoPointer>, outputs: Vec<UtxoPointer>, fee: u64) -> HistoryEntry {
+        let inputs: Vec<_> = inputs.into_iter().map(Input::from_utxo).collect();
+        let fee = if inputs.is_empty() {
+            None
+        } else {
+            Some(Value(fee))
+        };
+
+        HistoryEntry {
+            fragment_id: Hash::from_bytes([0xff; 32]),
+            inputs,
+            outputs,
+            fee,
+            status: TransactionStatus::Pending,
+            block_date: None,
+        }
+    }
+
+    #[test]
+    fn direction_and_net_value() {
+        let incoming = entry(vec![], vec![utxo(1, 10)], 0);
+        assert_eq!(incoming.direction(), Direction::Incoming);
+        assert_eq!(incoming.net_value(), Value(10));
+
+        let outgoing = entry(vec![utxo(1, 10)], vec![utxo(2, 3)], 1);
+        assert_eq!(outgoing.direction(), Direction::Outgoing);
+        assert_eq!(outgoing.net_value(), Value(7));
+
+        let internal = entry(vec![utxo(1, 10)], vec![utxo(2, 9)], 1);
+        assert_eq!(internal.direction(), Direction::Internal);
+        assert_eq!(internal.net_value(), Value(1));
+    }
+
+    #[test]
+    fn merge_entries_of_the_same_fragment() {
+        let mut spending = entry(vec![utxo(1, 10)], vec![], 1);
+        spending.status = TransactionStatus::Confirmed;
+        let receiving = entry(vec![], vec![utxo(2, 9)], 0);
+
+        spending.merge(&receiving);
+        assert_eq!(spending.direction(), Direction::Internal);
+        assert_eq!(spending.fee(), Some(Value(1)));
+        assert_eq!(spending.status(), TransactionStatus::Pending);
+    }
+
+    #[test]
+    fn status_is_only_updated_once() {
+        let mut history = History::new();
+        let entry = entry(vec![], vec![utxo(1, 10)], 0);
+        let id = *entry.fragment_id();
+        history.record(entry);
+
+        history.confirm(&id);
+        history.reject(&id);
+        assert_eq!(
+            history.get(&id).unwrap().status(),
+            TransactionStatus::Confirmed
+        );
+
+        // recording the same fragment again keeps the status
+        history.record(self::entry(vec![], vec![utxo(1, 10)], 0));
+        assert_eq!(history.len(), 1);
+        assert_eq!(
+            history.get(&id).unwrap().status(),
+            TransactionStatus::Confirmed
+        );
+    }
+
+    #[test]
+    fn dropped_entry_can_still_be_confirmed() {
+        let mut history = History::new();
+        let entry = entry(vec![utxo(1, 10)], vec![], 1);
+        let id = *entry.fragment_id();
+        history.record(entry);
+
+        history.mark_dropped(&id);
+        history.reject(&id);
+        assert_eq!(
+            history.get(&id).unwrap().status(),
+            TransactionStatus::Dropped
+        );
+
+        let mut merged = history.get(&id).unwrap().clone();
+        merged.merge(&self::entry(vec![], vec![utxo(2, 9)], 0));
+        assert_eq!(merged.status(), TransactionStatus::Dropped);
+
+        history.confirm(&id);
+        assert_eq!(
+            history.get(&id).unwrap().status(),
+            TransactionStatus::Confirmed
+        );
+    }
+}
@@ -1,7 +1,11 @@
 mod account;
+mod block_states;
 mod blockchain;
+mod confirmations;
+mod history;
 mod keygen;
 mod password;
+mod persistence;
 mod recovering;
 pub mod scheme;
 mod states;
@@ -9,9 +13,18 @@ mod store;
 pub mod transaction;
 
 pub use self::{
-    account::Wallet,
+    account::{
+        delegation_type, PaymentError, Reconciliation, SpendingCounterError, SyncStatus, Wallet,
+    },
+    block_states::{BlockStates, BlockStatesError, TipUpdate},
     blockchain::Settings,
+    confirmations::{
+        Appearance, BlockUpdate, Confirmations, DEFAULT_CONFIRMATION_DEPTH,
+        DEFAULT_EXPIRATION_DEADLINE,
+    },
+    history::{Direction, History, HistoryEntry, TransactionStatus},
     password::{Password, ScrubbedBytes},
+    persistence::PersistenceError,
     recovering::{RecoveryBuilder, RecoveryError},
     transaction::{AccountWitnessBuilder, TransactionBuilder},
 };
@@ -0,0 +1,463 @@
+//! stable and versioned binary serialization of the wallets' state
+//!
+//! Every exported wallet starts with a header of 2 bytes: the version of
+//! the format ([`VERSION`]) and the kind of wallet that has been exported.
+//! All the integers are encoded in big endian.
+//!
+//! The [`States`] are serialized from the confirmed state up to the most
+//! recent one with their status, so the pending transactions are kept
+//! as pending. The [`UtxoStore`] of a state is only serialized as the
+//! difference with the store of the previous state: the UTxOs that have
+//! been removed and the one that have been added.
+//!
+//! The signing keys associated to the UTxOs are not serialized, only what
+//! is needed to derive them again from the root key of the wallet (the
+//! derivation path for example).
+
+use crate::{
+    states::{States, Status},
+    store::{Groupable, UtxoStore},
+};
+use chain_impl_mockchain::{
+    fragment::FragmentId, key::Hash, transaction::UtxoPointer, value::Value,
+};
+use chain_path_derivation::{AnyScheme, Derivation, DerivationPath};
+use ed25519_bip32::{XPrv, XPub, XPRV_SIZE, XPUB_SIZE};
+use std::convert::TryInto;
+use thiserror::Error;
+
+/// the current version of the serialization format
+pub const VERSION: u8 = 1;
+
+#[derive(Debug, Error)]
+pub enum PersistenceError {
+    #[error("unsupported serialization format version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("the serialized wallet is not a {expected} wallet")]
+    InvalidKind { expected: &'static str },
+
+    #[error("unexpected end of the serialized wallet")]
+    UnexpectedEnd,
+
+    #[error("unexpected trailing bytes at the end of the serialized wallet")]
+    TrailingBytes,
+
+    #[error("invalid {0} in the serialized wallet")]
+    Invalid(&'static str),
+}
+
+/// the kind of wallet that is serialized, this is checked on import so
+/// a wallet is not mistakenly imported as a wallet of a different kind
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Account = 1,
+    Rindex = 2,
+    Bip44Legacy = 3,
+    Bip44 = 4,
+    FreeUtxo = 5,
+    Confirmations = 6,
+    UnsignedTransaction = 7,
+    Bip44LegacyWatchOnly = 8,
+    Bip44WatchOnly = 9,
+}
+
+pub(crate) struct Writer {
+    bytes: Vec<u8>,
+}
+
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Account => "account",
+            Self::Rindex => "daedalus",
+            Self::Bip44Legacy => "yoroi",
+            Self::Bip44 => "bip44",
+            Self::FreeUtxo => "free utxo",
+            Self::Confirmations => "confirmations",
+            Self::UnsignedTransaction => "unsigned transaction",
+            Self::Bip44LegacyWatchOnly => "yoroi watch-only",
+            Self::Bip44WatchOnly => "bip44 watch-only",
+        }
+    }
+}
+
+impl Writer {
+    pub(crate) fn new(kind: Kind) -> Self {
+        let mut writer = Self {
+            bytes: Vec::with_capacity(1024),
+        };
+        writer.u8(VERSION);
+        writer.u8(kind as u8);
+        writer
+    }
+
+    pub(crate) fn finalize(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub(crate) fn u8(&mut self, v: u8) {
+        self.bytes.push(v)
+    }
+
+    pub(crate) fn u32(&mut self, v: u32) {
+        self.bytes.extend_from_slice(&v.to_be_bytes())
+    }
+
+    pub(crate) fn u64(&mut self, v: u64) {
+        self.bytes.extend_from_slice(&v.to_be_bytes())
+    }
+
+    pub(crate) fn count(&mut self, count: usize) {
+        self.u32(count.try_into().expect("no more than 2^32 entries"))
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes)
+    }
+
+    pub(crate) fn xprv(&mut self, xprv: &XPrv) {
+        self.bytes(xprv.as_ref())
+    }
+
+    pub(crate) fn xpub(&mut self, xpub: &XPub) {
+        self.bytes(xpub.as_ref())
+    }
+
+    pub(crate) fn path<S>(&mut self, path: &DerivationPath<S>) {
+        self.count(path.len());
+        for derivation in path.iter() {
+            self.u32(**derivation);
+        }
+    }
+
+    /// the protocol magic of the Byron network, `None` for the mainnet
+    pub(crate) fn protocol_magic(&mut self, protocol_magic: Option<u32>) {
+        match protocol_magic {
+            None => self.u8(0),
+            Some(protocol_magic) => {
+                self.u8(1);
+                self.u32(protocol_magic);
+            }
+        }
+    }
+
+    pub(crate) fn fragment_id(&mut self, id: &FragmentId) {
+        self.bytes(id.as_ref())
+    }
+
+    pub(crate) fn utxo(&mut self, utxo: &UtxoPointer) {
+        self.fragment_id(&utxo.transaction_id);
+        self.u8(utxo.output_index);
+        self.u64(utxo.value.0);
+    }
+
+    fn status(&mut self, status: Status) {
+        self.u8(match status {
+            Status::Confirmed => 0,
+            Status::Pending => 1,
+        })
+    }
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8], kind: Kind) -> Result<Self, PersistenceError> {
+        let mut reader = Self { bytes };
+
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(PersistenceError::UnsupportedVersion(version));
+        }
+
+        if reader.u8()? != kind as u8 {
+            return Err(PersistenceError::InvalidKind {
+                expected: kind.name(),
+            });
+        }
+
+        Ok(reader)
+    }
+
+    /// check all the bytes have been read
+    pub(crate) fn finalize(self) -> Result<(), PersistenceError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(PersistenceError::TrailingBytes)
+        }
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], PersistenceError> {
+        if self.bytes.len() < len {
+            return Err(PersistenceError::UnexpectedEnd);
+        }
+
+        let (bytes, remaining) = self.bytes.split_at(len);
+        self.bytes = remaining;
+        Ok(bytes)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, PersistenceError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, PersistenceError> {
+        let bytes = self.bytes(4)?.try_into().unwrap();
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, PersistenceError> {
+        let bytes = self.bytes(8)?.try_into().unwrap();
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    pub(crate) fn count(&mut self) -> Result<usize, PersistenceError> {
+        self.u32().map(|count| count as usize)
+    }
+
+    pub(crate) fn xprv(&mut self) -> Result<XPrv, PersistenceError> {
+        let bytes = self.bytes(XPRV_SIZE)?.try_into().unwrap();
+        XPrv::from_bytes_verified(bytes).map_err(|_| PersistenceError::Invalid("private key"))
+    }
+
+    pub(crate) fn xpub(&mut self) -> Result<XPub, PersistenceError> {
+        XPub::from_slice(self.bytes(XPUB_SIZE)?)
+            .map_err(|_| PersistenceError::Invalid("public key"))
+    }
+
+    /// read a derivation path, it is expected to be of the given length
+    pub(crate) fn path<S>(&mut self, len: usize) -> Result<DerivationPath<S>, PersistenceError> {
+        if self.count()? != len {
+            return Err(PersistenceError::Invalid("derivation path"));
+        }
+
+        let path = (0..len)
+            .map(|_| self.u32().map(Derivation::new))
+            .collect::<Result<DerivationPath<AnyScheme>, _>>()?;
+
+        Ok(path.coerce_unchecked())
+    }
+
+    /// read a derivation path of any length
+    pub(crate) fn any_path(&mut self) -> Result<DerivationPath<AnyScheme>, PersistenceError> {
+        let len = self.count()?;
+        (0..len).map(|_| self.u32().map(Derivation::new)).collect()
+    }
+
+    pub(crate) fn protocol_magic(&mut self) -> Result<Option<u32>, PersistenceError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => self.u32().map(Some),
+            _ => Err(PersistenceError::Invalid("protocol magic")),
+        }
+    }
+
+    pub(crate) fn fragment_id(&mut self) -> Result<FragmentId, PersistenceError> {
+        let bytes = self.bytes(32)?.try_into().unwrap();
+        Ok(Hash::from_bytes(bytes))
+    }
+
+    pub(crate) fn utxo(&mut self) -> Result<UtxoPointer, PersistenceError> {
+        Ok(UtxoPointer {
+            transaction_id: self.fragment_id()?,
+            output_index: self.u8()?,
+            value: Value(self.u64()?),
+        })
+    }
+
+    fn status(&mut self) -> Result<Status, PersistenceError> {
+        match self.u8()? {
+            0 => Ok(Status::Confirmed),
+            1 => Ok(Status::Pending),
+            _ => Err(PersistenceError::Invalid("state status")),
+        }
+    }
+}
+
+/// write all the states, from the confirmed one up to the most recent one
+///
+/// `write_state` is called with the previous state (`None` for the confirmed
+/// state) so only the difference between the states can be serialized.
+pub(crate) fn write_states<S, F>(
+    writer: &mut Writer,
+    states: &States<FragmentId, S>,
+    mut write_state: F,
+) where
+    F: FnMut(&mut Writer, Option<&S>, &S),
+{
+    writer.count(states.len());
+
+    let mut previous = None;
+    for (id, state, status) in states.iter() {
+        writer.fragment_id(id);
+        writer.status(status);
+        write_state(writer, previous, state);
+        previous = Some(state);
+    }
+}
+
+/// read the states written with `write_states`
+///
+/// `read_state` is called with the previous state (`None` for the confirmed
+/// state).
+pub(crate) fn read_states<S, F>(
+    reader: &mut Reader,
+    mut read_state: F,
+) -> Result<States<FragmentId, S>, PersistenceError>
+where
+    F: FnMut(&mut Reader, Option<&S>) -> Result<S, PersistenceError>,
+{
+    let len = reader.count()?;
+    if len == 0 {
+        return Err(PersistenceError::Invalid("states"));
+    }
+
+    let id = reader.fragment_id()?;
+    if reader.status()? != Status::Confirmed {
+        return Err(PersistenceError::Invalid("state status"));
+    }
+    let state = read_state(reader, None)?;
+    let mut states = States::new(id, state);
+
+    for _ in 1..len {
+        let id = reader.fragment_id()?;
+        let status = reader.status()?;
+        let state = read_state(reader, Some(states.last_state().1))?;
+
+        if states.contains(&id) {
+            return Err(PersistenceError::Invalid("duplicated state"));
+        }
+
+        states.push(id, state);
+        if status == Status::Confirmed {
+            states.confirm(&id);
+        }
+    }
+
+    Ok(states)
+}
+
+/// write the UTxOs of the `store` that are not in the `previous` store and
+/// the UTxOs of the `previous` store that are not in `store` anymore.
+pub(crate) fn write_utxo_store<K, F>(
+    writer: &mut Writer,
+    previous: Option<&UtxoStore<K>>,
+    store: &UtxoStore<K>,
+    mut write_key: F,
+) where
+    K: Groupable,
+    F: FnMut(&mut Writer, &K),
+{
+    let empty = UtxoStore::new();
+    let previous = previous.unwrap_or(&empty);
+
+    let removed: Vec<_> = previous.difference(store).collect();
+    writer.count(removed.len());
+    for utxo in removed {
+        writer.utxo(utxo);
+    }
+
+    let added: Vec<_> = store.difference(previous).collect();
+    writer.count(added.len());
+    for utxo in added {
+        let key = store
+            .get_signing_key(utxo)
+            .expect("the utxo is in the store");
+        writer.utxo(utxo);
+        write_key(writer, &key);
+    }
+}
+
+/// read a UTxO store written with `write_utxo_store`, applying the
+/// changes on top of the `previous` store.
+pub(crate) fn read_utxo_store<K, F>(
+    reader: &mut Reader,
+    previous: Option<&UtxoStore<K>>,
+    mut read_key: F,
+) -> Result<UtxoStore<K>, PersistenceError>
+where
+    K: Groupable,
+    F: FnMut(&mut Reader) -> Result<K, PersistenceError>,
+{
+    let mut store = previous.cloned().unwrap_or_else(UtxoStore::new);
+
+    for _ in 0..reader.count()? {
+        let utxo = reader.utxo()?;
+        store = store
+            .remove(&utxo)
+            .ok_or(PersistenceError::Invalid("removed utxo"))?;
+    }
+
+    for _ in 0..reader.count()? {
+        let utxo = reader.utxo()?;
+        let key = read_key(reader)?;
+        if store.contains(&utxo) {
+            return Err(PersistenceError::Invalid("duplicated utxo"));
+        }
+        store = store.add(utxo, key);
+    }
+
+    Ok(store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn states_are_kept_with_their_status() {
+        let id = |i: u8| Hash::from_bytes([i; 32]);
+
+        let mut states = States::new(id(0), 0u64);
+        states.push(id(1), 10);
+        states.push(id(2), 20);
+        states.push(id(3), 30);
+        states.confirm(&id(2));
+
+        let mut writer = Writer::new(Kind::Account);
+        write_states(&mut writer, &states, |writer, _, state| writer.u64(*state));
+        let bytes = writer.finalize();
+
+        let mut reader = Reader::new(&bytes, Kind::Account).unwrap();
+        let mut decoded = read_states(&mut reader, |reader, _| reader.u64()).unwrap();
+        reader.finalize().unwrap();
+
+        itertools::assert_equal(states.iter(), decoded.iter());
+        // `id(2)` is confirmed but stays behind the pending `id(1)`
+        itertools::assert_equal(
+            decoded.iter().map(|(key, _, status)| (*key, status)),
+            vec![
+                (id(0), Status::Confirmed),
+                (id(1), Status::Pending),
+                (id(2), Status::Confirmed),
+                (id(3), Status::Pending),
+            ],
+        );
+        assert_eq!(decoded.confirmed_state(), (&id(0), &0));
+
+        decoded.confirm(&id(1));
+        assert_eq!(decoded.confirmed_state(), (&id(2), &20));
+    }
+
+    #[test]
+    fn header_is_checked() {
+        let bytes = Writer::new(Kind::Account).finalize();
+
+        assert!(matches!(
+            Reader::new(&bytes, Kind::Rindex),
+            Err(PersistenceError::InvalidKind { .. })
+        ));
+        assert!(matches!(
+            Reader::new(&[VERSION + 1, Kind::Account as u8], Kind::Account),
+            Err(PersistenceError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            Reader::new(&[VERSION], Kind::Account),
+            Err(PersistenceError::UnexpectedEnd)
+        ));
+    }
+}
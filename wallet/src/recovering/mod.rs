@@ -2,7 +2,7 @@
 
 mod paperwallet;
 
-use crate::{account::Wallet, keygen, scheme as wallet, Password};
+use crate::{account::Wallet, keygen, scheme as wallet, transaction::SoftwareSigner, Password};
 use chain_crypto::{Ed25519Extended, SecretKey};
 use chain_impl_mockchain::legacy::OldAddress;
 use chain_path_derivation::{
@@ -24,6 +24,12 @@ pub enum RecoveryError {
     MissingEntropy,
     #[error("Tried to recover same utxo more than once, either the function was called twice or the block is malformed")]
     DuplicatedUtxo,
+
+    #[error(
+        "The gap limit must be greater than 0 and not greater than {}",
+        wallet::bip44::MAX_GAP_LIMIT
+    )]
+    InvalidGapLimit,
 }
 
 pub struct RecoveryBuilder {
@@ -31,6 +37,8 @@ pub struct RecoveryBuilder {
     password: Option<Password>,
     free_keys: Vec<SecretKey<Ed25519Extended>>,
     account: Option<AccountFrom>,
+    gap_limit: Option<u32>,
+    protocol_magic: Option<u32>,
 }
 
 enum AccountFrom {
@@ -100,6 +108,26 @@ impl RecoveryBuilder {
         }
     }
 
+    /// the number of consecutive unused addresses to scan on each chain of
+    /// the bip44 accounts before stopping the discovery, see
+    /// `bip44::DEFAULT_GAP_LIMIT` for the default and `bip44::MAX_GAP_LIMIT`
+    /// for the largest one
+    pub fn gap_limit(self, gap_limit: u32) -> Self {
+        Self {
+            gap_limit: Some(gap_limit),
+            ..self
+        }
+    }
+
+    /// recover the legacy addresses (Daedalus and Yoroi) of the Byron
+    /// network with the given protocol magic instead of the mainnet
+    pub fn protocol_magic(self, protocol_magic: u32) -> Self {
+        Self {
+            protocol_magic: Some(protocol_magic),
+            ..self
+        }
+    }
+
     pub fn add_key(mut self, key: SecretKey<Ed25519Extended>) -> Self {
         self.free_keys.push(key);
         self
@@ -115,10 +143,17 @@ impl RecoveryBuilder {
         let key = from_daedalus_entropy(entropy, ed25519_bip32::DerivationScheme::V1)
             .expect("Cannot fail to serialize some bytes...");
 
-        Ok(wallet::rindex::Wallet::from_root_key(key))
+        Ok(wallet::rindex::Wallet::from_root_key_with_protocol_magic(
+            key,
+            self.protocol_magic,
+        ))
     }
 
     pub fn build_yoroi(&self) -> Result<wallet::bip44::Wallet<OldAddress>, RecoveryError> {
+        let gap_limit = self.gap_limit.unwrap_or(wallet::bip44::DEFAULT_GAP_LIMIT);
+        if gap_limit == 0 || gap_limit > wallet::bip44::MAX_GAP_LIMIT {
+            return Err(RecoveryError::InvalidGapLimit);
+        }
         let entropy = self.entropy.clone().ok_or(RecoveryError::MissingEntropy)?;
         let password = self.password.clone().unwrap_or_default();
 
@@ -127,13 +162,29 @@ impl RecoveryBuilder {
         let root: Key<XPrv, Bip44<bip44::Root>> = key.coerce_unchecked();
         let key = root.bip44().cardano();
 
-        Ok(wallet::bip44::Wallet::<OldAddress>::from_root_key(key))
+        Ok(
+            wallet::bip44::Wallet::<OldAddress>::from_root_key_with_protocol_magic(
+                key,
+                gap_limit,
+                self.protocol_magic,
+            ),
+        )
     }
 
     pub fn build_wallet(&self) -> Result<Wallet, RecoveryError> {
-        let wallet = match &self.account {
-            Some(AccountFrom::SecretKey(key)) => Wallet::new_from_key(key.clone()),
-            Some(AccountFrom::Seed(seed)) => Wallet::new_from_seed(*seed),
+        let wallet = match self.account()? {
+            AccountFrom::SecretKey(key) => Wallet::new_from_key(key),
+            AccountFrom::Seed(seed) => Wallet::new_from_seed(seed),
+        };
+        Ok(wallet)
+    }
+
+    /// the key of the account: the one given with `account_seed` or
+    /// `account_secret_key`, or else the seed generated from the mnemonics
+    fn account(&self) -> Result<AccountFrom, RecoveryError> {
+        match &self.account {
+            Some(AccountFrom::SecretKey(key)) => Ok(AccountFrom::SecretKey(key.clone())),
+            Some(AccountFrom::Seed(seed)) => Ok(AccountFrom::Seed(*seed)),
             None => {
                 let entropy = self.entropy.clone().ok_or(RecoveryError::MissingEntropy)?;
                 let password = self.password.clone().unwrap_or_default();
@@ -141,10 +192,27 @@ impl RecoveryBuilder {
                 let mut seed = [0u8; hdkeygen::account::SEED_LENGTH];
                 keygen::generate_seed(&entropy, password.as_ref(), &mut seed);
 
-                Wallet::new_from_seed(seed)
+                Ok(AccountFrom::Seed(seed))
             }
-        };
-        Ok(wallet)
+        }
+    }
+
+    /// build a signer holding the bip39 root key of the wallet (the root
+    /// key of the Yoroi and bip44 wallets) and the key of the account (see
+    /// `build_wallet`), the inputs are identified by their derivation path
+    /// from the root key or by the public key of the account
+    pub fn build_software_signer(&self) -> Result<SoftwareSigner, RecoveryError> {
+        let entropy = self.entropy.clone().ok_or(RecoveryError::MissingEntropy)?;
+        let password = self.password.clone().unwrap_or_default();
+
+        let key = from_bip39_entropy(entropy, password, ed25519_bip32::DerivationScheme::V2);
+
+        let signer = SoftwareSigner::new(key);
+
+        Ok(match self.account()? {
+            AccountFrom::SecretKey(key) => signer.with_account(key),
+            AccountFrom::Seed(seed) => signer.with_account_seed(seed),
+        })
     }
 
     pub fn build_free_utxos(&self) -> Result<wallet::freeutxo::Wallet, RecoveryError> {
@@ -258,6 +326,8 @@ impl Default for RecoveryBuilder {
             password: Default::default(),
             free_keys: Vec::<SecretKey<Ed25519Extended>>::new(),
             account: Default::default(),
+            gap_limit: Default::default(),
+            protocol_magic: Default::default(),
         }
     }
 }
@@ -290,6 +360,32 @@ mod tests {
         "Ae2tdPwUPEZ8og5u4WF5rmSyme5Gvp8RYiLM2u7Vm8CyDQzLN3VYTN895Wk",
         "Ae2tdPwUPEZEAjEsQsCtBMkLKANxQUEvzLkumPWWYugLeXcgkeMCDH1gnuL",
     ];
+    /// the Byron testnet
+    const PROTOCOL_MAGIC3: u32 = 1_097_911_063;
+    /// the receive addresses 0 and 1 then the change addresses 0 and 1 of
+    /// the first account, on the Byron testnet
+    const ADDRESSES3_TESTNET: &[&str] = &[
+        "2cWKMJemoBamMjTawcLYQmvyj1iBJwdfQARAXwoVWejVncaGuGc4ebjBc48qHHBJ6VL9J",
+        "2cWKMJemoBak981p6MX6iEXcTBL4ev5H2yFxnZXg5RcFAB2jSS8YqqP5wTxfbAyj5mpLW",
+        "2cWKMJemoBaiE8Us4xh27MbJ3LraFKtdM4739cY2c2u4xyJiK26JqF7731yRzpRn6g81E",
+        "2cWKMJemoBakSkM9xrc6wa5kaAqfbFqpCM9GPyL4QyDj2YCojkPDCzxkYDcFWm7jSbS3r",
+    ];
+    const PASSWORD3: &str = "password";
+    /// the receive addresses 0 and 1 then the change addresses 0 and 1 of
+    /// the first account, with the mnemonics protected by `PASSWORD3`
+    ///
+    /// computed, with `ACCOUNT3_PASSWORD`, by the independent implementation
+    /// of `test-vectors/yoroi/recover_with_password.py`, which also gives
+    /// `ADDRESSES3` and `ACCOUNT3` without the password
+    const ADDRESSES3_PASSWORD: &[&str] = &[
+        "Ae2tdPwUPEYz3zig6hg7NHkGAYat1wHHzrFQznb2C4M3vubm9nyH8pB1BXJ",
+        "Ae2tdPwUPEZ7WeC8rRnV1dBBhoPm1urN8R5EzUaYEwJBiLboaJEcK5QStCW",
+        "Ae2tdPwUPEZB91Y7N9WYP7w9DD9yvptjPfH4xWBkBpiwG21hyh1BSJLoPVU",
+        "Ae2tdPwUPEZDdgGQMXjfAxSxvtgPTWPgwHYEiqSqekPFagve4vHkpaNRVAY",
+    ];
+    const ACCOUNT3: &str = "d06583fdcc3293e717271408a2283228cd63bf1a35da1d50b734567dee1bfae9";
+    const ACCOUNT3_PASSWORD: &str =
+        "7baab35876156f3119a6a49b3efa771f56e429dec5aee1f01cc7d4f834dcc044";
 
     /// not sure yet, but it appears this test is not valid
     ///
@@ -406,6 +502,120 @@ mod tests {
         assert_eq!(wallet.unconfirmed_value(), Some(fragment_value));
     }
 
+    #[test]
+    fn recover_yoroi_mainnet_and_testnet() {
+        let mut mainnet = RecoveryBuilder::new()
+            .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS3)
+            .unwrap()
+            .build_yoroi()
+            .unwrap();
+        let mut testnet = RecoveryBuilder::new()
+            .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS3)
+            .unwrap()
+            .protocol_magic(PROTOCOL_MAGIC3)
+            .build_yoroi()
+            .unwrap();
+        assert_eq!(mainnet.protocol_magic(), None);
+        assert_eq!(testnet.protocol_magic(), Some(PROTOCOL_MAGIC3));
+
+        // the addresses are issued before any of them is seen on the
+        // blockchain
+        let issued = [
+            testnet.next_receive_address().unwrap(),
+            testnet.next_receive_address().unwrap(),
+            testnet.next_change_address().unwrap(),
+            testnet.next_change_address().unwrap(),
+        ];
+        for (issued, expected) in issued.iter().zip(ADDRESSES3_TESTNET) {
+            assert_eq!(issued.to_string(), *expected);
+        }
+        assert_eq!(
+            mainnet.next_receive_address().unwrap().to_string(),
+            ADDRESSES3[0]
+        );
+
+        for address in ADDRESSES3 {
+            let address: OldAddress = address.parse().unwrap();
+            assert!(mainnet.check_address(&address).is_some());
+            assert!(testnet.check_address(&address).is_none());
+        }
+        for address in ADDRESSES3_TESTNET {
+            let address: OldAddress = address.parse().unwrap();
+            assert!(testnet.check_address(&address).is_some());
+            assert!(mainnet.check_address(&address).is_none());
+        }
+
+        let imported = wallet::bip44::Wallet::<OldAddress>::import(&testnet.export()).unwrap();
+        assert_eq!(imported.protocol_magic(), Some(PROTOCOL_MAGIC3));
+    }
+
+    #[test]
+    fn recover_with_password() {
+        let builder = RecoveryBuilder::new()
+            .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS3)
+            .unwrap();
+        assert_eq!(
+            builder.build_wallet().unwrap().account_id().to_string(),
+            ACCOUNT3
+        );
+
+        let builder = builder.password(Password::from(PASSWORD3.to_owned()));
+        assert_eq!(
+            builder.build_wallet().unwrap().account_id().to_string(),
+            ACCOUNT3_PASSWORD
+        );
+        assert!(matches!(
+            builder.build_daedalus(),
+            Err(RecoveryError::SchemeDoesNotRequirePassword)
+        ));
+
+        let mut yoroi = builder.build_yoroi().unwrap();
+        let issued = [
+            yoroi.next_receive_address().unwrap(),
+            yoroi.next_receive_address().unwrap(),
+            yoroi.next_change_address().unwrap(),
+            yoroi.next_change_address().unwrap(),
+        ];
+        for (issued, expected) in issued.iter().zip(ADDRESSES3_PASSWORD) {
+            assert_eq!(issued.to_string(), *expected);
+        }
+        for address in ADDRESSES3 {
+            let address: OldAddress = address.parse().unwrap();
+            assert!(yoroi.check_address(&address).is_none());
+        }
+    }
+
+    #[test]
+    fn recover_daedalus_testnet() {
+        use rand_chacha::{rand_core::SeedableRng as _, ChaChaRng};
+
+        let mut mainnet = RecoveryBuilder::new()
+            .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS1)
+            .unwrap()
+            .build_daedalus()
+            .unwrap();
+        let mut testnet = RecoveryBuilder::new()
+            .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS1)
+            .unwrap()
+            .protocol_magic(PROTOCOL_MAGIC3)
+            .build_daedalus()
+            .unwrap();
+
+        // same derivation path, only the network differs
+        let address = testnet.new_address(ChaChaRng::from_seed([0; 32]));
+        let mainnet_address = mainnet.new_address(ChaChaRng::from_seed([0; 32]));
+        assert_ne!(address, mainnet_address);
+
+        // the derivation path is in the attributes, the addresses are
+        // recognised on every network
+        assert!(testnet.check_address(&address));
+        assert!(testnet.check_address(&mainnet_address));
+        assert!(mainnet.check_address(&address));
+
+        let imported = wallet::rindex::Wallet::import(&testnet.export()).unwrap();
+        assert_eq!(imported.protocol_magic(), Some(PROTOCOL_MAGIC3));
+    }
+
     #[test]
     #[ignore]
     fn recover_yoroi_paperwallet() {
@@ -1,30 +1,76 @@
 use crate::{
-    scheme::{on_tx_input, on_tx_output},
+    history::{History, HistoryEntry},
+    persistence::{
+        read_states, read_utxo_store, write_states, write_utxo_store, Kind, PersistenceError,
+        Reader, Writer,
+    },
+    scheme::{on_tx_input, on_tx_output, remove_pending_utxo_transaction},
     states::{States, Status},
     store::UtxoStore,
 };
+use chain_addr::{Address, Discrimination};
 use chain_crypto::{Ed25519, PublicKey};
 use chain_impl_mockchain::{
+    block::BlockDate,
     fragment::{Fragment, FragmentId},
     legacy::OldAddress,
-    transaction::{InputEnum, UtxoPointer},
+    transaction::{Input, InputEnum, UtxoPointer},
     value::Value,
 };
 use chain_path_derivation::{
     bip44::{self, Bip44},
-    DerivationPath, HardDerivation, SoftDerivation, SoftDerivationRange,
+    Derivation, DerivationPath, HardDerivation, SoftDerivation, SoftDerivationRange,
 };
-use ed25519_bip32::{XPrv, XPub};
+use ed25519_bip32::{DerivationScheme, XPrv, XPub};
 use hdkeygen::Key;
-use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+use std::{
+    borrow::Borrow,
+    collections::{BTreeSet, HashMap},
+    hash::Hash,
+};
+use thiserror::Error;
+
+mod discovery;
+mod watch_only;
+
+pub use self::{
+    discovery::{AccountDiscovery, ChainDiscovery, DiscoveryReport},
+    watch_only::WatchOnlyWallet,
+};
+
+/// the number of consecutive unused addresses scanned on each chain of the
+/// accounts before stopping the discovery, as in Yoroi
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// the largest gap limit accepted, every address within the gap limit of
+/// the last used one is derived and kept in memory
+pub const MAX_GAP_LIMIT: u32 = 1_000;
+
+/// number of unused accounts to discover after the last used one
+const DEFAULT_ACCOUNT_GAP_LIMIT: u32 = 1;
+
+/// the chain of the addresses to receive funds
+const EXTERNAL: SoftDerivation = DerivationPath::<Bip44<bip44::Account>>::EXTERNAL;
+/// the chain of the change addresses
+const INTERNAL: SoftDerivation = DerivationPath::<Bip44<bip44::Account>>::INTERNAL;
 
-const DEFAULT_GAG_LIMIT: u32 = 20;
+#[derive(Debug, Error)]
+pub enum AddressError {
+    #[error("{0} addresses have been issued without being used, the gap limit is reached")]
+    GapLimitReached(u32),
+
+    #[error("there is no account to issue the address from")]
+    NoAccount,
+}
 
 pub struct Wallet<A: 'static> {
     coin_type_key: Key<XPrv, Bip44<bip44::CoinType>>,
     state: States<FragmentId, UtxoStore<Key<XPrv, Bip44<bip44::Address>>>>,
+    history: History,
     soft_derivation_range_length: u32,
-    mk_key: &'static dyn Fn(&XPub) -> A,
+    account_gap_limit: u32,
+    protocol_magic: Option<u32>,
+    mk_key: &'static dyn Fn(&XPub, Option<u32>) -> A,
     accounts: Vec<Account<A>>,
 }
 
@@ -33,6 +79,20 @@ pub struct Account<A> {
     next_index: SoftDerivation,
     soft_derivation_range_length: u32,
     addresses: HashMap<A, DerivationPath<Bip44<bip44::Address>>>,
+    external: Chain,
+    internal: Chain,
+}
+
+/// the addresses issued and used on the external (receive) or the internal
+/// (change) chain of an account
+#[derive(Debug, Clone, Default)]
+struct Chain {
+    /// the index of the next address to issue
+    next_issued: SoftDerivation,
+    /// the index following the last address seen on the blockchain
+    first_unused: SoftDerivation,
+    /// the indices of the addresses seen on the blockchain
+    used: BTreeSet<SoftDerivation>,
 }
 
 impl<A> Account<A> {
@@ -71,6 +131,8 @@ where
             next_index: SoftDerivation::min_value(),
             soft_derivation_range_length: range,
             addresses: HashMap::new(),
+            external: Chain::default(),
+            internal: Chain::default(),
         };
 
         ra.extend_range_with(f);
@@ -100,6 +162,173 @@ where
             self.addresses.insert(a, address.path().clone());
         }
     }
+
+    /// read the next address index and the chains of an account, written
+    /// by `write_with`, and derive its addresses again up to that index
+    ///
+    /// The chains are checked before deriving anything: an address is only
+    /// used once it has been derived, so every used address is within two
+    /// ranges of the used address before it and the next index is within
+    /// two ranges of the last used address.
+    fn read_with<F>(
+        account: Key<XPub, Bip44<bip44::Account>>,
+        range: u32,
+        reader: &mut Reader,
+        f: F,
+    ) -> Result<Self, PersistenceError>
+    where
+        F: Fn(&XPub) -> A,
+    {
+        let next_index = read_soft_derivation(reader)?;
+        let external = Chain::read(reader, range)?;
+        let internal = Chain::read(reader, range)?;
+
+        let mut limit = u64::from(range);
+        for index in external.used.union(&internal.used) {
+            let index = u64::from(**index);
+            if index >= limit {
+                return Err(PersistenceError::Invalid("used addresses"));
+            }
+            limit = limit.max(index + 2 * u64::from(range));
+        }
+        if u64::from(*next_index) > limit {
+            return Err(PersistenceError::Invalid("address derivation"));
+        }
+
+        let mut ra = Self {
+            account,
+            next_index: SoftDerivation::min_value(),
+            soft_derivation_range_length: range,
+            addresses: HashMap::new(),
+            external,
+            internal,
+        };
+
+        ra.extend_range_with(&f);
+        while ra.next_index < next_index {
+            ra.extend_range_with(&f);
+        }
+
+        Ok(ra)
+    }
+}
+
+impl Chain {
+    /// read a chain written by `Account::write_with`
+    fn read(reader: &mut Reader, range: u32) -> Result<Self, PersistenceError> {
+        let next_issued = read_soft_derivation(reader)?;
+        let first_unused = read_soft_derivation(reader)?;
+
+        let mut used = BTreeSet::new();
+        for _ in 0..reader.count()? {
+            let index = read_soft_derivation(reader)?;
+            if index >= first_unused {
+                return Err(PersistenceError::Invalid("used addresses"));
+            }
+            used.insert(index);
+        }
+
+        // `first_unused` follows the last used address and at most `range`
+        // addresses are issued after it
+        let last_used = used.iter().next_back().map(|index| index.saturating_add(1));
+        if last_used.unwrap_or_else(SoftDerivation::min_value) != first_unused {
+            return Err(PersistenceError::Invalid("used addresses"));
+        }
+        if u64::from(*next_issued) > u64::from(*first_unused) + u64::from(range) {
+            return Err(PersistenceError::Invalid("issued addresses"));
+        }
+
+        Ok(Self {
+            next_issued,
+            first_unused,
+            used,
+        })
+    }
+}
+
+impl<A> Account<A> {
+    /// an account is used once one of its addresses has been seen on the
+    /// blockchain
+    fn is_used(&self) -> bool {
+        self.external.first_unused > SoftDerivation::min_value()
+            || self.internal.first_unused > SoftDerivation::min_value()
+    }
+
+    fn chain_mut(&mut self, change: SoftDerivation) -> Option<&mut Chain> {
+        if change == EXTERNAL {
+            Some(&mut self.external)
+        } else if change == INTERNAL {
+            Some(&mut self.internal)
+        } else {
+            None
+        }
+    }
+
+    /// record that the address of the given path has been seen on the
+    /// blockchain
+    fn mark_used(&mut self, path: &DerivationPath<Bip44<bip44::Address>>) {
+        if let Some(chain) = self.chain_mut(path.change()) {
+            let next = path.address().saturating_add(1);
+            chain.first_unused = chain.first_unused.max(next);
+            chain.used.insert(path.address());
+        }
+    }
+
+    /// issue the next address of the given chain
+    ///
+    /// The addresses already seen on the blockchain are not issued again and
+    /// at most `soft_derivation_range_length` addresses are issued after the
+    /// last used one, so they are all found again when recovering the
+    /// wallet.
+    fn next_address(
+        &mut self,
+        change: SoftDerivation,
+    ) -> Result<Key<XPub, Bip44<bip44::Address>>, AddressError> {
+        let gap = self.soft_derivation_range_length;
+        let chain = self
+            .chain_mut(change)
+            .expect("only the external and internal chains have addresses");
+
+        let index = chain.next_issued.max(chain.first_unused);
+        if *index - *chain.first_unused >= gap {
+            return Err(AddressError::GapLimitReached(gap));
+        }
+        chain.next_issued = index.saturating_add(1);
+
+        Ok(self.account.change(change).address(index))
+    }
+
+    /// write the next address index and the chains of the account, see
+    /// `read_with`
+    fn write_with(&self, writer: &mut Writer) {
+        writer.u32(*self.next_index);
+        for chain in [&self.external, &self.internal].iter() {
+            writer.u32(*chain.next_issued);
+            writer.u32(*chain.first_unused);
+            writer.count(chain.used.len());
+            for index in chain.used.iter() {
+                writer.u32(**index);
+            }
+        }
+    }
+
+    /// the addresses of the account seen on the blockchain so far
+    fn discovery(&self, gap_limit: u32) -> AccountDiscovery {
+        let path = self.account.path();
+        AccountDiscovery::new(
+            self.id(),
+            ChainDiscovery::new(
+                path.external(),
+                self.external.used.iter().copied().collect(),
+                gap_limit,
+            ),
+            ChainDiscovery::new(
+                path.internal(),
+                self.internal.used.iter().copied().collect(),
+                gap_limit,
+            ),
+        )
+    }
 }
 
 impl<A> Wallet<A> {
@@ -110,7 +339,18 @@ impl<A> Wallet<A> {
     /// as immutable
     ///
     pub fn confirm(&mut self, fragment_id: &FragmentId) {
-        self.state.confirm(fragment_id)
+        self.state.confirm(fragment_id);
+        self.history.confirm(fragment_id);
+    }
+
+    /// get the history of the fragments that touched the wallet
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// set the date of the block the given fragment is in
+    pub fn set_block_date(&mut self, fragment_id: &FragmentId, block_date: BlockDate) {
+        self.history.set_block_date(fragment_id, block_date)
     }
 
     /// get the confirmed value of the wallet
@@ -150,10 +390,70 @@ impl<A> Wallet<A> {
         })
     }
 
+    /// remove a pending transaction, for example because it was rejected
+    /// by the node, and recompute the more recent pending states
+    ///
+    /// returns the inputs that were used by this transaction, the associated
+    /// UTxOs are available again. `None` if the transaction is not pending.
+    ///
+    pub fn remove_pending_transaction(&mut self, fragment_id: &FragmentId) -> Option<Vec<Input>> {
+        let inputs = remove_pending_utxo_transaction(&mut self.state, fragment_id)?;
+        self.history.reject(fragment_id);
+        Some(inputs)
+    }
+
     /// get the utxos of this given wallet
     pub fn utxos(&self) -> &UtxoStore<Key<XPrv, Bip44<bip44::Address>>> {
         self.state.last_state().1
     }
+
+    /// the identifiers of the discovered accounts, the last ones are the
+    /// unused accounts kept to discover the funds sent to them
+    pub fn accounts(&self) -> impl Iterator<Item = HardDerivation> + '_ {
+        self.accounts.iter().map(|account| account.id())
+    }
+
+    /// get the utxos of the given account only, to use the account as the
+    /// funding source of a transaction (see `select_inputs`)
+    pub fn account_utxos(
+        &self,
+        account: HardDerivation,
+    ) -> UtxoStore<Key<XPrv, Bip44<bip44::Address>>> {
+        self.utxos().filter(|key| key.path().account() == account)
+    }
+
+    /// get the value of the given account, including the pending
+    /// transactions
+    pub fn account_value(&self, account: HardDerivation) -> Value {
+        self.account_utxos(account).total_value()
+    }
+
+    /// the number of consecutive unused addresses scanned on each chain of
+    /// the accounts
+    pub fn gap_limit(&self) -> u32 {
+        self.soft_derivation_range_length
+    }
+
+    /// report the addresses of the discovered accounts seen on the
+    /// blockchain so far
+    pub fn discovery_report(&self) -> DiscoveryReport {
+        let gap_limit = self.soft_derivation_range_length;
+        DiscoveryReport::new(
+            gap_limit,
+            self.accounts
+                .iter()
+                .map(|account| account.discovery(gap_limit))
+                .collect(),
+        )
+    }
+
+    /// the public keys of the discovered accounts, to watch the wallet
+    /// without its private keys (see `WatchOnlyWallet`)
+    pub fn account_public_keys(
+        &self,
+    ) -> impl Iterator<Item = &Key<XPub, Bip44<bip44::Account>>> + '_ {
+        self.accounts.iter().map(|account| &account.account)
+    }
 }
 
 impl<A> Wallet<A>
@@ -164,10 +464,11 @@ where
         let account_id = HardDerivation::min_value();
         let account = self.coin_type_key.account(account_id);
 
+        let (mk_key, protocol_magic) = (self.mk_key, self.protocol_magic);
         let account = Account::<A>::new_with(
             account.public(),
             self.soft_derivation_range_length,
-            self.mk_key,
+            |xpub| mk_key(xpub, protocol_magic),
         );
         self.accounts.push(account);
     }
@@ -181,10 +482,11 @@ where
 
         if let Some(id) = last_id.checked_add(1) {
             let account = self.coin_type_key.account(id);
+            let (mk_key, protocol_magic) = (self.mk_key, self.protocol_magic);
             let account = Account::<A>::new_with(
                 account.public(),
                 self.soft_derivation_range_length,
-                self.mk_key,
+                |xpub| mk_key(xpub, protocol_magic),
             );
             self.accounts.push(account);
         } else {
@@ -192,34 +494,149 @@ where
         }
     }
 
+    /// discover new accounts until there are `account_gap_limit` unused
+    /// accounts after the last used one
+    fn discover_accounts(&mut self) {
+        let unused = self
+            .accounts
+            .iter()
+            .rev()
+            .take_while(|account| !account.is_used())
+            .count() as u32;
+
+        for _ in unused..self.account_gap_limit {
+            self.populate_new_account();
+        }
+    }
+
+    fn export_as(&self, kind: Kind) -> Vec<u8> {
+        let mut writer = Writer::new(kind);
+
+        writer.path(self.coin_type_key.path());
+        writer.xprv(self.coin_type_key.as_ref());
+        writer.u32(self.soft_derivation_range_length);
+        writer.u32(self.account_gap_limit);
+        writer.protocol_magic(self.protocol_magic);
+
+        writer.count(self.accounts.len());
+        for account in self.accounts.iter() {
+            account.write_with(&mut writer);
+        }
+
+        write_address_states(&mut writer, &self.state);
+        self.history.write(&mut writer);
+
+        writer.finalize()
+    }
+
+    fn import_as(
+        bytes: &[u8],
+        kind: Kind,
+        mk_key: &'static dyn Fn(&XPub, Option<u32>) -> A,
+    ) -> Result<Self, PersistenceError> {
+        let mut reader = Reader::new(bytes, kind)?;
+
+        let path = reader.path(2)?;
+        let coin_type_key = Key::new_unchecked(reader.xprv()?, path, DerivationScheme::V2);
+
+        let soft_derivation_range_length = reader.u32()?;
+        if soft_derivation_range_length == 0 || soft_derivation_range_length > MAX_GAP_LIMIT {
+            return Err(PersistenceError::Invalid("address range"));
+        }
+        let account_gap_limit = reader.u32()?;
+        if account_gap_limit > MAX_GAP_LIMIT {
+            return Err(PersistenceError::Invalid("account gap limit"));
+        }
+        let protocol_magic = reader.protocol_magic()?;
+
+        let mut wallet = Self {
+            coin_type_key,
+            state: States::new(FragmentId::zero_hash(), UtxoStore::new()),
+            history: History::new(),
+            soft_derivation_range_length,
+            account_gap_limit,
+            protocol_magic,
+            mk_key,
+            accounts: Vec::with_capacity(2),
+        };
+
+        // the accounts and their addresses are derived again up to the
+        // same range of addresses that have already been discovered
+        let accounts = reader.count()?;
+        if accounts == 0 {
+            return Err(PersistenceError::Invalid("accounts"));
+        }
+        let mut id = HardDerivation::min_value();
+        for index in 0..accounts {
+            if index > 0 {
+                id = id
+                    .checked_add(1)
+                    .ok_or(PersistenceError::Invalid("accounts"))?;
+            }
+
+            let account = Account::read_with(
+                wallet.coin_type_key.account(id).public(),
+                soft_derivation_range_length,
+                &mut reader,
+                |xpub| mk_key(xpub, protocol_magic),
+            )?;
+            wallet.accounts.push(account);
+        }
+
+        let coin_type_key = &wallet.coin_type_key;
+        let state = read_address_states(&mut reader, |account, change, address| {
+            Ok(coin_type_key
+                .account(account)
+                .change(change)
+                .address(address))
+        })?;
+        let history = History::read(&mut reader)?;
+
+        reader.finalize()?;
+
+        wallet.state = state;
+        wallet.history = history;
+        Ok(wallet)
+    }
+
     pub(crate) fn check_address(
         &mut self,
         address: &A,
     ) -> Option<DerivationPath<Bip44<bip44::Address>>> {
-        let mut accounts = self.accounts.iter_mut();
+        let (mk_key, protocol_magic) = (self.mk_key, self.protocol_magic);
         let mut result = None;
 
-        while let Some(account) = accounts.next() {
+        for account in self.accounts.iter_mut() {
             if let Some(path) = account.lookup(address).cloned() {
                 if account.within_last_range(&path) {
-                    account.extend_range_with(self.mk_key);
+                    account.extend_range_with(|xpub| mk_key(xpub, protocol_magic));
                 }
+                account.mark_used(&path);
 
                 result = Some(path);
                 break;
             }
         }
 
-        // this is true if we found an address in the last account
-        //
-        // so we always have 1 account with UTxO ahead
-        if result.is_some() && accounts.next().is_none() {
-            self.populate_new_account();
+        // keep unused accounts ahead of the used ones, so the funds sent to
+        // the next accounts are discovered
+        if result.is_some() {
+            self.discover_accounts();
         }
 
         result
     }
 
+    fn next_address_key(
+        &mut self,
+        change: SoftDerivation,
+    ) -> Result<Key<XPub, Bip44<bip44::Address>>, AddressError> {
+        self.accounts
+            .first_mut()
+            .ok_or(AddressError::NoAccount)?
+            .next_address(change)
+    }
+
     fn check(&mut self, address: &A) -> Option<Key<XPrv, Bip44<bip44::Address>>> {
         let path = self.check_address(address)?;
 
@@ -229,171 +646,409 @@ where
 
         Some(key)
     }
+
+    /// check the fragment with the `outputs` that may belong to the wallet:
+    /// their index in the fragment, their value and their address
+    fn check_fragment_with(
+        &mut self,
+        fragment_id: &FragmentId,
+        fragment: &Fragment,
+        outputs: Vec<(usize, Value, A)>,
+    ) -> bool {
+        if self.state.contains(fragment_id) {
+            return true;
+        }
+
+        let received = outputs
+            .into_iter()
+            .filter_map(|(index, value, address)| Some((index, value, self.check(&address)?)))
+            .collect();
+
+        apply_fragment(
+            &mut self.state,
+            &mut self.history,
+            fragment_id,
+            fragment,
+            received,
+        )
+    }
 }
 
 impl Wallet<PublicKey<Ed25519>> {
     pub fn from_root_key(coin_type_key: Key<XPrv, Bip44<bip44::CoinType>>) -> Self {
+        Self::from_root_key_with_gap_limit(coin_type_key, DEFAULT_GAP_LIMIT)
+    }
+
+    /// same as `from_root_key` but scanning `gap_limit` consecutive unused
+    /// addresses on each chain of the accounts instead of
+    /// `DEFAULT_GAP_LIMIT`
+    ///
+    /// # Panics
+    ///
+    /// if the `gap_limit` is `0` or greater than `MAX_GAP_LIMIT`
+    pub fn from_root_key_with_gap_limit(
+        coin_type_key: Key<XPrv, Bip44<bip44::CoinType>>,
+        gap_limit: u32,
+    ) -> Self {
+        assert!(gap_limit > 0, "the gap limit cannot be 0");
+        assert!(
+            gap_limit <= MAX_GAP_LIMIT,
+            "the gap limit cannot be greater than {}",
+            MAX_GAP_LIMIT
+        );
+
         let mut wallet = Self {
             coin_type_key,
             state: States::new(FragmentId::zero_hash(), UtxoStore::new()),
-            soft_derivation_range_length: DEFAULT_GAG_LIMIT,
+            history: History::new(),
+            soft_derivation_range_length: gap_limit,
+            account_gap_limit: DEFAULT_ACCOUNT_GAP_LIMIT,
+            protocol_magic: None,
             mk_key: &mk_public_key,
             accounts: Vec::with_capacity(2),
         };
 
         wallet.populate_first_account();
+        wallet.discover_accounts();
 
         wallet
     }
 
+    /// serialize the wallet: the coin type key, the discovered accounts and
+    /// addresses and all the states (the UTxOs) with their status, so the
+    /// pending transactions are kept pending.
+    ///
+    /// Use `Wallet::import` to restore the wallet.
+    pub fn export(&self) -> Vec<u8> {
+        self.export_as(Kind::Bip44)
+    }
+
+    /// restore a wallet serialized with `Wallet::export`
+    pub fn import(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        Self::import_as(bytes, Kind::Bip44, &mk_public_key)
+    }
+
+    /// issue a new address of the external chain of the first account, to
+    /// receive funds
+    ///
+    /// This is a single address, or a group address if the stake of the
+    /// funds is delegated to the given `group` account.
+    ///
+    /// # Errors
+    ///
+    /// * `AddressError::GapLimitReached` if too many addresses have been
+    ///   issued since the last one seen on the blockchain.
+    pub fn next_receive_address(
+        &mut self,
+        discrimination: Discrimination,
+        group: Option<PublicKey<Ed25519>>,
+    ) -> Result<Address, AddressError> {
+        let key = self.next_address_key(EXTERNAL)?;
+        Ok(new_address(&key, discrimination, group))
+    }
+
+    /// same as `next_receive_address` but on the internal chain, to send
+    /// the change of a transaction to
+    pub fn next_change_address(
+        &mut self,
+        discrimination: Discrimination,
+        group: Option<PublicKey<Ed25519>>,
+    ) -> Result<Address, AddressError> {
+        let key = self.next_address_key(INTERNAL)?;
+        Ok(new_address(&key, discrimination, group))
+    }
+
     pub fn check_fragments<'a, I>(&mut self, fragments: I) -> bool
     where
         I: Iterator<Item = &'a Fragment>,
     {
-        let mut at_least_once = false;
-
-        for fragment in fragments {
-            let fragment_id = fragment.hash();
-            at_least_once |= self.check_fragment(&fragment_id, fragment);
-        }
-
-        at_least_once
+        check_fragments(fragments, |fragment_id, fragment| {
+            self.check_fragment(fragment_id, fragment)
+        })
     }
 
     pub fn check_fragment(&mut self, fragment_id: &FragmentId, fragment: &Fragment) -> bool {
-        if self.state.contains(fragment_id) {
-            return true;
-        }
-
-        let mut at_least_one_match = false;
-        let (_, legacy, _) = self.state.last_state();
-        let mut store = legacy.clone();
-
-        match fragment {
-            Fragment::Initial(_config_params) => {}
-            Fragment::UpdateProposal(_update_proposal) => {}
-            Fragment::UpdateVote(_signed_update) => {}
-            Fragment::OldUtxoDeclaration(_utxos) => {}
-            _ => {
-                on_tx_input(fragment, |input| {
-                    if let InputEnum::UtxoInput(pointer) = input.to_enum() {
-                        if let Some(spent) = store.remove(&pointer) {
-                            at_least_one_match = true;
-                            store = spent;
-                        }
-                    }
-                });
-
-                on_tx_output(fragment, |(index, output)| {
-                    use chain_addr::Kind::{Group, Single};
-                    let pk = match output.address.kind() {
-                        Single(pk) => Some(pk),
-                        Group(pk, _) => {
-                            // TODO: the account used for the group case
-                            // needs to be checked and handled
-                            Some(pk)
-                        }
-                        _ => None,
-                    };
-                    if let Some(pk) = pk {
-                        if let Some(key) = self.check(&pk) {
-                            let pointer = UtxoPointer {
-                                transaction_id: *fragment_id,
-                                output_index: index as u8,
-                                value: output.value,
-                            };
-
-                            store = store.add(pointer, key);
-                            at_least_one_match = true;
-                        }
-                    }
-                });
-            }
-        }
-
-        self.state.push(*fragment_id, store);
-        at_least_one_match
+        self.check_fragment_with(fragment_id, fragment, public_key_outputs(fragment))
     }
 }
 
 impl Wallet<OldAddress> {
     pub fn from_root_key(coin_type_key: Key<XPrv, Bip44<bip44::CoinType>>) -> Self {
+        Self::from_root_key_with_gap_limit(coin_type_key, DEFAULT_GAP_LIMIT)
+    }
+
+    /// same as `from_root_key` but scanning `gap_limit` consecutive unused
+    /// addresses on each chain of the accounts instead of
+    /// `DEFAULT_GAP_LIMIT`
+    ///
+    /// # Panics
+    ///
+    /// if the `gap_limit` is `0` or greater than `MAX_GAP_LIMIT`
+    pub fn from_root_key_with_gap_limit(
+        coin_type_key: Key<XPrv, Bip44<bip44::CoinType>>,
+        gap_limit: u32,
+    ) -> Self {
+        Self::from_root_key_with_protocol_magic(coin_type_key, gap_limit, None)
+    }
+
+    /// same as `from_root_key_with_gap_limit` but for the legacy addresses
+    /// of the Byron network with the given `protocol_magic`, `None` being
+    /// the mainnet
+    ///
+    /// The addresses of the other networks carry the protocol magic in
+    /// their attributes, the same key gives a different address on every
+    /// network.
+    ///
+    /// # Panics
+    ///
+    /// if the `gap_limit` is `0` or greater than `MAX_GAP_LIMIT`
+    pub fn from_root_key_with_protocol_magic(
+        coin_type_key: Key<XPrv, Bip44<bip44::CoinType>>,
+        gap_limit: u32,
+        protocol_magic: Option<u32>,
+    ) -> Self {
+        assert!(gap_limit > 0, "the gap limit cannot be 0");
+        assert!(
+            gap_limit <= MAX_GAP_LIMIT,
+            "the gap limit cannot be greater than {}",
+            MAX_GAP_LIMIT
+        );
+
         let mut wallet = Self {
             coin_type_key,
             state: States::new(FragmentId::zero_hash(), UtxoStore::new()),
-            soft_derivation_range_length: DEFAULT_GAG_LIMIT,
+            history: History::new(),
+            soft_derivation_range_length: gap_limit,
+            account_gap_limit: DEFAULT_ACCOUNT_GAP_LIMIT,
+            protocol_magic,
             mk_key: &mk_legacy_address,
             accounts: Vec::with_capacity(2),
         };
 
         wallet.populate_first_account();
+        wallet.discover_accounts();
 
         wallet
     }
 
+    /// the protocol magic of the Byron network of the legacy addresses,
+    /// `None` for the mainnet
+    pub fn protocol_magic(&self) -> Option<u32> {
+        self.protocol_magic
+    }
+
+    /// serialize the wallet: the coin type key, the discovered accounts and
+    /// addresses and all the states (the UTxOs) with their status, so the
+    /// pending transactions are kept pending.
+    ///
+    /// Use `Wallet::import` to restore the wallet.
+    pub fn export(&self) -> Vec<u8> {
+        self.export_as(Kind::Bip44Legacy)
+    }
+
+    /// restore a wallet serialized with `Wallet::export`
+    pub fn import(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        Self::import_as(bytes, Kind::Bip44Legacy, &mk_legacy_address)
+    }
+
+    /// issue a new legacy address of the external chain of the first
+    /// account, to receive funds
+    ///
+    /// # Errors
+    ///
+    /// * `AddressError::GapLimitReached` if too many addresses have been
+    ///   issued since the last one seen on the blockchain.
+    pub fn next_receive_address(&mut self) -> Result<OldAddress, AddressError> {
+        let key = self.next_address_key(EXTERNAL)?;
+        Ok(mk_legacy_address(key.public_key(), self.protocol_magic))
+    }
+
+    /// same as `next_receive_address` but on the internal chain, to send
+    /// the change of a transaction to
+    pub fn next_change_address(&mut self) -> Result<OldAddress, AddressError> {
+        let key = self.next_address_key(INTERNAL)?;
+        Ok(mk_legacy_address(key.public_key(), self.protocol_magic))
+    }
+
     pub fn check_fragments<'a, I>(&mut self, fragments: I) -> bool
     where
         I: Iterator<Item = &'a Fragment>,
     {
-        let mut at_least_once = false;
+        check_fragments(fragments, |fragment_id, fragment| {
+            self.check_fragment(fragment_id, fragment)
+        })
+    }
 
-        for fragment in fragments {
-            let fragment_id = fragment.hash();
-            at_least_once |= self.check_fragment(&fragment_id, fragment);
-        }
+    pub fn check_fragment(&mut self, fragment_id: &FragmentId, fragment: &Fragment) -> bool {
+        self.check_fragment_with(fragment_id, fragment, legacy_outputs(fragment))
+    }
+}
 
-        at_least_once
+fn read_soft_derivation(reader: &mut Reader) -> Result<SoftDerivation, PersistenceError> {
+    SoftDerivation::new(Derivation::new(reader.u32()?))
+        .map_err(|_| PersistenceError::Invalid("address derivation"))
+}
+
+/// write the states of the UTxOs, the keys of the UTxOs are written as the
+/// derivation path of their address
+fn write_address_states<K>(
+    writer: &mut Writer,
+    state: &States<FragmentId, UtxoStore<Key<K, Bip44<bip44::Address>>>>,
+) {
+    write_states(writer, state, |writer, previous, store| {
+        write_utxo_store(writer, previous, store, |writer, key| {
+            let path = key.path();
+            writer.u32(*path.account());
+            writer.u32(*path.change());
+            writer.u32(*path.address());
+        })
+    })
+}
+
+/// read the states written with `write_address_states`, the keys of the
+/// UTxOs are derived again from the account, change and address indices
+fn read_address_states<K, F>(
+    reader: &mut Reader,
+    mut mk_key: F,
+) -> Result<States<FragmentId, UtxoStore<Key<K, Bip44<bip44::Address>>>>, PersistenceError>
+where
+    F: FnMut(
+        HardDerivation,
+        SoftDerivation,
+        SoftDerivation,
+    ) -> Result<Key<K, Bip44<bip44::Address>>, PersistenceError>,
+{
+    read_states(reader, |reader, previous| {
+        read_utxo_store(reader, previous, |reader| {
+            let account = HardDerivation::new(Derivation::new(reader.u32()?))
+                .map_err(|_| PersistenceError::Invalid("account derivation"))?;
+            let change = read_soft_derivation(reader)?;
+            let address = read_soft_derivation(reader)?;
+
+            mk_key(account, change, address)
+        })
+    })
+}
+
+fn check_fragments<'a, I, F>(fragments: I, mut check_fragment: F) -> bool
+where
+    I: Iterator<Item = &'a Fragment>,
+    F: FnMut(&FragmentId, &Fragment) -> bool,
+{
+    let mut at_least_once = false;
+
+    for fragment in fragments {
+        let fragment_id = fragment.hash();
+        at_least_once |= check_fragment(&fragment_id, fragment);
     }
 
-    pub fn check_fragment(&mut self, fragment_id: &FragmentId, fragment: &Fragment) -> bool {
-        if self.state.contains(fragment_id) {
-            return true;
+    at_least_once
+}
+
+/// the outputs of the fragment to a single or a group address: their index
+/// in the fragment, their value and the public key of their address
+fn public_key_outputs(fragment: &Fragment) -> Vec<(usize, Value, PublicKey<Ed25519>)> {
+    let mut outputs = Vec::new();
+    on_tx_output(fragment, |(index, output)| {
+        use chain_addr::Kind::{Group, Single};
+        // TODO: the account used for the group case needs to be checked
+        // and handled
+        if let Single(pk) | Group(pk, _) = output.address.kind() {
+            outputs.push((index, output.value, pk.clone()));
         }
+    });
+    outputs
+}
 
-        let mut at_least_one_match = false;
-        let (_, legacy, _) = self.state.last_state();
-        let mut store = legacy.clone();
-
-        match fragment {
-            Fragment::Initial(_config_params) => {}
-            Fragment::UpdateProposal(_update_proposal) => {}
-            Fragment::UpdateVote(_signed_update) => {}
-            Fragment::OldUtxoDeclaration(utxos) => {
-                for (output_index, (address, value)) in utxos.addrs.iter().enumerate() {
-                    let pointer = UtxoPointer {
-                        transaction_id: *fragment_id,
-                        output_index: output_index as u8,
-                        value: *value,
-                    };
-
-                    if let Some(key) = self.check(address) {
-                        at_least_one_match = true;
-                        store = store.add(pointer, key);
-                    }
-                }
-            }
-            _ => {
-                on_tx_input(fragment, |input| {
-                    if let InputEnum::UtxoInput(pointer) = input.to_enum() {
-                        if let Some(spent) = store.remove(&pointer) {
-                            at_least_one_match = true;
-                            store = spent;
-                        }
-                    }
-                });
+/// the UTxOs declared by the fragment: their index in the fragment, their
+/// value and their legacy address
+fn legacy_outputs(fragment: &Fragment) -> Vec<(usize, Value, OldAddress)> {
+    match fragment {
+        Fragment::OldUtxoDeclaration(utxos) => utxos
+            .addrs
+            .iter()
+            .enumerate()
+            .map(|(index, (address, value))| (index, *value, address.clone()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// push the state of the UTxOs after the fragment: the UTxOs it spends are
+/// removed and the `received` outputs (their index in the fragment, their
+/// value and the key of their address) are added
+///
+/// The fragment is recorded in the history if it spends or receives any
+/// UTxO, returns `true` in that case.
+fn apply_fragment<K>(
+    state: &mut States<FragmentId, UtxoStore<Key<K, Bip44<bip44::Address>>>>,
+    history: &mut History,
+    fragment_id: &FragmentId,
+    fragment: &Fragment,
+    received: Vec<(usize, Value, Key<K, Bip44<bip44::Address>>)>,
+) -> bool {
+    let mut at_least_one_match = false;
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    let (_, last, _) = state.last_state();
+    let mut store = last.clone();
+
+    on_tx_input(fragment, |input| {
+        if let InputEnum::UtxoInput(pointer) = input.to_enum() {
+            if let Some(spent) = store.remove(&pointer) {
+                at_least_one_match = true;
+                inputs.push(Input::from_utxo(pointer));
+                store = spent;
             }
         }
+    });
+
+    for (index, value, key) in received {
+        let pointer = UtxoPointer {
+            transaction_id: *fragment_id,
+            output_index: index as u8,
+            value,
+        };
+
+        outputs.push(pointer);
+        store = store.add(pointer, key);
+        at_least_one_match = true;
+    }
+
+    state.push(*fragment_id, store);
+
+    if at_least_one_match {
+        history.record(HistoryEntry::new(
+            *fragment_id,
+            Some(fragment),
+            inputs,
+            outputs,
+        ));
+    }
+
+    at_least_one_match
+}
 
-        self.state.push(*fragment_id, store);
-        at_least_one_match
+/// a single address, or a group address delegating to the `group` account
+fn new_address(
+    key: &Key<XPub, Bip44<bip44::Address>>,
+    discrimination: Discrimination,
+    group: Option<PublicKey<Ed25519>>,
+) -> Address {
+    match group {
+        None => key.address_single(discrimination),
+        Some(group) => key.address_group(discrimination, group),
     }
 }
 
-fn mk_legacy_address(xpub: &XPub) -> OldAddress {
-    cardano_legacy_address::ExtendedAddr::new_simple(&xpub, None).to_address()
+/// the legacy address of the given key on the Byron network of the given
+/// `protocol_magic` (`None` for the mainnet)
+fn mk_legacy_address(xpub: &XPub, protocol_magic: Option<u32>) -> OldAddress {
+    cardano_legacy_address::ExtendedAddr::new_simple(&xpub, protocol_magic).to_address()
 }
 
-fn mk_public_key(xpub: &XPub) -> PublicKey<Ed25519> {
+/// the protocol magic is only part of the legacy addresses
+fn mk_public_key(xpub: &XPub, _protocol_magic: Option<u32>) -> PublicKey<Ed25519> {
     if let Ok(pk) = PublicKey::from_binary(xpub.public_key_slice()) {
         pk
     } else {
@@ -0,0 +1,158 @@
+//! report of the addresses discovered by the bip44 wallets
+//!
+//! The addresses of an account are scanned up to a gap limit: the scan of a
+//! chain stops once `gap_limit` consecutive addresses have not been seen on
+//! the blockchain. Funds sent to an address after a wider gap are not
+//! found, the [`DiscoveryReport`] tells when the used addresses come close
+//! to the limit so the wallet can be recovered again with a larger one.
+
+use chain_path_derivation::{
+    bip44::{self, Bip44},
+    DerivationPath, HardDerivation, SoftDerivation,
+};
+
+/// the addresses of the accounts seen on the blockchain
+#[derive(Debug, Clone)]
+pub struct DiscoveryReport {
+    gap_limit: u32,
+    accounts: Vec<AccountDiscovery>,
+}
+
+/// the addresses of one account seen on the blockchain
+#[derive(Debug, Clone)]
+pub struct AccountDiscovery {
+    account: HardDerivation,
+    external: ChainDiscovery,
+    internal: ChainDiscovery,
+}
+
+/// the addresses of the external (receive) or internal (change) chain of
+/// an account seen on the blockchain
+#[derive(Debug, Clone)]
+pub struct ChainDiscovery {
+    chain: DerivationPath<Bip44<bip44::Change>>,
+    used: Vec<SoftDerivation>,
+    gap_limit_reached: bool,
+}
+
+impl DiscoveryReport {
+    pub(super) fn new(gap_limit: u32, accounts: Vec<AccountDiscovery>) -> Self {
+        Self {
+            gap_limit,
+            accounts,
+        }
+    }
+
+    /// the number of consecutive unused addresses scanned before stopping
+    pub fn gap_limit(&self) -> u32 {
+        self.gap_limit
+    }
+
+    /// the discovered accounts, including the unused accounts kept ahead of
+    /// the used ones
+    pub fn accounts(&self) -> &[AccountDiscovery] {
+        &self.accounts
+    }
+
+    /// all the derivation paths of the addresses seen on the blockchain
+    pub fn used_paths(&self) -> impl Iterator<Item = DerivationPath<Bip44<bip44::Address>>> + '_ {
+        self.accounts
+            .iter()
+            .flat_map(|account| account.used_paths())
+    }
+
+    /// `true` if the discovery of one of the chains hit the gap limit, see
+    /// `ChainDiscovery::gap_limit_reached`
+    pub fn gap_limit_reached(&self) -> bool {
+        self.accounts
+            .iter()
+            .any(|account| account.external.gap_limit_reached || account.internal.gap_limit_reached)
+    }
+}
+
+impl AccountDiscovery {
+    pub(super) fn new(
+        account: HardDerivation,
+        external: ChainDiscovery,
+        internal: ChainDiscovery,
+    ) -> Self {
+        Self {
+            account,
+            external,
+            internal,
+        }
+    }
+
+    pub fn account(&self) -> HardDerivation {
+        self.account
+    }
+
+    /// the addresses to receive funds
+    pub fn external(&self) -> &ChainDiscovery {
+        &self.external
+    }
+
+    /// the change addresses
+    pub fn internal(&self) -> &ChainDiscovery {
+        &self.internal
+    }
+
+    /// the derivation paths of the addresses of the account seen on the
+    /// blockchain, the external chain first
+    pub fn used_paths(&self) -> impl Iterator<Item = DerivationPath<Bip44<bip44::Address>>> + '_ {
+        self.external.used_paths().chain(self.internal.used_paths())
+    }
+
+    /// `true` if at least one address of the account has been seen on the
+    /// blockchain
+    pub fn is_used(&self) -> bool {
+        !self.external.used.is_empty() || !self.internal.used.is_empty()
+    }
+}
+
+impl ChainDiscovery {
+    /// `used` are the indices of the addresses seen on the blockchain, in
+    /// increasing order
+    pub(super) fn new(
+        chain: DerivationPath<Bip44<bip44::Change>>,
+        used: Vec<SoftDerivation>,
+        gap_limit: u32,
+    ) -> Self {
+        let mut previous = None;
+        let mut largest_gap = 0;
+        for index in used.iter() {
+            let gap = match previous {
+                None => **index,
+                Some(previous) => **index - previous - 1,
+            };
+            largest_gap = largest_gap.max(gap);
+            previous = Some(**index);
+        }
+
+        Self {
+            gap_limit_reached: !used.is_empty() && largest_gap.saturating_add(1) >= gap_limit,
+            chain,
+            used,
+        }
+    }
+
+    /// the derivation paths of the addresses seen on the blockchain
+    pub fn used_paths(&self) -> impl Iterator<Item = DerivationPath<Bip44<bip44::Address>>> + '_ {
+        self.used
+            .iter()
+            .map(move |index| self.chain.address(*index))
+    }
+
+    /// the highest index of the addresses seen on the blockchain, `None`
+    /// if none of the addresses of the chain has been used
+    pub fn highest_used(&self) -> Option<SoftDerivation> {
+        self.used.last().copied()
+    }
+
+    /// `true` if an address has been found after `gap_limit - 1` unused
+    /// addresses: the discovery went as far as the gap limit allows and the
+    /// addresses after a wider gap would have been missed.
+    pub fn gap_limit_reached(&self) -> bool {
+        self.gap_limit_reached
+    }
+}
@@ -0,0 +1,596 @@
+//! watch-only bip44 wallets
+//!
+//! A [`WatchOnlyWallet`] is built from the extended public keys of one or
+//! more accounts. It discovers the addresses of these accounts, tracks
+//! their UTxOs and builds [`UnsignedTransaction`]s without ever holding a
+//! private key. The inputs of the transactions are identified by their
+//! derivation path from the root key, so they can be signed by the wallet
+//! holding the keys (see [`SoftwareSigner`](crate::transaction::SoftwareSigner)).
+
+use super::{
+    apply_fragment, check_fragments, legacy_outputs, mk_legacy_address, mk_public_key, new_address,
+    public_key_outputs, read_address_states, write_address_states, Account, AddressError,
+    DiscoveryReport, DEFAULT_GAP_LIMIT, EXTERNAL, INTERNAL, MAX_GAP_LIMIT,
+};
+use crate::{
+    history::History,
+    persistence::{Kind, PersistenceError, Reader, Writer},
+    scheme::remove_pending_utxo_transaction,
+    states::{States, Status},
+    store::UtxoStore,
+    transaction::{
+        select_inputs_with_keys, KeyId, SelectionError, Strategy, TransactionBuilder,
+        UnsignedTransaction, WitnessKind,
+    },
+    Settings,
+};
+use chain_addr::{Address, Discrimination};
+use chain_crypto::{Ed25519, PublicKey};
+use chain_impl_mockchain::{
+    block::BlockDate,
+    fragment::{Fragment, FragmentId},
+    legacy::OldAddress,
+    transaction::{Input, Output, Payload},
+    value::Value,
+};
+use chain_path_derivation::{
+    bip44::{self, Bip44},
+    DerivationPath, HardDerivation, SoftDerivation,
+};
+use ed25519_bip32::{DerivationScheme, XPub};
+use hdkeygen::Key;
+use std::hash::Hash;
+
+/// a bip44 wallet without the private keys, see the module documentation
+pub struct WatchOnlyWallet<A: 'static> {
+    state: States<FragmentId, UtxoStore<Key<XPub, Bip44<bip44::Address>>>>,
+    history: History,
+    soft_derivation_range_length: u32,
+    protocol_magic: Option<u32>,
+    mk_key: &'static dyn Fn(&XPub, Option<u32>) -> A,
+    witness_kind: WitnessKind,
+    accounts: Vec<Account<A>>,
+}
+
+impl<A> WatchOnlyWallet<A> {
+    /// the identifiers of the watched accounts
+    pub fn accounts(&self) -> impl Iterator<Item = HardDerivation> + '_ {
+        self.accounts.iter().map(|account| account.id())
+    }
+
+    /// the number of consecutive unused addresses scanned on each chain of
+    /// the accounts
+    pub fn gap_limit(&self) -> u32 {
+        self.soft_derivation_range_length
+    }
+
+    /// report the addresses of the watched accounts seen on the blockchain
+    /// so far
+    pub fn discovery_report(&self) -> DiscoveryReport {
+        let gap_limit = self.soft_derivation_range_length;
+        DiscoveryReport::new(
+            gap_limit,
+            self.accounts
+                .iter()
+                .map(|account| account.discovery(gap_limit))
+                .collect(),
+        )
+    }
+
+    /// confirm a pending transaction
+    ///
+    /// to only do once it is confirmed a transaction is on chain
+    /// and is far enough in the blockchain history to be confirmed
+    /// as immutable
+    ///
+    pub fn confirm(&mut self, fragment_id: &FragmentId) {
+        self.state.confirm(fragment_id);
+        self.history.confirm(fragment_id);
+    }
+
+    /// get the history of the fragments that touched the wallet
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// set the date of the block the given fragment is in
+    pub fn set_block_date(&mut self, fragment_id: &FragmentId, block_date: BlockDate) {
+        self.history.set_block_date(fragment_id, block_date)
+    }
+
+    /// get the confirmed value of the wallet
+    pub fn confirmed_value(&self) -> Value {
+        self.state.confirmed_state().1.total_value()
+    }
+
+    /// get the unconfirmed value of the wallet
+    ///
+    /// if `None`, it means there is no unconfirmed state of the wallet
+    /// and the value can be known from `confirmed_value`.
+    pub fn unconfirmed_value(&self) -> Option<Value> {
+        let (k, s, _) = self.state.last_state();
+        let (kk, _) = self.state.confirmed_state();
+
+        if k == kk {
+            None
+        } else {
+            Some(s.total_value())
+        }
+    }
+
+    /// get all the pending transactions of the wallet
+    pub fn pending_transactions(&self) -> impl Iterator<Item = &FragmentId> {
+        self.state.iter().filter_map(|(k, _, status)| {
+            if status == Status::Pending {
+                Some(k)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// remove a pending transaction, for example because it was rejected
+    /// by the node, and recompute the more recent pending states
+    ///
+    /// returns the inputs that were used by this transaction, the associated
+    /// UTxOs are available again. `None` if the transaction is not pending.
+    ///
+    pub fn remove_pending_transaction(&mut self, fragment_id: &FragmentId) -> Option<Vec<Input>> {
+        let inputs = remove_pending_utxo_transaction(&mut self.state, fragment_id)?;
+        self.history.reject(fragment_id);
+        Some(inputs)
+    }
+
+    /// get the utxos of this given wallet, with the public key of the
+    /// address they belong to
+    pub fn utxos(&self) -> &UtxoStore<Key<XPub, Bip44<bip44::Address>>> {
+        self.state.last_state().1
+    }
+
+    /// get the utxos of the given account only
+    pub fn account_utxos(
+        &self,
+        account: HardDerivation,
+    ) -> UtxoStore<Key<XPub, Bip44<bip44::Address>>> {
+        self.utxos().filter(|key| key.path().account() == account)
+    }
+
+    /// get the value of the given account, including the pending
+    /// transactions
+    pub fn account_value(&self, account: HardDerivation) -> Value {
+        self.account_utxos(account).total_value()
+    }
+
+    /// build a transaction paying for the given `outputs`, the inputs are
+    /// selected from the UTxOs of the wallet with the given `strategies`
+    /// and the change, if any, is sent to `change_address`.
+    ///
+    /// The returned transaction needs to be signed by the wallet holding
+    /// the private keys. It is not added to the pending transactions: check
+    /// the fragment once it has been signed and sent.
+    ///
+    /// # Errors
+    ///
+    /// * `SelectionError::NotEnoughFunds` if the wallet does not have
+    ///   enough funds to pay for the outputs and the fees;
+    /// * `SelectionError::NoSolution` if none of the strategies could
+    ///   select the inputs.
+    pub fn unsigned_transaction<P: Payload>(
+        &self,
+        settings: &Settings,
+        payload: P,
+        outputs: Vec<Output<Address>>,
+        strategies: &[Strategy],
+        change_address: Address,
+    ) -> Result<UnsignedTransaction<P>, SelectionError> {
+        self.unsigned_transaction_with(
+            self.utxos(),
+            settings,
+            payload,
+            outputs,
+            strategies,
+            change_address,
+        )
+    }
+
+    /// same as `unsigned_transaction` but the inputs are only selected
+    /// from the UTxOs of the given `account`
+    pub fn unsigned_transaction_from_account<P: Payload>(
+        &self,
+        account: HardDerivation,
+        settings: &Settings,
+        payload: P,
+        outputs: Vec<Output<Address>>,
+        strategies: &[Strategy],
+        change_address: Address,
+    ) -> Result<UnsignedTransaction<P>, SelectionError> {
+        self.unsigned_transaction_with(
+            &self.account_utxos(account),
+            settings,
+            payload,
+            outputs,
+            strategies,
+            change_address,
+        )
+    }
+
+    fn unsigned_transaction_with<P: Payload>(
+        &self,
+        utxos: &UtxoStore<Key<XPub, Bip44<bip44::Address>>>,
+        settings: &Settings,
+        payload: P,
+        outputs: Vec<Output<Address>>,
+        strategies: &[Strategy],
+        change_address: Address,
+    ) -> Result<UnsignedTransaction<P>, SelectionError> {
+        let mut builder = TransactionBuilder::new(settings, payload);
+        for output in outputs {
+            builder.add_output(output);
+        }
+
+        let witness_kind = self.witness_kind;
+        select_inputs_with_keys(
+            &mut builder,
+            utxos,
+            strategies,
+            &|key| {
+                let path = key.path().clone().coerce_unchecked();
+                (KeyId::Path(path), witness_kind)
+            },
+            |_| change_address.clone(),
+        )?;
+
+        Ok(builder
+            .into_unsigned()
+            .expect("the selected inputs balance the transaction and have a key"))
+    }
+}
+
+impl<A> WatchOnlyWallet<A>
+where
+    A: Eq + Hash,
+{
+    fn new(
+        account_keys: Vec<Key<XPub, Bip44<bip44::Account>>>,
+        gap_limit: u32,
+        protocol_magic: Option<u32>,
+        mk_key: &'static dyn Fn(&XPub, Option<u32>) -> A,
+        witness_kind: WitnessKind,
+    ) -> Self {
+        assert!(gap_limit > 0, "the gap limit cannot be 0");
+        assert!(
+            gap_limit <= MAX_GAP_LIMIT,
+            "the gap limit cannot be greater than {}",
+            MAX_GAP_LIMIT
+        );
+
+        let accounts = account_keys
+            .into_iter()
+            .map(|key| Account::new_with(key, gap_limit, |xpub| mk_key(xpub, protocol_magic)))
+            .collect();
+
+        Self {
+            state: States::new(FragmentId::zero_hash(), UtxoStore::new()),
+            history: History::new(),
+            soft_derivation_range_length: gap_limit,
+            protocol_magic,
+            mk_key,
+            witness_kind,
+            accounts,
+        }
+    }
+
+    fn export_as(&self, kind: Kind) -> Vec<u8> {
+        let mut writer = Writer::new(kind);
+
+        writer.u32(self.soft_derivation_range_length);
+        writer.protocol_magic(self.protocol_magic);
+
+        writer.count(self.accounts.len());
+        for account in self.accounts.iter() {
+            writer.path(account.account.path());
+            writer.xpub(account.account.public_key());
+            account.write_with(&mut writer);
+        }
+
+        write_address_states(&mut writer, &self.state);
+        self.history.write(&mut writer);
+
+        writer.finalize()
+    }
+
+    fn import_as(
+        bytes: &[u8],
+        kind: Kind,
+        mk_key: &'static dyn Fn(&XPub, Option<u32>) -> A,
+        witness_kind: WitnessKind,
+    ) -> Result<Self, PersistenceError> {
+        let mut reader = Reader::new(bytes, kind)?;
+
+        let soft_derivation_range_length = reader.u32()?;
+        if soft_derivation_range_length == 0 || soft_derivation_range_length > MAX_GAP_LIMIT {
+            return Err(PersistenceError::Invalid("address range"));
+        }
+        let protocol_magic = reader.protocol_magic()?;
+        let mk_address_key = |xpub: &XPub| mk_key(xpub, protocol_magic);
+
+        // the addresses are derived again up to the same range of addresses
+        // that have already been discovered
+        let mut accounts = Vec::new();
+        for _ in 0..reader.count()? {
+            let path = reader.path(3)?;
+            let key = Key::new_unchecked(reader.xpub()?, path, DerivationScheme::V2);
+            let account = Account::read_with(
+                key,
+                soft_derivation_range_length,
+                &mut reader,
+                mk_address_key,
+            )?;
+            accounts.push(account);
+        }
+
+        let state = read_address_states(&mut reader, |id, change, address| {
+            let account = accounts
+                .iter()
+                .find(|account| account.id() == id)
+                .ok_or(PersistenceError::Invalid("account derivation"))?;
+            Ok(account.account.change(change).address(address))
+        })?;
+        let history = History::read(&mut reader)?;
+
+        reader.finalize()?;
+
+        Ok(Self {
+            state,
+            history,
+            soft_derivation_range_length,
+            protocol_magic,
+            mk_key,
+            witness_kind,
+            accounts,
+        })
+    }
+
+    fn check_address(&mut self, address: &A) -> Option<DerivationPath<Bip44<bip44::Address>>> {
+        let (mk_key, protocol_magic) = (self.mk_key, self.protocol_magic);
+        for account in self.accounts.iter_mut() {
+            if let Some(path) = account.lookup(address).cloned() {
+                if account.within_last_range(&path) {
+                    account.extend_range_with(|xpub| mk_key(xpub, protocol_magic));
+                }
+                account.mark_used(&path);
+
+                return Some(path);
+            }
+        }
+
+        None
+    }
+
+    fn next_address_key(
+        &mut self,
+        change: SoftDerivation,
+    ) -> Result<Key<XPub, Bip44<bip44::Address>>, AddressError> {
+        self.accounts
+            .first_mut()
+            .ok_or(AddressError::NoAccount)?
+            .next_address(change)
+    }
+
+    fn check(&mut self, address: &A) -> Option<Key<XPub, Bip44<bip44::Address>>> {
+        let path = self.check_address(address)?;
+
+        let account = self
+            .accounts
+            .iter()
+            .find(|account| account.id() == path.account())
+            .expect("the address was found in one of the accounts");
+
+        Some(
+            account
+                .account
+                .change(path.change())
+                .address(path.address()),
+        )
+    }
+
+    /// check the fragment with the `outputs` that may belong to the wallet:
+    /// their index in the fragment, their value and their address
+    fn check_fragment_with(
+        &mut self,
+        fragment_id: &FragmentId,
+        fragment: &Fragment,
+        outputs: Vec<(usize, Value, A)>,
+    ) -> bool {
+        if self.state.contains(fragment_id) {
+            return true;
+        }
+
+        let received = outputs
+            .into_iter()
+            .filter_map(|(index, value, address)| Some((index, value, self.check(&address)?)))
+            .collect();
+
+        apply_fragment(
+            &mut self.state,
+            &mut self.history,
+            fragment_id,
+            fragment,
+            received,
+        )
+    }
+}
+
+impl WatchOnlyWallet<PublicKey<Ed25519>> {
+    /// watch the addresses of the given accounts
+    pub fn from_account_keys<I>(account_keys: I) -> Self
+    where
+        I: IntoIterator<Item = Key<XPub, Bip44<bip44::Account>>>,
+    {
+        Self::from_account_keys_with_gap_limit(account_keys, DEFAULT_GAP_LIMIT)
+    }
+
+    /// same as `from_account_keys` but scanning `gap_limit` consecutive
+    /// unused addresses on each chain of the accounts
+    ///
+    /// # Panics
+    ///
+    /// if the `gap_limit` is `0` or greater than `MAX_GAP_LIMIT`
+    pub fn from_account_keys_with_gap_limit<I>(account_keys: I, gap_limit: u32) -> Self
+    where
+        I: IntoIterator<Item = Key<XPub, Bip44<bip44::Account>>>,
+    {
+        Self::new(
+            account_keys.into_iter().collect(),
+            gap_limit,
+            None,
+            &mk_public_key,
+            WitnessKind::Utxo,
+        )
+    }
+
+    /// serialize the wallet: the account public keys, the discovered
+    /// addresses and all the states (the UTxOs) with their status.
+    ///
+    /// Use `WatchOnlyWallet::import` to restore the wallet.
+    pub fn export(&self) -> Vec<u8> {
+        self.export_as(Kind::Bip44WatchOnly)
+    }
+
+    /// restore a wallet serialized with `WatchOnlyWallet::export`
+    pub fn import(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        Self::import_as(
+            bytes,
+            Kind::Bip44WatchOnly,
+            &mk_public_key,
+            WitnessKind::Utxo,
+        )
+    }
+
+    /// issue a new address of the external chain of the first watched
+    /// account, see `Wallet::next_receive_address`
+    pub fn next_receive_address(
+        &mut self,
+        discrimination: Discrimination,
+        group: Option<PublicKey<Ed25519>>,
+    ) -> Result<Address, AddressError> {
+        let key = self.next_address_key(EXTERNAL)?;
+        Ok(new_address(&key, discrimination, group))
+    }
+
+    /// issue a new address of the internal chain of the first watched
+    /// account, see `Wallet::next_change_address`
+    pub fn next_change_address(
+        &mut self,
+        discrimination: Discrimination,
+        group: Option<PublicKey<Ed25519>>,
+    ) -> Result<Address, AddressError> {
+        let key = self.next_address_key(INTERNAL)?;
+        Ok(new_address(&key, discrimination, group))
+    }
+
+    pub fn check_fragments<'a, I>(&mut self, fragments: I) -> bool
+    where
+        I: Iterator<Item = &'a Fragment>,
+    {
+        check_fragments(fragments, |fragment_id, fragment| {
+            self.check_fragment(fragment_id, fragment)
+        })
+    }
+
+    pub fn check_fragment(&mut self, fragment_id: &FragmentId, fragment: &Fragment) -> bool {
+        self.check_fragment_with(fragment_id, fragment, public_key_outputs(fragment))
+    }
+}
+
+impl WatchOnlyWallet<OldAddress> {
+    /// watch the legacy addresses of the given accounts
+    pub fn from_account_keys<I>(account_keys: I) -> Self
+    where
+        I: IntoIterator<Item = Key<XPub, Bip44<bip44::Account>>>,
+    {
+        Self::from_account_keys_with_gap_limit(account_keys, DEFAULT_GAP_LIMIT)
+    }
+
+    /// same as `from_account_keys` but scanning `gap_limit` consecutive
+    /// unused addresses on each chain of the accounts
+    ///
+    /// # Panics
+    ///
+    /// if the `gap_limit` is `0` or greater than `MAX_GAP_LIMIT`
+    pub fn from_account_keys_with_gap_limit<I>(account_keys: I, gap_limit: u32) -> Self
+    where
+        I: IntoIterator<Item = Key<XPub, Bip44<bip44::Account>>>,
+    {
+        Self::from_account_keys_with_protocol_magic(account_keys, gap_limit, None)
+    }
+
+    /// same as `from_account_keys_with_gap_limit` but for the legacy
+    /// addresses of the Byron network with the given `protocol_magic`,
+    /// `None` being the mainnet
+    ///
+    /// # Panics
+    ///
+    /// if the `gap_limit` is `0` or greater than `MAX_GAP_LIMIT`
+    pub fn from_account_keys_with_protocol_magic<I>(
+        account_keys: I,
+        gap_limit: u32,
+        protocol_magic: Option<u32>,
+    ) -> Self
+    where
+        I: IntoIterator<Item = Key<XPub, Bip44<bip44::Account>>>,
+    {
+        Self::new(
+            account_keys.into_iter().collect(),
+            gap_limit,
+            protocol_magic,
+            &mk_legacy_address,
+            WitnessKind::OldUtxo,
+        )
+    }
+
+    /// serialize the wallet: the account public keys, the discovered
+    /// addresses and all the states (the UTxOs) with their status.
+    ///
+    /// Use `WatchOnlyWallet::import` to restore the wallet.
+    pub fn export(&self) -> Vec<u8> {
+        self.export_as(Kind::Bip44LegacyWatchOnly)
+    }
+
+    /// restore a wallet serialized with `WatchOnlyWallet::export`
+    pub fn import(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        Self::import_as(
+            bytes,
+            Kind::Bip44LegacyWatchOnly,
+            &mk_legacy_address,
+            WitnessKind::OldUtxo,
+        )
+    }
+
+    /// issue a new legacy address of the external chain of the first
+    /// watched account, see `Wallet::next_receive_address`
+    pub fn next_receive_address(&mut self) -> Result<OldAddress, AddressError> {
+        let key = self.next_address_key(EXTERNAL)?;
+        Ok(mk_legacy_address(key.public_key(), self.protocol_magic))
+    }
+
+    /// issue a new legacy address of the internal chain of the first
+    /// watched account, see `Wallet::next_change_address`
+    pub fn next_change_address(&mut self) -> Result<OldAddress, AddressError> {
+        let key = self.next_address_key(INTERNAL)?;
+        Ok(mk_legacy_address(key.public_key(), self.protocol_magic))
+    }
+
+    pub fn check_fragments<'a, I>(&mut self, fragments: I) -> bool
+    where
+        I: Iterator<Item = &'a Fragment>,
+    {
+        check_fragments(fragments, |fragment_id, fragment| {
+            self.check_fragment(fragment_id, fragment)
+        })
+    }
+
+    pub fn check_fragment(&mut self, fragment_id: &FragmentId, fragment: &Fragment) -> bool {
+        self.check_fragment_with(fragment_id, fragment, legacy_outputs(fragment))
+    }
+}
@@ -1,26 +1,257 @@
 use crate::{
-    scheme::{on_tx_input, on_tx_output},
+    history::{History, HistoryEntry},
+    persistence::{
+        read_states, read_utxo_store, write_states, write_utxo_store, Kind, PersistenceError,
+        Reader, Writer,
+    },
+    scheme::{on_tx_input, on_tx_output, remove_pending_utxo_transaction},
     states::{States, Status},
     store::UtxoStore,
 };
+use chain_addr::Address;
 use chain_crypto::{Ed25519, Ed25519Extended, PublicKey, SecretKey};
 use chain_impl_mockchain::{
+    block::BlockDate,
     fragment::{Fragment, FragmentId},
-    transaction::{InputEnum, UtxoPointer},
+    transaction::{Input, InputEnum, UtxoPointer},
     value::Value,
 };
+use std::collections::HashMap;
+
+pub use crate::store::DelegatedKey;
 
 pub struct Wallet {
-    state: States<FragmentId, UtxoStore<SecretKey<Ed25519Extended>>>,
+    state: States<FragmentId, UtxoStore<DelegatedKey>>,
+    history: History,
     keys: Vec<SecretKey<Ed25519Extended>>,
+    /// the position of the keys in `keys`, by public key
+    index: HashMap<PublicKey<Ed25519>, usize>,
 }
 
 impl Wallet {
     pub fn from_keys(keys: Vec<SecretKey<Ed25519Extended>>) -> Self {
         Wallet {
+            index: index_keys(&keys),
             keys,
             state: States::new(FragmentId::zero_hash(), UtxoStore::new()),
+            history: History::new(),
+        }
+    }
+
+    /// check wether the key of the given public key is in the wallet
+    pub fn contains_key(&self, pk: &PublicKey<Ed25519>) -> bool {
+        self.index.contains_key(pk)
+    }
+
+    /// add a key to the wallet and rescan the given fragments for the UTxOs
+    /// of the key
+    ///
+    /// The `fragments` are the fragments already checked by the wallet, in
+    /// the order they were checked (the fragments of the block0 then the
+    /// fragments of the following blocks). The wallet does not keep them.
+    ///
+    /// returns `false` if the key is already in the wallet, the fragments
+    /// are not rescanned.
+    pub fn add_key<'a, I>(&mut self, key: SecretKey<Ed25519Extended>, fragments: I) -> bool
+    where
+        I: IntoIterator<Item = &'a Fragment>,
+    {
+        let pk = key.to_public();
+        if self.index.contains_key(&pk) {
+            return false;
+        }
+
+        self.index.insert(pk.clone(), self.keys.len());
+        self.keys.push(key.clone());
+        self.rescan(&pk, key, fragments);
+
+        true
+    }
+
+    /// remove a key from the wallet, its UTxOs are removed from all the
+    /// states of the wallet (the history is kept as it is)
+    ///
+    /// returns the removed key, `None` if the key is not in the wallet.
+    pub fn remove_key(&mut self, pk: &PublicKey<Ed25519>) -> Option<SecretKey<Ed25519Extended>> {
+        let index = self.index.remove(pk)?;
+        let key = self.keys.swap_remove(index);
+        if let Some(moved) = self.keys.get(index) {
+            self.index.insert(moved.to_public(), index);
         }
+
+        self.state = map_states(&self.state, |_, store| {
+            store.filter(|key| key.public_key() != pk)
+        });
+
+        Some(key)
+    }
+
+    /// look for the UTxOs of the newly added key in the checked fragments
+    /// and add them to the states following the fragment they are in
+    fn rescan<'a, I>(
+        &mut self,
+        pk: &PublicKey<Ed25519>,
+        key: SecretKey<Ed25519Extended>,
+        fragments: I,
+    ) where
+        I: IntoIterator<Item = &'a Fragment>,
+    {
+        // the UTxOs of the key after each fragment still in the states
+        let mut found = UtxoStore::new();
+        let mut snapshots = HashMap::new();
+        // the UTxOs of the key found before the first fragment still in the
+        // states, the confirmed fragments before it are not in the states
+        let mut before_states = None;
+
+        for fragment in fragments {
+            let fragment_id = fragment.hash();
+            if before_states.is_none() && self.state.contains(&fragment_id) {
+                before_states = Some(found.clone());
+            }
+
+            let mut inputs = Vec::new();
+            let mut outputs = Vec::new();
+
+            on_tx_input(fragment, |input| {
+                if let InputEnum::UtxoInput(pointer) = input.to_enum() {
+                    if let Some(spent) = found.remove(&pointer) {
+                        inputs.push(Input::from_utxo(pointer));
+                        found = spent;
+                    }
+                }
+            });
+
+            on_tx_output(fragment, |(index, output)| {
+                if let Some((output_pk, delegation)) = output_key(&output.address) {
+                    if output_pk == pk {
+                        let pointer = UtxoPointer {
+                            transaction_id: fragment_id,
+                            output_index: index as u8,
+                            value: output.value,
+                        };
+
+                        outputs.push(pointer);
+                        found = found.add(pointer, DelegatedKey::new(key.clone(), delegation));
+                    }
+                }
+            });
+
+            if self.state.contains(&fragment_id) {
+                snapshots.insert(fragment_id, found.clone());
+            }
+
+            if !inputs.is_empty() || !outputs.is_empty() {
+                let recorded = self.history.get(&fragment_id);
+                let confirmed = recorded.is_none()
+                    && !matches!(self.state.get(&fragment_id), Some((_, Status::Pending)));
+                if let Some(entry) = recorded {
+                    inputs = entry.inputs().iter().cloned().chain(inputs).collect();
+                    outputs = entry.outputs().iter().copied().chain(outputs).collect();
+                }
+
+                self.history.record(HistoryEntry::new(
+                    fragment_id,
+                    Some(fragment),
+                    inputs,
+                    outputs,
+                ));
+                if confirmed {
+                    self.history.confirm(&fragment_id);
+                }
+            }
+        }
+
+        // the states of the fragments that were not rescanned get the UTxOs
+        // found up to the previous state, the first state gets the UTxOs
+        // found before the fragments of the states (all the UTxOs found if
+        // none of the fragments is in the states)
+        let mut current = before_states.unwrap_or(found);
+        self.state = map_states(&self.state, |fragment_id, store| {
+            if let Some(snapshot) = snapshots.remove(fragment_id) {
+                current = snapshot;
+            }
+
+            let mut store = store.clone();
+            for group in current.groups() {
+                for utxo in group.utxos() {
+                    store = store.add(**utxo, DelegatedKey::clone(group.key()));
+                }
+            }
+            store
+        });
+    }
+
+    /// serialize the wallet: the keys and all the states (the UTxOs) with
+    /// their status, so the pending transactions are kept pending.
+    ///
+    /// Use `Wallet::import` to restore the wallet.
+    pub fn export(&self) -> Vec<u8> {
+        let mut writer = Writer::new(Kind::FreeUtxo);
+
+        writer.count(self.keys.len());
+        for key in self.keys.iter() {
+            writer.bytes(key.clone().leak_secret().as_ref());
+        }
+
+        write_states(&mut writer, &self.state, |writer, previous, store| {
+            write_utxo_store(writer, previous, store, |writer, key| {
+                let index = self
+                    .index
+                    .get(key.public_key())
+                    .expect("the UTxOs are only associated to the wallet's keys");
+                writer.count(*index);
+                match key.delegation() {
+                    None => writer.u8(0),
+                    Some(account) => {
+                        writer.u8(1);
+                        writer.bytes(account.as_ref());
+                    }
+                }
+            })
+        });
+        self.history.write(&mut writer);
+
+        writer.finalize()
+    }
+
+    /// restore a wallet serialized with `Wallet::export`
+    pub fn import(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        let mut reader = Reader::new(bytes, Kind::FreeUtxo)?;
+
+        let keys = (0..reader.count()?)
+            .map(|_| {
+                SecretKey::from_binary(reader.bytes(64)?)
+                    .map_err(|_| PersistenceError::Invalid("secret key"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let state = read_states(&mut reader, |reader, previous| {
+            read_utxo_store(reader, previous, |reader| {
+                let key = keys
+                    .get(reader.count()?)
+                    .cloned()
+                    .ok_or(PersistenceError::Invalid("utxo key"))?;
+                let delegation = match reader.u8()? {
+                    0 => None,
+                    1 => Some(
+                        PublicKey::from_binary(reader.bytes(32)?)
+                            .map_err(|_| PersistenceError::Invalid("delegation"))?,
+                    ),
+                    _ => return Err(PersistenceError::Invalid("delegation")),
+                };
+                Ok(DelegatedKey::new(key, delegation))
+            })
+        })?;
+        let history = History::read(&mut reader)?;
+
+        reader.finalize()?;
+
+        Ok(Wallet {
+            state,
+            history,
+            index: index_keys(&keys),
+            keys,
+        })
     }
 
     /// confirm a pending transaction
@@ -30,7 +261,18 @@ impl Wallet {
     /// as immutable
     ///
     pub fn confirm(&mut self, fragment_id: &FragmentId) {
-        self.state.confirm(fragment_id)
+        self.state.confirm(fragment_id);
+        self.history.confirm(fragment_id);
+    }
+
+    /// get the history of the fragments that touched the wallet
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// set the date of the block the given fragment is in
+    pub fn set_block_date(&mut self, fragment_id: &FragmentId, block_date: BlockDate) {
+        self.history.set_block_date(fragment_id, block_date)
     }
 
     /// get the confirmed value of the wallet
@@ -70,14 +312,25 @@ impl Wallet {
         })
     }
 
+    /// remove a pending transaction, for example because it was rejected
+    /// by the node, and recompute the more recent pending states
+    ///
+    /// returns the inputs that were used by this transaction, the associated
+    /// UTxOs are available again. `None` if the transaction is not pending.
+    ///
+    pub fn remove_pending_transaction(&mut self, fragment_id: &FragmentId) -> Option<Vec<Input>> {
+        let inputs = remove_pending_utxo_transaction(&mut self.state, fragment_id)?;
+        self.history.reject(fragment_id);
+        Some(inputs)
+    }
+
     /// get the utxos of this given wallet
-    pub fn utxos(&self) -> &UtxoStore<SecretKey<Ed25519Extended>> {
+    pub fn utxos(&self) -> &UtxoStore<DelegatedKey> {
         self.state.last_state().1
     }
 
     fn check(&self, pk: &PublicKey<Ed25519>) -> Option<SecretKey<Ed25519Extended>> {
-        // FIXME: O(n)?
-        self.keys.iter().cloned().find(|k| &k.to_public() == pk)
+        self.index.get(pk).map(|index| self.keys[*index].clone())
     }
 
     pub fn check_fragment(&mut self, fragment_id: &FragmentId, fragment: &Fragment) -> bool {
@@ -86,6 +339,8 @@ impl Wallet {
         }
 
         let mut at_least_one_match = false;
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
 
         let (_, store, _) = self.state.last_state();
 
@@ -101,30 +356,24 @@ impl Wallet {
                     if let InputEnum::UtxoInput(pointer) = input.to_enum() {
                         if let Some(spent) = store.remove(&pointer) {
                             at_least_one_match = true;
+                            inputs.push(Input::from_utxo(pointer));
                             store = spent;
                         }
                     }
                 });
 
                 on_tx_output(fragment, |(index, output)| {
-                    use chain_addr::Kind::{Group, Single};
-                    let pk = match output.address.kind() {
-                        Single(pk) => Some(pk),
-                        Group(pk, _) => {
-                            // TODO: the account used for the group case
-                            // needs to be checked and handled
-                            Some(pk)
-                        }
-                        _ => None,
-                    };
-                    if let Some(pk) = pk {
+                    if let Some((pk, delegation)) = output_key(&output.address) {
                         if let Some(key) = self.check(pk) {
+                            let key = DelegatedKey::new(key, delegation);
                             let pointer = UtxoPointer {
                                 transaction_id: *fragment_id,
                                 output_index: index as u8,
                                 value: output.value,
                             };
 
+                            outputs.push(pointer);
+
                             store = store.add(pointer, key);
                             at_least_one_match = true;
                         }
@@ -134,6 +383,60 @@ impl Wallet {
         }
 
         self.state.push(*fragment_id, store);
+
+        if at_least_one_match {
+            self.history.record(HistoryEntry::new(
+                *fragment_id,
+                Some(fragment),
+                inputs,
+                outputs,
+            ));
+        }
+
         at_least_one_match
     }
 }
+
+fn index_keys(keys: &[SecretKey<Ed25519Extended>]) -> HashMap<PublicKey<Ed25519>, usize> {
+    keys.iter()
+        .enumerate()
+        .map(|(index, key)| (key.to_public(), index))
+        .collect()
+}
+
+/// the public key of the single or group address with the account the
+/// stake is delegated to (if any)
+fn output_key(address: &Address) -> Option<(&PublicKey<Ed25519>, Option<PublicKey<Ed25519>>)> {
+    use chain_addr::Kind::{Group, Single};
+    match address.kind() {
+        Single(pk) => Some((pk, None)),
+        Group(pk, account) => Some((pk, Some(account.clone()))),
+        _ => None,
+    }
+}
+
+/// rebuild the states with `f` applied to every store, keeping their status
+fn map_states<F>(
+    states: &States<FragmentId, UtxoStore<DelegatedKey>>,
+    mut f: F,
+) -> States<FragmentId, UtxoStore<DelegatedKey>>
+where
+    F: FnMut(&FragmentId, &UtxoStore<DelegatedKey>) -> UtxoStore<DelegatedKey>,
+{
+    let mut iter = states.iter();
+    let (key, store, _) = iter.next().expect("there is always a confirmed state");
+    let mut new_states = States::new(*key, f(key, store));
+
+    let mut confirmed = Vec::new();
+    for (key, store, status) in iter {
+        new_states.push(*key, f(key, store));
+        if status == Status::Confirmed {
+            confirmed.push(*key);
+        }
+    }
+    for key in confirmed.iter() {
+        new_states.confirm(key);
+    }
+
+    new_states
+}
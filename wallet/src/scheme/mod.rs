@@ -2,11 +2,41 @@ pub mod bip44;
 pub mod freeutxo;
 pub mod rindex;
 
+use crate::{
+    states::States,
+    store::{Groupable, UtxoStore},
+};
 use chain_impl_mockchain::{
-    fragment::Fragment,
-    transaction::{Input, Output, Witness},
+    fragment::{Fragment, FragmentId},
+    transaction::{Input, Output, TransactionSignDataHash, Witness},
 };
 
+/// remove the pending transaction from the UTxO states, rebasing the
+/// more recent states on top of the remaining ones.
+///
+/// returns the inputs that were spent by the removed transaction, the
+/// UTxOs are back in the store.
+pub(crate) fn remove_pending_utxo_transaction<K>(
+    state: &mut States<FragmentId, UtxoStore<K>>,
+    fragment_id: &FragmentId,
+) -> Option<Vec<Input>>
+where
+    K: Groupable + Clone,
+{
+    let (_, previous) = state.previous(fragment_id)?;
+    let (removed, _) = state.get(fragment_id)?;
+    let inputs: Vec<Input> = previous
+        .difference(removed)
+        .map(|utxo| Input::from_utxo(*utxo))
+        .collect();
+
+    state.reject(fragment_id, |new_previous, previous, store| {
+        new_previous.apply_changes(previous, store)
+    })?;
+
+    Some(inputs)
+}
+
 pub(crate) fn on_tx_output<FO>(fragment: &Fragment, on_output: FO)
 where
     FO: FnMut((usize, Output<chain_addr::Address>)),
@@ -162,3 +192,24 @@ where
             .for_each(on_input),
     }
 }
+
+/// get the hash of the data signed by the witnesses of the transaction, if
+/// the fragment is a transaction
+pub(crate) fn transaction_sign_data_hash(fragment: &Fragment) -> Option<TransactionSignDataHash> {
+    match fragment {
+        Fragment::Initial(_config_params) => None,
+        Fragment::UpdateProposal(_update_proposal) => None,
+        Fragment::UpdateVote(_signed_update) => None,
+        Fragment::OldUtxoDeclaration(_utxos) => None,
+        Fragment::Transaction(tx) => Some(tx.as_slice().transaction_sign_data_hash()),
+        Fragment::OwnerStakeDelegation(tx) => Some(tx.as_slice().transaction_sign_data_hash()),
+        Fragment::StakeDelegation(tx) => Some(tx.as_slice().transaction_sign_data_hash()),
+        Fragment::PoolRegistration(tx) => Some(tx.as_slice().transaction_sign_data_hash()),
+        Fragment::PoolRetirement(tx) => Some(tx.as_slice().transaction_sign_data_hash()),
+        Fragment::PoolUpdate(tx) => Some(tx.as_slice().transaction_sign_data_hash()),
+        Fragment::VotePlan(tx) => Some(tx.as_slice().transaction_sign_data_hash()),
+        Fragment::VoteCast(tx) => Some(tx.as_slice().transaction_sign_data_hash()),
+        Fragment::VoteTally(tx) => Some(tx.as_slice().transaction_sign_data_hash()),
+        Fragment::EncryptedVoteTally(tx) => Some(tx.as_slice().transaction_sign_data_hash()),
+    }
+}
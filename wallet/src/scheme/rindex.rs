@@ -1,41 +1,162 @@
 use crate::{
-    scheme::on_tx_input,
+    history::{History, HistoryEntry},
+    persistence::{
+        read_states, read_utxo_store, write_states, write_utxo_store, Kind, PersistenceError,
+        Reader, Writer,
+    },
+    scheme::{on_tx_input, remove_pending_utxo_transaction},
     states::{States, Status},
     store::UtxoStore,
 };
-use cardano_legacy_address::{AddressMatchXPub, ExtendedAddr};
+use cardano_legacy_address::{AddressMatchXPub, Attributes, ExtendedAddr};
 use chain_impl_mockchain::{
+    block::BlockDate,
     fragment::{Fragment, FragmentId},
     legacy::OldAddress,
-    transaction::{InputEnum, UtxoPointer},
+    transaction::{Input, InputEnum, UtxoPointer},
     value::Value,
 };
 use chain_path_derivation::{
     rindex::{self, Rindex},
-    DerivationPath,
+    Derivation, DerivationPath,
 };
-use ed25519_bip32::XPrv;
+use ed25519_bip32::{DerivationScheme, XPrv};
 use hdkeygen::{
-    rindex::{decode_derivation_path, HDKey},
+    rindex::{decode_derivation_path, encode_derivation_path, HDKey},
     Key,
 };
+use std::collections::HashSet;
+
+/// the derivation indices of the Daedalus addresses are hard derivations
+const HARD_DERIVATION_START: u32 = 0x8000_0000;
 
 pub struct Wallet {
     root_key: Key<XPrv, Rindex<rindex::Root>>,
     payload_key: HDKey,
     state: States<FragmentId, UtxoStore<Key<XPrv, Rindex<rindex::Address>>>>,
+    history: History,
+    /// the derivation paths of the addresses generated by the wallet or
+    /// seen on the blockchain, not to generate the same address twice
+    known: HashSet<DerivationPath<Rindex<rindex::Address>>>,
+    /// the protocol magic of the Byron network, `None` for the mainnet
+    protocol_magic: Option<u32>,
 }
 
 impl Wallet {
     pub fn from_root_key(root_key: Key<XPrv, Rindex<rindex::Root>>) -> Self {
+        Self::from_root_key_with_protocol_magic(root_key, None)
+    }
+
+    /// same as `from_root_key` but the new addresses are generated for the
+    /// Byron network with the given `protocol_magic`, `None` being the
+    /// mainnet
+    ///
+    /// The addresses of the wallet are recognised on any network, the
+    /// derivation path is found from the encrypted attributes of the
+    /// address.
+    pub fn from_root_key_with_protocol_magic(
+        root_key: Key<XPrv, Rindex<rindex::Root>>,
+        protocol_magic: Option<u32>,
+    ) -> Self {
         let payload_key = root_key.hd_key();
         Self {
             root_key,
             payload_key,
             state: States::new(FragmentId::zero_hash(), UtxoStore::new()),
+            history: History::new(),
+            known: HashSet::new(),
+            protocol_magic,
         }
     }
 
+    /// the protocol magic of the Byron network of the new addresses, `None`
+    /// for the mainnet
+    pub fn protocol_magic(&self) -> Option<u32> {
+        self.protocol_magic
+    }
+
+    /// generate a new Daedalus address with random account and address
+    /// indices, different from the ones of the addresses already known by
+    /// the wallet
+    ///
+    /// The derivation path is encrypted in the attributes of the address,
+    /// so the wallet can find the key of the address once it receives funds.
+    pub fn new_address<G: rand::Rng + rand::CryptoRng>(&mut self, mut random: G) -> OldAddress {
+        let path = loop {
+            let account = Derivation::new(HARD_DERIVATION_START | random.next_u32());
+            let address = Derivation::new(HARD_DERIVATION_START | random.next_u32());
+            let path = rindex::new().account(account).address(address);
+
+            if !self.known.contains(&path) {
+                break path;
+            }
+        };
+
+        let xpub = self.root_key.key(&path).public();
+        let payload = self.payload_key.encrypt(&encode_derivation_path(&path));
+        let attributes = Attributes::new_bootstrap_era(Some(payload), self.protocol_magic);
+
+        self.known.insert(path);
+        ExtendedAddr::new(xpub.public_key(), attributes).to_address()
+    }
+
+    /// serialize the wallet: the root key and all the states (the UTxOs)
+    /// with their status, so the pending transactions are kept pending.
+    ///
+    /// Use `Wallet::import` to restore the wallet.
+    pub fn export(&self) -> Vec<u8> {
+        let mut writer = Writer::new(Kind::Rindex);
+
+        writer.xprv(self.root_key.as_ref());
+        writer.protocol_magic(self.protocol_magic);
+        writer.count(self.known.len());
+        for path in self.known.iter() {
+            writer.u32(*path.account());
+            writer.u32(*path.address());
+        }
+        write_states(&mut writer, &self.state, |writer, previous, store| {
+            write_utxo_store(writer, previous, store, |writer, key| {
+                writer.u32(*key.path().account());
+                writer.u32(*key.path().address());
+            })
+        });
+        self.history.write(&mut writer);
+
+        writer.finalize()
+    }
+
+    /// restore a wallet serialized with `Wallet::export`
+    pub fn import(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        let mut reader = Reader::new(bytes, Kind::Rindex)?;
+
+        let root_key = Key::new_unchecked(reader.xprv()?, rindex::new(), DerivationScheme::V1);
+        let protocol_magic = reader.protocol_magic()?;
+        let mut known = HashSet::new();
+        for _ in 0..reader.count()? {
+            let account = Derivation::new(reader.u32()?);
+            let address = Derivation::new(reader.u32()?);
+            known.insert(rindex::new().account(account).address(address));
+        }
+        let state = read_states(&mut reader, |reader, previous| {
+            read_utxo_store(reader, previous, |reader| {
+                let account = Derivation::new(reader.u32()?);
+                let address = Derivation::new(reader.u32()?);
+                let path = rindex::new().account(account).address(address);
+
+                Ok(root_key.key(&path))
+            })
+        })?;
+        let history = History::read(&mut reader)?;
+
+        reader.finalize()?;
+
+        let mut wallet = Self::from_root_key_with_protocol_magic(root_key, protocol_magic);
+        wallet.state = state;
+        wallet.history = history;
+        wallet.known = known;
+        Ok(wallet)
+    }
+
     /// get the utxos of this given wallet
     pub fn utxos(&self) -> &UtxoStore<Key<XPrv, Rindex<rindex::Address>>> {
         self.state.last_state().1
@@ -48,7 +169,18 @@ impl Wallet {
     /// as immutable
     ///
     pub fn confirm(&mut self, fragment_id: &FragmentId) {
-        self.state.confirm(fragment_id)
+        self.state.confirm(fragment_id);
+        self.history.confirm(fragment_id);
+    }
+
+    /// get the history of the fragments that touched the wallet
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// set the date of the block the given fragment is in
+    pub fn set_block_date(&mut self, fragment_id: &FragmentId, block_date: BlockDate) {
+        self.history.set_block_date(fragment_id, block_date)
     }
 
     /// get the confirmed value of the wallet
@@ -88,6 +220,18 @@ impl Wallet {
         })
     }
 
+    /// remove a pending transaction, for example because it was rejected
+    /// by the node, and recompute the more recent pending states
+    ///
+    /// returns the inputs that were used by this transaction, the associated
+    /// UTxOs are available again. `None` if the transaction is not pending.
+    ///
+    pub fn remove_pending_transaction(&mut self, fragment_id: &FragmentId) -> Option<Vec<Input>> {
+        let inputs = remove_pending_utxo_transaction(&mut self.state, fragment_id)?;
+        self.history.reject(fragment_id);
+        Some(inputs)
+    }
+
     pub fn check_fragments<'a, I>(&mut self, fragments: I) -> bool
     where
         I: Iterator<Item = &'a Fragment>,
@@ -108,6 +252,8 @@ impl Wallet {
         }
 
         let mut at_least_one_match = false;
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
         let (_, legacy, _) = self.state.last_state();
         let mut store = legacy.clone();
 
@@ -124,7 +270,9 @@ impl Wallet {
                     };
 
                     if let Some(key) = self.check(address) {
+                        self.known.insert(key.path().clone());
                         at_least_one_match = true;
+                        outputs.push(pointer);
                         store = store.add(pointer, key);
                     }
                 }
@@ -134,6 +282,7 @@ impl Wallet {
                     if let InputEnum::UtxoInput(pointer) = input.to_enum() {
                         if let Some(spent) = store.remove(&pointer) {
                             at_least_one_match = true;
+                            inputs.push(Input::from_utxo(pointer));
                             store = spent;
                         }
                     }
@@ -144,6 +293,16 @@ impl Wallet {
         }
 
         self.state.push(*fragment_id, store);
+
+        if at_least_one_match {
+            self.history.record(HistoryEntry::new(
+                *fragment_id,
+                Some(fragment),
+                inputs,
+                outputs,
+            ));
+        }
+
         at_least_one_match
     }
 
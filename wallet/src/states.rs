@@ -119,6 +119,81 @@ where
         while self.pop_legacy_confirmed() {}
     }
 
+    /// get the state preceding the state associated to the given key
+    ///
+    /// returns `None` if the key is not in the States or if it is the
+    /// confirmed state (there is nothing before it).
+    pub fn previous<Q: ?Sized>(&self, key: &Q) -> Option<(&K, &S)>
+    where
+        KeyRef<K>: Borrow<Q>,
+        Q: Hash + Eq,
+    {
+        let state = self.map.get(key)?;
+        let previous = unsafe { state.prev.as_ref() }?;
+
+        Some((&previous.key, &previous.state))
+    }
+
+    /// remove the **pending** state associated to the given key and recompute
+    /// all the states that were pushed after it
+    ///
+    /// `rebase` is called for every following state, from the oldest to the
+    /// most recent, with:
+    ///
+    /// 1. the new previous state (already rebased);
+    /// 2. the previous state as it was before the removal;
+    /// 3. the state to rebase.
+    ///
+    /// and returns the new state to use in place of the state to rebase.
+    ///
+    /// returns the removed key and state, `None` if there is no pending state
+    /// associated to the given key.
+    pub fn reject<Q: ?Sized, F>(&mut self, key: &Q, mut rebase: F) -> Option<(K, S)>
+    where
+        KeyRef<K>: Borrow<Q>,
+        Q: Hash + Eq,
+        F: FnMut(&S, &S, &S) -> S,
+    {
+        let removed: *mut State<K, S> = match self.map.get_mut(key) {
+            Some(state) if !state.confirmed() => &mut **state,
+            _ => return None,
+        };
+
+        unsafe {
+            // the head is always confirmed so a pending state always
+            // has a previous state
+            let prev = (*removed).prev;
+            let next = (*removed).next;
+            debug_assert!(!prev.is_null());
+
+            (*prev).next = next;
+            if let Some(next) = next.as_mut() {
+                next.prev = prev;
+            } else {
+                self.tail = prev;
+            }
+
+            let mut old_prev: Option<S> = None;
+            let mut cursor = next;
+            while let Some(current) = cursor.as_mut() {
+                let new_state = rebase(
+                    &(*current.prev).state,
+                    old_prev.as_ref().unwrap_or(&(*removed).state),
+                    &current.state,
+                );
+                old_prev = Some(std::mem::replace(&mut current.state, new_state));
+                cursor = current.next;
+            }
+        }
+
+        let removed = self.map.remove(key).expect("the state is in the map");
+
+        while self.pop_legacy_confirmed() {}
+
+        let State { key, state, .. } = *removed;
+        Some((key, state))
+    }
+
     fn pop_legacy_confirmed(&mut self) -> bool {
         let current = unsafe { &mut (*self.head) as &mut State<K, S> };
         debug_assert!(current.confirmed());
@@ -373,4 +448,51 @@ mod tests {
         assert_eq!((&4, &()), multiverse.confirmed_state());
         assert_eq!((&4, &(), Status::Confirmed), multiverse.last_state());
     }
+
+    #[test]
+    fn reject_rebases_following_states() {
+        // every state is the running sum of the deltas: 1, 2, 3, 4
+        let mut multiverse = States::new(0u8, 0u32);
+        multiverse.push(1, 1);
+        multiverse.push(2, 3);
+        multiverse.push(3, 6);
+        multiverse.push(4, 10);
+
+        let rebase = |new_prev: &u32, old_prev: &u32, state: &u32| new_prev + (state - old_prev);
+
+        assert_eq!(Some((2, 3)), multiverse.reject(&2, rebase));
+        assert!(!multiverse.contains(&2));
+        assert_eq!(multiverse.len(), 4);
+
+        let states: Vec<_> = multiverse.iter().map(|(k, s, _)| (*k, *s)).collect();
+        assert_eq!(states, vec![(0, 0), (1, 1), (3, 4), (4, 8)]);
+
+        assert_eq!(Some((4, 8)), multiverse.reject(&4, rebase));
+        assert_eq!((&3, &4, Status::Pending), multiverse.last_state());
+
+        let states: Vec<_> = multiverse.iter().rev().map(|(k, s, _)| (*k, *s)).collect();
+        assert_eq!(states, vec![(3, 4), (1, 1), (0, 0)]);
+    }
+
+    #[test]
+    fn reject_only_pending_states() {
+        let mut multiverse = States::new(0u8, ());
+        multiverse.push(1, ());
+        multiverse.push(2, ());
+        multiverse.confirm(&2);
+
+        let rebase = |_: &(), _: &(), _: &()| ();
+
+        assert_eq!(None, multiverse.reject(&0, rebase));
+        assert_eq!(None, multiverse.reject(&2, rebase));
+        assert_eq!(None, multiverse.reject(&3, rebase));
+        assert_eq!(Some((&0, &())), multiverse.previous(&1));
+
+        // rejecting the only pending state before the confirmed one makes
+        // the later state the new confirmed state
+        assert_eq!(Some((1, ())), multiverse.reject(&1, rebase));
+        assert_eq!((&2, &()), multiverse.confirmed_state());
+        assert_eq!(multiverse.len(), 1);
+        assert_eq!(None, multiverse.previous(&2));
+    }
 }
@@ -0,0 +1,69 @@
+use super::Groupable;
+use chain_addr::{Address, Discrimination};
+use chain_crypto::{Ed25519, Ed25519Extended, PublicKey, SecretKey};
+
+/// the key spending a UTxO of the wallet, with the account the stake of the
+/// UTxO is delegated to if it is on a group address
+///
+/// The UTxOs are grouped by key and delegation in the `UtxoStore`, see
+/// `UtxoGroup::delegation`.
+#[derive(Clone)]
+pub struct DelegatedKey {
+    key: SecretKey<Ed25519Extended>,
+    public_key: PublicKey<Ed25519>,
+    delegation: Option<PublicKey<Ed25519>>,
+}
+
+impl DelegatedKey {
+    pub(crate) fn new(
+        key: SecretKey<Ed25519Extended>,
+        delegation: Option<PublicKey<Ed25519>>,
+    ) -> Self {
+        let public_key = key.to_public();
+        Self {
+            key,
+            public_key,
+            delegation,
+        }
+    }
+
+    pub fn secret_key(&self) -> &SecretKey<Ed25519Extended> {
+        &self.key
+    }
+
+    /// the public key of `secret_key`, computed once when the key is built
+    pub fn public_key(&self) -> &PublicKey<Ed25519> {
+        &self.public_key
+    }
+
+    /// the account the stake is delegated to, `None` for the UTxOs on a
+    /// single address
+    pub fn delegation(&self) -> Option<&PublicKey<Ed25519>> {
+        self.delegation.as_ref()
+    }
+
+    /// the address of the UTxO: the single address of the key or the group
+    /// address with the same delegation
+    ///
+    /// Use it as the change address of a transaction (see `select_inputs`)
+    /// so spending the UTxOs does not undelegate the change.
+    pub fn address(&self, discrimination: Discrimination) -> Address {
+        let pk = self.public_key.clone();
+        match &self.delegation {
+            None => Address(discrimination, chain_addr::Kind::Single(pk)),
+            Some(account) => Address(discrimination, chain_addr::Kind::Group(pk, account.clone())),
+        }
+    }
+}
+
+impl Groupable for DelegatedKey {
+    type Key = (PublicKey<Ed25519>, Option<PublicKey<Ed25519>>);
+
+    fn group_key(&self) -> Self::Key {
+        (self.public_key.clone(), self.delegation.clone())
+    }
+
+    fn delegation(&self) -> Option<&PublicKey<Ed25519>> {
+        self.delegation.as_ref()
+    }
+}
@@ -1,3 +1,7 @@
+mod delegated_key;
 mod utxo;
 
-pub use self::utxo::{Groupable, UtxoGroup, UtxoStore};
+pub use self::{
+    delegated_key::DelegatedKey,
+    utxo::{Groupable, UtxoGroup, UtxoStore},
+};
@@ -57,6 +57,15 @@ pub trait Groupable {
     type Key: std::hash::Hash + Eq + Clone;
 
     fn group_key(&self) -> Self::Key;
+
+    /// the account the stake of the UTxOs is delegated to, if they are
+    /// on a group address
+    ///
+    /// The keys delegating their UTxOs need to be grouped by delegation
+    /// too, so all the UTxOs of a group have the same delegation.
+    fn delegation(&self) -> Option<&chain_crypto::PublicKey<chain_crypto::Ed25519>> {
+        None
+    }
 }
 
 impl<KIND, SCHEME> Groupable for Key<KIND, SCHEME> {
@@ -155,6 +164,14 @@ impl<K> UtxoGroup<K> {
     }
 }
 
+impl<K: Groupable> UtxoGroup<K> {
+    /// the account the stake of the UTxOs of this group is delegated to,
+    /// `None` if they are on a single address
+    pub fn delegation(&self) -> Option<&chain_crypto::PublicKey<chain_crypto::Ed25519>> {
+        self.key.delegation()
+    }
+}
+
 impl<K: Groupable> UtxoStore<K> {
     pub fn new() -> Self {
         Self::default()
@@ -184,6 +201,22 @@ impl<K: Groupable> UtxoStore<K> {
             .flatten()
     }
 
+    /// create a new UTxOStore with only the groups of UTxOs whose key
+    /// satisfies the `predicate`
+    pub fn filter<F>(&self, predicate: F) -> Self
+    where
+        K: Clone,
+        F: Fn(&K) -> bool,
+    {
+        let mut store = Self::new();
+        for group in self.groups().filter(|group| predicate(group.key())) {
+            for utxo in group.utxos() {
+                store = store.add(**utxo, K::clone(group.key()));
+            }
+        }
+        store
+    }
+
     /// lookup the UTxO group (if any) associated to the given derivation path
     pub fn group(&self, dp: &<K as Groupable>::Key) -> Option<&GroupRef<K>> {
         self.by_derivation_path.lookup(dp)
@@ -241,7 +274,7 @@ impl<K: Groupable> UtxoStore<K> {
         let group = new.by_utxo.lookup(utxo)?;
         let path = group.key.group_key();
 
-        new.by_utxo.remove(utxo).ok()?;
+        new.by_utxo = new.by_utxo.remove(utxo).ok()?;
 
         new.by_derivation_path = new
             .by_derivation_path
@@ -269,6 +302,48 @@ impl<K: Groupable> UtxoStore<K> {
     pub fn get_signing_key(&self, utxo: &UtxoPointer) -> Option<Rc<K>> {
         self.by_utxo.lookup(utxo).map(|group| Rc::clone(&group.key))
     }
+
+    /// check if the given UTxO is in the store
+    pub fn contains(&self, utxo: &UtxoPointer) -> bool {
+        self.by_utxo.lookup(utxo).is_some()
+    }
+
+    /// get the UTxOs of this store that are not in the `other` store
+    pub fn difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a UtxoPointer> {
+        self.by_utxo
+            .iter()
+            .map(|(utxo, _)| utxo.as_ref())
+            .filter(move |utxo| !other.contains(utxo))
+    }
+
+    /// create a new UTxOStore applying the changes that lead from the
+    /// `before` store to the `after` store: the UTxOs that were removed are
+    /// removed (if present) and the UTxOs that were added are added.
+    ///
+    /// This is useful to replay the effect of a transaction on top of a
+    /// different state, for example when an older pending transaction
+    /// has been rejected.
+    #[must_use = "function does not modify the internal state, the returned value is the new state"]
+    pub fn apply_changes(&self, before: &Self, after: &Self) -> Self
+    where
+        K: Clone,
+    {
+        let mut new = self.clone();
+
+        for utxo in before.difference(after) {
+            if let Some(updated) = new.remove(utxo) {
+                new = updated;
+            }
+        }
+
+        for (utxo, group) in after.by_utxo.iter() {
+            if !before.contains(utxo) && !new.contains(utxo) {
+                new = new.add(**utxo, group.key.as_ref().clone());
+            }
+        }
+
+        new
+    }
 }
 
 impl<K> Clone for UtxoGroup<K> {
@@ -447,6 +522,47 @@ mod tests {
         assert_eq!(by_key(&key, &store4).len(), 3);
     }
 
+    #[test]
+    fn test_apply_changes() {
+        use chain_impl_mockchain::key::Hash;
+        let key = MockKey(0);
+        let store1 = UtxoStore::<MockKey>::new();
+
+        let utxo1 = UtxoPointer {
+            transaction_id: Hash::from_bytes([0u8; 32]),
+            output_index: 0u8,
+            value: Value(100),
+        };
+
+        let utxo2 = UtxoPointer {
+            transaction_id: Hash::from_bytes([1u8; 32]),
+            output_index: 1u8,
+            value: Value(300),
+        };
+
+        let utxo3 = UtxoPointer {
+            transaction_id: Hash::from_bytes([2u8; 32]),
+            output_index: 2u8,
+            value: Value(200),
+        };
+
+        let store2 = store1.add(utxo1, key).add(utxo2, key);
+        // a transaction spending `utxo1` and creating `utxo3`
+        let store3 = store2.remove(&utxo1).unwrap().add(utxo3, key);
+
+        assert!(!store3.contains(&utxo1));
+        itertools::assert_equal(store2.difference(&store3), vec![&utxo1]);
+
+        // replay the transaction on a store without `utxo2`
+        let other = store1.add(utxo1, key);
+        let replayed = other.apply_changes(&store2, &store3);
+
+        assert!(!replayed.contains(&utxo1));
+        assert!(!replayed.contains(&utxo2));
+        assert!(replayed.contains(&utxo3));
+        assert_eq!(replayed.total_value(), Value(200));
+    }
+
     #[test]
     fn test_utxos_are_sorted() {
         use chain_impl_mockchain::key::Hash;
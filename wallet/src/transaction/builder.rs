@@ -1,3 +1,7 @@
+use super::signer::{SignRequest, Signer, SignerError};
+use super::unsigned::{
+    KeyId, UnsignedInput, UnsignedTransaction, UnsignedTransactionError, WitnessKind,
+};
 use super::witness_builder::WitnessBuilder;
 use crate::Settings;
 use chain_addr::Address;
@@ -5,7 +9,7 @@ use chain_impl_mockchain::{
     fee::FeeAlgorithm as _,
     transaction::{
         Balance, Input, Output, Payload, SetAuthData, SetIOs, SetWitnesses, Transaction,
-        TxBuilderState,
+        TransactionBindingAuthData, TxBuilderState, Witness,
     },
     value::Value,
 };
@@ -15,16 +19,43 @@ use thiserror::Error;
 #[error("Cannot balance the transaction")]
 pub struct BalancingError;
 
+#[derive(Debug, Error)]
+pub enum FinalizeError {
+    #[error("Cannot balance the transaction")]
+    Balancing(
+        #[source]
+        #[from]
+        BalancingError,
+    ),
+
+    #[error("no signer to sign the input {0}")]
+    MissingSigner(usize),
+
+    #[error("cannot sign the input {index}")]
+    Signer {
+        index: usize,
+        #[source]
+        source: SignerError,
+    },
+}
+
 pub struct TransactionBuilder<'settings, P: Payload> {
     settings: &'settings Settings,
     payload: P,
     outputs: Vec<Output<Address>>,
     inputs: Vec<Input>,
-    witness_builders: Vec<WB>,
+    input_witnesses: Vec<InputWitness>,
 }
 
 type WB = Box<dyn WitnessBuilder>;
 
+/// how the witness of an input is built
+enum InputWitness {
+    Builder(WB),
+    /// signed by the external signer given when finalizing the transaction
+    External(KeyId, WitnessKind),
+}
+
 pub enum AddInputStatus {
     Added,
     Skipped(Input),
@@ -39,10 +70,15 @@ impl<'settings, P: Payload> TransactionBuilder<'settings, P> {
             payload,
             outputs: Vec::with_capacity(255),
             inputs: Vec::with_capacity(255),
-            witness_builders: Vec::with_capacity(255),
+            input_witnesses: Vec::with_capacity(255),
         }
     }
 
+    #[inline]
+    pub fn settings(&self) -> &'settings Settings {
+        self.settings
+    }
+
     #[inline]
     pub fn inputs(&self) -> &[Input] {
         &self.inputs
@@ -97,10 +133,25 @@ impl<'settings, P: Payload> TransactionBuilder<'settings, P> {
         input: Input,
         witness_builder: B,
     ) -> bool {
+        self.push_input(input, InputWitness::Builder(Box::new(witness_builder)))
+    }
+
+    /// add an input that will be signed by the external signer given to
+    /// `finalize_tx_with_signer`, with the given key
+    pub fn add_input_with_key(
+        &mut self,
+        input: Input,
+        key: KeyId,
+        witness_kind: WitnessKind,
+    ) -> bool {
+        self.push_input(input, InputWitness::External(key, witness_kind))
+    }
+
+    fn push_input(&mut self, input: Input, witness: InputWitness) -> bool {
         match self.inputs.len().cmp(&255) {
             std::cmp::Ordering::Less => {
                 self.inputs.push(input);
-                self.witness_builders.push(Box::new(witness_builder));
+                self.input_witnesses.push(witness);
                 true
             }
             _ => false,
@@ -136,17 +187,89 @@ impl<'settings, P: Payload> TransactionBuilder<'settings, P> {
         }
     }
 
-    pub fn finalize_tx(self, auth: <P as Payload>::Auth) -> Result<Transaction<P>, BalancingError> {
+    /// finalize the transaction, all the inputs need to have been added
+    /// with a witness builder
+    ///
+    /// # Errors
+    ///
+    /// * `FinalizeError::Balancing` if the transaction is not balanced;
+    /// * `FinalizeError::MissingSigner` if an input has been added with
+    ///   `add_input_with_key`, use `finalize_tx_with_signer` instead.
+    pub fn finalize_tx(self, auth: <P as Payload>::Auth) -> Result<Transaction<P>, FinalizeError> {
+        self.finalize_tx_with(|_| auth)
+    }
+
+    /// same as `finalize_tx` but the payload's authentication is built from
+    /// the transaction's binding data, for the payloads that need to be
+    /// signed (a `StakeDelegation` certificate for example).
+    pub fn finalize_tx_with<F>(self, auth: F) -> Result<Transaction<P>, FinalizeError>
+    where
+        F: FnOnce(&TransactionBindingAuthData<'_>) -> <P as Payload>::Auth,
+    {
+        self.finalize(None, auth)
+    }
+
+    /// same as `finalize_tx` but the inputs added with `add_input_with_key`
+    /// are signed by the given `signer`
+    ///
+    /// # Errors
+    ///
+    /// * `FinalizeError::Balancing` if the transaction is not balanced;
+    /// * `FinalizeError::Signer` if the signer failed to sign one of the
+    ///   inputs.
+    pub fn finalize_tx_with_signer(
+        self,
+        signer: &dyn Signer,
+        auth: <P as Payload>::Auth,
+    ) -> Result<Transaction<P>, FinalizeError> {
+        self.finalize(Some(signer), |_| auth)
+    }
+
+    /// build an unsigned transaction from the inputs and outputs of the
+    /// builder, to be signed later or on a different device
+    ///
+    /// # Errors
+    ///
+    /// * `UnsignedTransactionError::MissingKey` if an input has been added
+    ///   with a witness builder instead of `add_input_with_key`;
+    /// * `UnsignedTransactionError::Balancing` if the transaction is not
+    ///   balanced.
+    pub fn into_unsigned(self) -> Result<UnsignedTransaction<P>, UnsignedTransactionError> {
+        let inputs = self
+            .inputs
+            .into_iter()
+            .zip(self.input_witnesses)
+            .enumerate()
+            .map(|(index, (input, witness))| match witness {
+                InputWitness::External(key, witness_kind) => {
+                    Ok(UnsignedInput::new(input, key, witness_kind))
+                }
+                InputWitness::Builder(_) => Err(UnsignedTransactionError::MissingKey(index)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        UnsignedTransaction::new(self.settings, self.payload, inputs, self.outputs)
+    }
+
+    fn finalize<F>(
+        self,
+        signer: Option<&dyn Signer>,
+        auth: F,
+    ) -> Result<Transaction<P>, FinalizeError>
+    where
+        F: FnOnce(&TransactionBindingAuthData<'_>) -> <P as Payload>::Auth,
+    {
         if !matches!(self.check_balance(), Balance::Zero) {
-            return Err(BalancingError);
+            return Err(BalancingError.into());
         }
 
         let builder = TxBuilderState::new();
         let builder = builder.set_payload(&self.payload);
 
         let builder = self.set_ios(builder);
-        let builder = self.set_witnesses(builder);
+        let builder = self.set_witnesses(builder, signer)?;
 
+        let auth = auth(&builder.get_auth_data());
         Ok(builder.set_payload_auth(&auth))
     }
 
@@ -157,18 +280,30 @@ impl<'settings, P: Payload> TransactionBuilder<'settings, P> {
     fn set_witnesses(
         &self,
         builder: TxBuilderState<SetWitnesses<P>>,
-    ) -> TxBuilderState<SetAuthData<P>>
+        signer: Option<&dyn Signer>,
+    ) -> Result<TxBuilderState<SetAuthData<P>>, FinalizeError>
     where
         P: Payload,
     {
         let header_id = self.settings.static_parameters.block0_initial_hash;
         let auth_data = builder.get_auth_data_for_witness().hash();
-        let witnesses: Vec<_> = self
-            .witness_builders
+        let witnesses = self
+            .input_witnesses
             .iter()
-            .map(|wb| wb.build(&header_id, &auth_data))
-            .collect();
+            .enumerate()
+            .map(|(index, witness)| match witness {
+                InputWitness::Builder(wb) => Ok(wb.build(&header_id, &auth_data)),
+                InputWitness::External(key, witness_kind) => {
+                    let signer = signer.ok_or(FinalizeError::MissingSigner(index))?;
+                    let request =
+                        SignRequest::new(header_id, auth_data.clone(), key.clone(), *witness_kind);
+                    signer
+                        .sign(&request)
+                        .map_err(|source| FinalizeError::Signer { index, source })
+                }
+            })
+            .collect::<Result<Vec<Witness>, FinalizeError>>()?;
 
-        builder.set_witnesses(&witnesses)
+        Ok(builder.set_witnesses(&witnesses))
     }
 }
@@ -6,6 +6,7 @@ use chain_impl_mockchain::{
     fragment::Fragment,
     transaction::{Balance, Input, NoExtra, Output, Transaction},
 };
+use chain_path_derivation::HardDerivation;
 
 pub struct DumpIter<'a, W> {
     settings: &'a crate::Settings,
@@ -18,6 +19,12 @@ pub type DumpIcarus<'a> =
     DumpIter<'a, crate::scheme::bip44::Wallet<chain_impl_mockchain::legacy::OldAddress>>;
 pub type DumpFreeKeys<'a> = DumpIter<'a, crate::scheme::freeutxo::Wallet>;
 
+/// same as `DumpIcarus` but only the UTxOs of one account are sent
+pub struct DumpIcarusAccount<'a> {
+    dump: DumpIcarus<'a>,
+    account: HardDerivation,
+}
+
 pub fn send_to_one_address<K: Clone + Groupable, WB: WitnessBuilder>(
     settings: &crate::Settings,
     address: &chain_addr::Address,
@@ -87,6 +94,18 @@ pub fn dump_icarus_utxo<'a>(
     }
 }
 
+pub fn dump_icarus_account_utxo<'a>(
+    settings: &'a crate::Settings,
+    address: &'a chain_addr::Address,
+    wallet: &'a mut crate::scheme::bip44::Wallet<chain_impl_mockchain::legacy::OldAddress>,
+    account: HardDerivation,
+) -> DumpIcarusAccount<'a> {
+    DumpIcarusAccount {
+        dump: dump_icarus_utxo(settings, address, wallet),
+        account,
+    }
+}
+
 pub fn dump_free_utxo<'a>(
     settings: &'a crate::Settings,
     address: &'a chain_addr::Address,
@@ -133,6 +152,25 @@ impl<'a> Iterator for DumpIcarus<'a> {
     }
 }
 
+impl<'a> Iterator for DumpIcarusAccount<'a> {
+    type Item = (Fragment, Vec<Input>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let dump = &mut self.dump;
+        let utxos = dump.wallet.account_utxos(self.account);
+        let next = send_to_one_address(dump.settings, dump.address, &utxos, &|key| {
+            OldUtxoWitnessBuilder(key)
+        })
+        .map(|(tx, ignored)| (Fragment::Transaction(tx), ignored));
+
+        if let Some((fragment, _)) = next.as_ref() {
+            dump.wallet.check_fragment(&fragment.hash(), &fragment);
+        }
+
+        next
+    }
+}
+
 impl<'a> Iterator for DumpFreeKeys<'a> {
     type Item = (Fragment, Vec<Input>);
 
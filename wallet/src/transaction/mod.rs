@@ -1,12 +1,20 @@
 mod builder;
 mod dump;
+mod selection;
+mod signer;
 mod strategy;
+mod unsigned;
 mod witness_builder;
 
 pub use dump::*;
 
 pub use self::{
-    builder::{AddInputStatus, TransactionBuilder},
+    builder::{AddInputStatus, BalancingError, FinalizeError, TransactionBuilder},
+    selection::{select_inputs, select_inputs_with_keys, Selection, SelectionError},
+    signer::{SignRequest, Signer, SignerError, SoftwareSigner},
     strategy::{InputStrategy, OutputStrategy, Strategy, StrategyBuilder, DEFAULT_STRATEGIES},
-    witness_builder::AccountWitnessBuilder,
+    unsigned::{KeyId, UnsignedInput, UnsignedTransaction, UnsignedTransactionError, WitnessKind},
+    witness_builder::{
+        AccountWitnessBuilder, OldUtxoWitnessBuilder, UtxoWitnessBuilder, WitnessBuilder,
+    },
 };
@@ -0,0 +1,351 @@
+use super::builder::TransactionBuilder;
+use super::strategy::{InputStrategy, OutputStrategy, Strategy};
+use super::unsigned::{KeyId, WitnessKind};
+use super::witness_builder::WitnessBuilder;
+use crate::store::{Groupable, UtxoStore};
+use chain_addr::Address;
+use chain_impl_mockchain::{
+    fee::FeeAlgorithm as _,
+    transaction::{Input, Output, Payload, UtxoPointer},
+    value::Value,
+};
+use std::rc::Rc;
+use thiserror::Error;
+
+/// a transaction cannot have more than this number of inputs
+const MAX_INPUTS: usize = 255;
+
+/// with the `UtxoReshuffle` output strategy, the change is only split
+/// if it is worth at least this many times the cost of spending an input
+const RESHUFFLE_DUST_FACTOR: u64 = 10;
+
+/// number of change outputs created when the change is reshuffled
+const RESHUFFLE_OUTPUTS: u8 = 2;
+
+#[derive(Debug, Error)]
+pub enum SelectionError {
+    #[error("not enough funds: {needed} is required but only {available} is available")]
+    NotEnoughFunds { needed: Value, available: Value },
+
+    #[error("none of the strategies could select inputs to balance the transaction")]
+    NoSolution,
+}
+
+/// the result of a successful coin selection
+///
+/// The inputs and the change outputs have already been added to the
+/// transaction builder, this is only a summary of what has been selected.
+#[derive(Debug, Clone)]
+pub struct Selection {
+    strategy: Strategy,
+    inputs: Vec<Input>,
+    change: Vec<Output<Address>>,
+}
+
+/// the inputs and the change values a strategy settled on, before they
+/// are added to the transaction builder
+struct Candidate {
+    utxos: Vec<Rc<UtxoPointer>>,
+    change: Vec<Value>,
+}
+
+impl Selection {
+    /// the strategy that has been used to select the inputs
+    pub fn strategy(&self) -> &Strategy {
+        &self.strategy
+    }
+
+    pub fn inputs(&self) -> &[Input] {
+        &self.inputs
+    }
+
+    /// the change outputs, if any
+    pub fn change(&self) -> &[Output<Address>] {
+        &self.change
+    }
+}
+
+/// select the inputs from the `store` to balance the transaction of the
+/// given `builder`.
+///
+/// The `strategies` are tried in order, falling back to the next one if
+/// a strategy cannot find a balanced set of inputs. Use `DEFAULT_STRATEGIES`
+/// if there are no specific needs.
+///
+/// If a change is needed, `change_address` is called with the signing key of
+/// the first selected input to get the address to send the change to. It may
+/// be called multiple times if the change is split in multiple outputs.
+///
+/// On success the selected inputs (with their witness builder) and the change
+/// outputs are added to the `builder` and the transaction is balanced.
+///
+/// # Errors
+///
+/// * `SelectionError::NotEnoughFunds` if the UTxOs of the store are not
+///   enough to pay for the outputs and the fees;
+/// * `SelectionError::NoSolution` if there is enough funds but none of the
+///   strategies succeeded (too many inputs needed for example).
+///
+pub fn select_inputs<P, K, WB, F>(
+    builder: &mut TransactionBuilder<P>,
+    store: &UtxoStore<K>,
+    strategies: &[Strategy],
+    mk_witness: &dyn Fn(K) -> WB,
+    change_address: F,
+) -> Result<Selection, SelectionError>
+where
+    P: Payload,
+    K: Groupable + Clone,
+    WB: WitnessBuilder + 'static,
+    F: FnMut(&K) -> Address,
+{
+    select_inputs_with(
+        builder,
+        store,
+        strategies,
+        |builder, input, key| {
+            builder.add_input(input, mk_witness(key.clone()));
+        },
+        change_address,
+    )
+}
+
+/// same as `select_inputs` but the selected inputs are added without a
+/// witness builder, only with the key expected to sign them (see
+/// `TransactionBuilder::add_input_with_key`).
+///
+/// This is for the wallets that do not hold the signing keys, a watch-only
+/// wallet for example. `mk_key` returns the key identifier and the kind of
+/// witness of the UTxOs of the given key.
+pub fn select_inputs_with_keys<P, K, F>(
+    builder: &mut TransactionBuilder<P>,
+    store: &UtxoStore<K>,
+    strategies: &[Strategy],
+    mk_key: &dyn Fn(&K) -> (KeyId, WitnessKind),
+    change_address: F,
+) -> Result<Selection, SelectionError>
+where
+    P: Payload,
+    K: Groupable + Clone,
+    F: FnMut(&K) -> Address,
+{
+    select_inputs_with(
+        builder,
+        store,
+        strategies,
+        |builder, input, key| {
+            let (key, witness_kind) = mk_key(key);
+            builder.add_input_with_key(input, key, witness_kind);
+        },
+        change_address,
+    )
+}
+
+fn select_inputs_with<P, K, A, F>(
+    builder: &mut TransactionBuilder<P>,
+    store: &UtxoStore<K>,
+    strategies: &[Strategy],
+    mut add_input: A,
+    mut change_address: F,
+) -> Result<Selection, SelectionError>
+where
+    P: Payload,
+    K: Groupable + Clone,
+    A: FnMut(&mut TransactionBuilder<P>, Input, &K),
+    F: FnMut(&K) -> Address,
+{
+    let found = strategies.iter().find_map(|strategy| {
+        select_with(builder, store, strategy).map(|candidate| (*strategy, candidate))
+    });
+
+    let (strategy, candidate) = match found {
+        Some(found) => found,
+        None => return Err(insufficient_funds(builder, store)),
+    };
+
+    let mut inputs = Vec::with_capacity(candidate.utxos.len());
+    let mut change_key = None;
+    for utxo in candidate.utxos {
+        let key = store
+            .get_signing_key(&utxo)
+            .expect("the utxo was selected from the store");
+        let input = Input::from_utxo(*utxo);
+
+        add_input(builder, input.clone(), &key);
+        inputs.push(input);
+        change_key.get_or_insert(key);
+    }
+
+    let mut change = Vec::with_capacity(candidate.change.len());
+    if let Some(key) = change_key {
+        for value in candidate.change {
+            let output = Output::from_address(change_address(&key), value);
+            builder.add_output(output.clone());
+            change.push(output);
+        }
+    }
+
+    Ok(Selection {
+        strategy,
+        inputs,
+        change,
+    })
+}
+
+fn select_with<P, K>(
+    builder: &TransactionBuilder<P>,
+    store: &UtxoStore<K>,
+    strategy: &Strategy,
+) -> Option<Candidate>
+where
+    P: Payload,
+    K: Groupable,
+{
+    match strategy.input() {
+        InputStrategy::BestEffort => {
+            let utxos: Vec<_> = store.utxos().collect();
+            accumulate(builder, utxos.into_iter().rev(), strategy.output())
+        }
+        InputStrategy::PrivacyPreserving => {
+            let mut groups: Vec<_> = store.groups().collect();
+            groups.sort_by_key(|group| group.total_value());
+
+            groups.into_iter().find_map(|group| {
+                let utxos: Vec<_> = group.utxos().collect();
+                accumulate(builder, utxos.into_iter().rev(), strategy.output())
+            })
+        }
+    }
+}
+
+/// add the given UTxOs, in order, until the transaction can be balanced
+fn accumulate<'a, P, I>(
+    builder: &TransactionBuilder<P>,
+    utxos: I,
+    output: OutputStrategy,
+) -> Option<Candidate>
+where
+    P: Payload,
+    I: Iterator<Item = &'a Rc<UtxoPointer>>,
+{
+    let settings = builder.settings();
+    let max_inputs = MAX_INPUTS.saturating_sub(builder.inputs().len());
+
+    let mut selected = Vec::new();
+    let mut selected_value = Value::zero();
+    let mut utxos = utxos.filter(|utxo| settings.is_input_worth(&Input::from_utxo(***utxo)));
+
+    loop {
+        if let Some(change) = balance(builder, selected_value, selected.len() as u8, output) {
+            return Some(Candidate {
+                utxos: selected,
+                change,
+            });
+        }
+
+        if selected.len() >= max_inputs {
+            return None;
+        }
+
+        let utxo = utxos.next()?;
+        selected_value = selected_value.saturating_add(utxo.value);
+        selected.push(Rc::clone(utxo));
+    }
+}
+
+/// compute the change outputs needed to balance the transaction with the
+/// extra inputs. `None` if the transaction cannot be balanced yet.
+fn balance<P: Payload>(
+    builder: &TransactionBuilder<P>,
+    extra_value: Value,
+    extra_inputs: u8,
+    output: OutputStrategy,
+) -> Option<Vec<Value>> {
+    let total_in = builder.inputs_value().saturating_add(extra_value);
+    let total_out = builder.outputs_value();
+
+    let remaining = |extra_outputs: u8| {
+        let fee = builder.estimate_fee_with(extra_inputs, extra_outputs);
+        total_in.checked_sub(total_out.saturating_add(fee)).ok()
+    };
+
+    if extra_inputs == 0 {
+        // at least one input needs to be selected from the store
+        return None;
+    }
+
+    if remaining(0) == Some(Value::zero()) {
+        return Some(Vec::new());
+    }
+
+    let change = remaining(1).filter(|change| *change > Value::zero())?;
+
+    if output == OutputStrategy::UtxoReshuffle {
+        let dust = builder
+            .settings()
+            .parameters
+            .fees
+            .fees_for_inputs_outputs(1, 0);
+        let threshold = Value(dust.0.saturating_mul(RESHUFFLE_DUST_FACTOR));
+
+        if let Some(change) = remaining(RESHUFFLE_OUTPUTS)
+            .filter(|change| *change >= threshold && change.0 >= u64::from(RESHUFFLE_OUTPUTS))
+        {
+            return Some(split(change, RESHUFFLE_OUTPUTS));
+        }
+    }
+
+    Some(vec![change])
+}
+
+/// split the value in `parts` values, the remainder goes in the first one
+fn split(value: Value, parts: u8) -> Vec<Value> {
+    let parts = u64::from(parts);
+    let share = value.0 / parts;
+    let remainder = value.0 % parts;
+
+    (0..parts)
+        .map(|i| if i == 0 { share + remainder } else { share })
+        .map(Value)
+        .collect()
+}
+
+fn insufficient_funds<P, K>(builder: &TransactionBuilder<P>, store: &UtxoStore<K>) -> SelectionError
+where
+    P: Payload,
+    K: Groupable,
+{
+    let settings = builder.settings();
+    let worth: Vec<_> = store
+        .utxos()
+        .filter(|utxo| settings.is_input_worth(&Input::from_utxo(***utxo)))
+        .collect();
+
+    let available = builder
+        .inputs_value()
+        .saturating_add(worth.iter().map(|utxo| utxo.value).sum());
+    let extra_inputs = worth
+        .len()
+        .min(MAX_INPUTS.saturating_sub(builder.inputs().len())) as u8;
+    let needed = builder
+        .outputs_value()
+        .saturating_add(builder.estimate_fee_with(extra_inputs.max(1), 0));
+
+    if available < needed {
+        SelectionError::NotEnoughFunds { needed, available }
+    } else {
+        SelectionError::NoSolution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_keeps_the_value() {
+        assert_eq!(split(Value(10), 2), vec![Value(5), Value(5)]);
+        assert_eq!(split(Value(11), 2), vec![Value(6), Value(5)]);
+        assert_eq!(split(Value(12), 3), vec![Value(4), Value(4), Value(4)]);
+    }
+}
@@ -0,0 +1,190 @@
+//! signing of the inputs by a signer holding the keys outside of the wallet
+//!
+//! A [`Signer`] only receives a [`SignRequest`]: the hash of the data to
+//! sign, the hash of the block0 and the key expected to sign the input. It
+//! can be a hardware device, a remote process or the in-memory
+//! [`SoftwareSigner`].
+//!
+//! When the signature is asynchronous (the user has to confirm on a device
+//! for example), the requests can be obtained from the
+//! [`UnsignedTransaction`](super::UnsignedTransaction) and the witnesses
+//! added back once they are available.
+
+use super::unsigned::{KeyId, WitnessKind};
+use super::witness_builder::{
+    AccountWitnessBuilder, OldUtxoWitnessBuilder, UtxoWitnessBuilder, WitnessBuilder as _,
+};
+use chain_crypto::{Ed25519, Ed25519Extended, SecretKey};
+use chain_impl_mockchain::{
+    block::HeaderId,
+    transaction::{TransactionSignDataHash, Witness},
+};
+use chain_path_derivation::AnyScheme;
+use ed25519_bip32::XPrv;
+use hdkeygen::{
+    account::{Account, AccountId, SEED},
+    Key,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SignerError {
+    #[error("the signer does not hold the key {0:?}")]
+    UnknownKey(KeyId),
+
+    #[error("the signature has been refused")]
+    Refused,
+
+    #[error("the signer failed to sign")]
+    Failed(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// what a signer needs to know to build the witness of an input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignRequest {
+    block0: HeaderId,
+    sign_data_hash: TransactionSignDataHash,
+    key: KeyId,
+    witness_kind: WitnessKind,
+}
+
+/// build the witnesses of the inputs with keys that are not held by the
+/// wallet
+pub trait Signer {
+    fn sign(&self, request: &SignRequest) -> Result<Witness, SignerError>;
+}
+
+/// a signer holding the root key of a wallet in memory, the inputs are
+/// expected to be identified by their derivation path from the root key
+///
+/// The account inputs are identified by the public key of the account, they
+/// can be signed once the account's key is added with `with_account` or
+/// `with_account_seed`.
+pub struct SoftwareSigner {
+    root: Key<XPrv, AnyScheme>,
+    account: Option<SignerAccount>,
+}
+
+enum SignerAccount {
+    Seed(Account<Ed25519>),
+    Extended(Account<Ed25519Extended>),
+}
+
+impl SignRequest {
+    pub fn new(
+        block0: HeaderId,
+        sign_data_hash: TransactionSignDataHash,
+        key: KeyId,
+        witness_kind: WitnessKind,
+    ) -> Self {
+        Self {
+            block0,
+            sign_data_hash,
+            key,
+            witness_kind,
+        }
+    }
+
+    pub fn block0(&self) -> &HeaderId {
+        &self.block0
+    }
+
+    /// the hash of the transaction's data to sign
+    pub fn sign_data_hash(&self) -> &TransactionSignDataHash {
+        &self.sign_data_hash
+    }
+
+    /// the key expected to sign the input
+    pub fn key(&self) -> &KeyId {
+        &self.key
+    }
+
+    pub fn witness_kind(&self) -> WitnessKind {
+        self.witness_kind
+    }
+}
+
+impl SoftwareSigner {
+    pub fn new(root: Key<XPrv, AnyScheme>) -> Self {
+        Self {
+            root,
+            account: None,
+        }
+    }
+
+    /// sign the inputs identified by the public key of the account with
+    /// the given secret key
+    pub fn with_account(self, key: SecretKey<Ed25519Extended>) -> Self {
+        Self {
+            account: Some(SignerAccount::Extended(Account::from_secret_key(key))),
+            ..self
+        }
+    }
+
+    /// sign the inputs identified by the public key of the account with the
+    /// secret key of the given seed
+    pub fn with_account_seed(self, seed: SEED) -> Self {
+        Self {
+            account: Some(SignerAccount::Seed(Account::from_seed(seed))),
+            ..self
+        }
+    }
+}
+
+impl SignerAccount {
+    fn account_id(&self) -> AccountId {
+        match self {
+            Self::Seed(account) => account.account_id(),
+            Self::Extended(account) => account.account_id(),
+        }
+    }
+
+    fn witness_builder(&self, counter: u32) -> AccountWitnessBuilder {
+        match self {
+            Self::Seed(account) => {
+                AccountWitnessBuilder::Ed25519(account.secret().clone(), counter.into())
+            }
+            Self::Extended(account) => {
+                AccountWitnessBuilder::Ed25519Extended(account.secret().clone(), counter.into())
+            }
+        }
+    }
+}
+
+impl Signer for SoftwareSigner {
+    fn sign(&self, request: &SignRequest) -> Result<Witness, SignerError> {
+        let block0 = request.block0();
+        let sign_data_hash = request.sign_data_hash();
+
+        let key = match request.key() {
+            KeyId::Path(path) => self.root.derive_path(path),
+            KeyId::PublicKey(public_key) => {
+                return match (&self.account, request.witness_kind()) {
+                    (Some(account), WitnessKind::Account { spending_counter })
+                        if account.account_id().as_ref() == public_key.as_slice() =>
+                    {
+                        Ok(account
+                            .witness_builder(spending_counter)
+                            .build(block0, sign_data_hash))
+                    }
+                    _ => Err(SignerError::UnknownKey(request.key().clone())),
+                };
+            }
+        };
+
+        let witness = match request.witness_kind() {
+            WitnessKind::OldUtxo => OldUtxoWitnessBuilder(key).build(block0, sign_data_hash),
+            WitnessKind::Utxo => UtxoWitnessBuilder(key).build(block0, sign_data_hash),
+            WitnessKind::Account { spending_counter } => {
+                let xprv: &XPrv = key.as_ref();
+                let secret =
+                    SecretKey::<Ed25519Extended>::from_binary(xprv.extended_secret_key_bytes())
+                        .map_err(|error| SignerError::Failed(Box::new(error)))?;
+                AccountWitnessBuilder::Ed25519Extended(secret, spending_counter.into())
+                    .build(block0, sign_data_hash)
+            }
+        };
+
+        Ok(witness)
+    }
+}
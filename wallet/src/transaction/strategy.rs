@@ -49,6 +49,7 @@ pub enum OutputStrategy {
     UtxoReshuffle,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Strategy {
     input: InputStrategy,
     output: OutputStrategy,
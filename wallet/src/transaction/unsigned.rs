@@ -0,0 +1,506 @@
+//! transactions built without the signing keys
+//!
+//! An [`UnsignedTransaction`] holds everything needed to sign a transaction
+//! without having access to the wallet: the inputs with the key that is
+//! expected to sign them, the outputs, the payload and the hash of the
+//! block0. It can be exported to be signed on a different device (an
+//! air-gapped machine for example) and exported again with the witnesses
+//! to build the final transaction.
+
+use super::builder::BalancingError;
+use super::signer::{SignRequest, Signer, SignerError};
+use super::witness_builder::WitnessBuilder;
+use crate::persistence::{Kind, PersistenceError, Reader, Writer};
+use crate::Settings;
+use chain_addr::Address;
+use chain_core::property::Serialize;
+use chain_impl_mockchain::{
+    block::HeaderId,
+    fee::FeeAlgorithm as _,
+    transaction::{
+        Input, InputEnum, Output, Payload, Transaction, TransactionBindingAuthData,
+        TransactionSignDataHash, TxBuilderState, UnspecifiedAccountIdentifier, Witness,
+    },
+    value::Value,
+};
+use chain_path_derivation::{AnyScheme, DerivationPath};
+use chain_ser::mempack::{ReadBuf, Readable};
+use std::convert::TryInto as _;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum UnsignedTransactionError {
+    #[error("there is no input at index {0}")]
+    InvalidIndex(usize),
+
+    #[error("the witness of the input {0} is not of the expected kind")]
+    InvalidWitnessKind(usize),
+
+    #[error("the input {0} has not been signed")]
+    MissingWitness(usize),
+
+    #[error("the key to sign the input {0} is not known")]
+    MissingKey(usize),
+
+    #[error("the transaction is not balanced")]
+    Balancing(
+        #[source]
+        #[from]
+        BalancingError,
+    ),
+}
+
+/// identify the key expected to sign an input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyId {
+    /// the derivation path of the key from the root key of the wallet
+    Path(DerivationPath<AnyScheme>),
+    /// the public key of the key (the account identifier for an account)
+    PublicKey(Vec<u8>),
+}
+
+/// the kind of witness expected for an input
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WitnessKind {
+    /// witness of a legacy UTxO (Daedalus or Yoroi addresses)
+    OldUtxo,
+    /// witness of a UTxO
+    Utxo,
+    /// witness of an account, signed with the given spending counter
+    Account { spending_counter: u32 },
+}
+
+/// an input of an unsigned transaction with the key expected to sign it
+#[derive(Debug, Clone)]
+pub struct UnsignedInput {
+    input: Input,
+    key: KeyId,
+    witness_kind: WitnessKind,
+}
+
+/// a balanced transaction waiting for the witnesses of its inputs
+pub struct UnsignedTransaction<P> {
+    block0: HeaderId,
+    payload: P,
+    inputs: Vec<UnsignedInput>,
+    outputs: Vec<Output<Address>>,
+    witnesses: Vec<Option<Witness>>,
+}
+
+impl UnsignedInput {
+    pub fn new(input: Input, key: KeyId, witness_kind: WitnessKind) -> Self {
+        Self {
+            input,
+            key,
+            witness_kind,
+        }
+    }
+
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    pub fn value(&self) -> Value {
+        self.input.value()
+    }
+
+    /// the key expected to sign the input
+    pub fn key(&self) -> &KeyId {
+        &self.key
+    }
+
+    pub fn witness_kind(&self) -> WitnessKind {
+        self.witness_kind
+    }
+
+    fn accepts(&self, witness: &Witness) -> bool {
+        matches!(
+            (self.witness_kind, witness),
+            (WitnessKind::OldUtxo, Witness::OldUtxo(..))
+                | (WitnessKind::Utxo, Witness::Utxo(_))
+                | (WitnessKind::Account { .. }, Witness::Account(_))
+        )
+    }
+}
+
+impl<P: Payload> UnsignedTransaction<P> {
+    /// create a new unsigned transaction, the inputs are expected to pay
+    /// exactly for the outputs and the fees computed from the `settings`
+    ///
+    /// # Errors
+    ///
+    /// * `UnsignedTransactionError::Balancing` if the transaction is not
+    ///   balanced;
+    ///
+    pub fn new(
+        settings: &Settings,
+        payload: P,
+        inputs: Vec<UnsignedInput>,
+        outputs: Vec<Output<Address>>,
+    ) -> Result<Self, UnsignedTransactionError> {
+        if inputs.len() > 255 || outputs.len() > 255 {
+            return Err(BalancingError.into());
+        }
+
+        let fee = settings.parameters.fees.calculate(
+            Payload::to_certificate_slice(payload.payload_data().borrow()),
+            inputs.len() as u8,
+            outputs.len() as u8,
+        );
+        if left_over(&inputs, &outputs) != Some(fee) {
+            return Err(BalancingError.into());
+        }
+
+        let witnesses = vec![None; inputs.len()];
+        Ok(Self {
+            block0: settings.static_parameters.block0_initial_hash,
+            payload,
+            inputs,
+            outputs,
+            witnesses,
+        })
+    }
+
+    /// the hash of the block0 of the blockchain the transaction is for
+    pub fn block0(&self) -> &HeaderId {
+        &self.block0
+    }
+
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+
+    pub fn inputs(&self) -> &[UnsignedInput] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[Output<Address>] {
+        &self.outputs
+    }
+
+    /// the fees paid by the transaction
+    pub fn fee(&self) -> Value {
+        left_over(&self.inputs, &self.outputs).expect("the inputs are checked to cover the outputs")
+    }
+
+    /// the hash of the data signed by the witnesses
+    pub fn sign_data_hash(&self) -> TransactionSignDataHash {
+        let inputs: Vec<Input> = self.inputs.iter().map(|i| i.input.clone()).collect();
+
+        TxBuilderState::new()
+            .set_payload(&self.payload)
+            .set_ios(&inputs, &self.outputs)
+            .get_auth_data_for_witness()
+            .hash()
+    }
+
+    /// get the witness of the input at the given index, if already signed
+    pub fn witness(&self, index: usize) -> Option<&Witness> {
+        self.witnesses.get(index).and_then(Option::as_ref)
+    }
+
+    /// the index of the inputs that are not signed yet
+    pub fn missing_witnesses(&self) -> impl Iterator<Item = usize> + '_ {
+        self.witnesses
+            .iter()
+            .enumerate()
+            .filter(|(_, witness)| witness.is_none())
+            .map(|(index, _)| index)
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.witnesses.iter().all(Option::is_some)
+    }
+
+    /// set the witness of the input at the given index, replacing the
+    /// previous one if any
+    ///
+    /// # Errors
+    ///
+    /// * `UnsignedTransactionError::InvalidIndex` if there is no such input;
+    /// * `UnsignedTransactionError::InvalidWitnessKind` if the witness is
+    ///   not of the kind expected by the input;
+    ///
+    pub fn add_witness(
+        &mut self,
+        index: usize,
+        witness: Witness,
+    ) -> Result<(), UnsignedTransactionError> {
+        let input = self
+            .inputs
+            .get(index)
+            .ok_or(UnsignedTransactionError::InvalidIndex(index))?;
+
+        if !input.accepts(&witness) {
+            return Err(UnsignedTransactionError::InvalidWitnessKind(index));
+        }
+
+        self.witnesses[index] = Some(witness);
+        Ok(())
+    }
+
+    /// sign the input at the given index with the given witness builder
+    pub fn sign<B>(
+        &mut self,
+        index: usize,
+        witness_builder: &B,
+    ) -> Result<(), UnsignedTransactionError>
+    where
+        B: WitnessBuilder + ?Sized,
+    {
+        let witness = witness_builder.build(&self.block0, &self.sign_data_hash());
+        self.add_witness(index, witness)
+    }
+
+    /// the requests to send to the signers for the inputs that are not
+    /// signed yet, with the index of the input
+    ///
+    /// The witnesses can be added with `add_witness` as they are received,
+    /// in any order.
+    pub fn sign_requests(&self) -> Vec<(usize, SignRequest)> {
+        let sign_data_hash = self.sign_data_hash();
+        self.missing_witnesses()
+            .map(|index| {
+                let input = &self.inputs[index];
+                let request = SignRequest::new(
+                    self.block0,
+                    sign_data_hash.clone(),
+                    input.key.clone(),
+                    input.witness_kind,
+                );
+                (index, request)
+            })
+            .collect()
+    }
+
+    /// sign all the inputs that are not signed yet with the given signer
+    ///
+    /// The inputs signed before an error are kept signed.
+    pub fn sign_with(&mut self, signer: &dyn Signer) -> Result<(), SignerError> {
+        for (index, request) in self.sign_requests() {
+            let witness = signer.sign(&request)?;
+            if !self.inputs[index].accepts(&witness) {
+                return Err(SignerError::Failed(
+                    UnsignedTransactionError::InvalidWitnessKind(index).into(),
+                ));
+            }
+            self.witnesses[index] = Some(witness);
+        }
+        Ok(())
+    }
+
+    /// build the final transaction, all the inputs need to be signed
+    pub fn finalize(
+        self,
+        auth: <P as Payload>::Auth,
+    ) -> Result<Transaction<P>, UnsignedTransactionError> {
+        self.finalize_with(|_| auth)
+    }
+
+    /// same as `finalize` but the payload's authentication is built from
+    /// the transaction's binding data
+    pub fn finalize_with<F>(self, auth: F) -> Result<Transaction<P>, UnsignedTransactionError>
+    where
+        F: FnOnce(&TransactionBindingAuthData<'_>) -> <P as Payload>::Auth,
+    {
+        if let Some(index) = self.missing_witnesses().next() {
+            return Err(UnsignedTransactionError::MissingWitness(index));
+        }
+
+        let inputs: Vec<Input> = self.inputs.iter().map(|i| i.input.clone()).collect();
+        let witnesses: Vec<Witness> = self.witnesses.into_iter().map(Option::unwrap).collect();
+
+        let builder = TxBuilderState::new()
+            .set_payload(&self.payload)
+            .set_ios(&inputs, &self.outputs)
+            .set_witnesses(&witnesses);
+
+        let auth = auth(&builder.get_auth_data());
+        Ok(builder.set_payload_auth(&auth))
+    }
+}
+
+impl<P: Payload + Serialize> UnsignedTransaction<P> {
+    /// serialize the unsigned transaction with the witnesses already added
+    ///
+    /// Use `UnsignedTransaction::import` to read it back.
+    pub fn export(&self) -> Vec<u8> {
+        let mut writer = Writer::new(Kind::UnsignedTransaction);
+        writer.fragment_id(&self.block0);
+
+        let payload = self
+            .payload
+            .serialize_as_vec()
+            .expect("in memory serialization");
+        writer.count(payload.len());
+        writer.bytes(&payload);
+
+        writer.count(self.inputs.len());
+        for input in self.inputs.iter() {
+            match input.input.to_enum() {
+                InputEnum::UtxoInput(utxo) => {
+                    writer.u8(0);
+                    writer.utxo(&utxo);
+                }
+                InputEnum::AccountInput(id, value) => {
+                    writer.u8(1);
+                    writer.bytes(id.as_ref());
+                    writer.u64(value.0);
+                }
+            }
+
+            match &input.key {
+                KeyId::Path(path) => {
+                    writer.u8(0);
+                    writer.path(path);
+                }
+                KeyId::PublicKey(key) => {
+                    writer.u8(1);
+                    writer.count(key.len());
+                    writer.bytes(key);
+                }
+            }
+
+            match input.witness_kind {
+                WitnessKind::OldUtxo => writer.u8(0),
+                WitnessKind::Utxo => writer.u8(1),
+                WitnessKind::Account { spending_counter } => {
+                    writer.u8(2);
+                    writer.u32(spending_counter);
+                }
+            }
+        }
+
+        writer.count(self.outputs.len());
+        for output in self.outputs.iter() {
+            let address = output.address.to_bytes();
+            writer.count(address.len());
+            writer.bytes(&address);
+            writer.u64(output.value.0);
+        }
+
+        for witness in self.witnesses.iter() {
+            match witness {
+                None => writer.u8(0),
+                Some(witness) => {
+                    let witness = witness.serialize_as_vec().expect("in memory serialization");
+                    writer.u8(1);
+                    writer.count(witness.len());
+                    writer.bytes(&witness);
+                }
+            }
+        }
+
+        writer.finalize()
+    }
+
+    /// read an unsigned transaction serialized with `UnsignedTransaction::export`
+    ///
+    /// The inputs are checked to cover the outputs but the fee is not
+    /// checked against the fee settings, the inspection API can be used to
+    /// review the transaction before signing it.
+    pub fn import(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        let mut reader = Reader::new(bytes, Kind::UnsignedTransaction)?;
+        let block0 = reader.fragment_id()?;
+
+        let len = reader.count()?;
+        let payload = P::read(&mut ReadBuf::from(reader.bytes(len)?))
+            .map_err(|_| PersistenceError::Invalid("payload"))?;
+
+        let mut inputs = Vec::new();
+        for _ in 0..reader.count()? {
+            let input = match reader.u8()? {
+                0 => Input::from_utxo(reader.utxo()?),
+                1 => {
+                    let id: [u8; 32] = reader.bytes(32)?.try_into().unwrap();
+                    let value = Value(reader.u64()?);
+                    Input::from_enum(InputEnum::AccountInput(
+                        UnspecifiedAccountIdentifier::from(id),
+                        value,
+                    ))
+                }
+                _ => return Err(PersistenceError::Invalid("input")),
+            };
+
+            let key = match reader.u8()? {
+                0 => KeyId::Path(reader.any_path()?),
+                1 => {
+                    let len = reader.count()?;
+                    KeyId::PublicKey(reader.bytes(len)?.to_vec())
+                }
+                _ => return Err(PersistenceError::Invalid("key identifier")),
+            };
+
+            let witness_kind = match reader.u8()? {
+                0 => WitnessKind::OldUtxo,
+                1 => WitnessKind::Utxo,
+                2 => WitnessKind::Account {
+                    spending_counter: reader.u32()?,
+                },
+                _ => return Err(PersistenceError::Invalid("witness kind")),
+            };
+
+            inputs.push(UnsignedInput::new(input, key, witness_kind));
+        }
+
+        let mut outputs = Vec::new();
+        for _ in 0..reader.count()? {
+            let len = reader.count()?;
+            let address = Address::from_bytes(reader.bytes(len)?)
+                .map_err(|_| PersistenceError::Invalid("address"))?;
+            let value = Value(reader.u64()?);
+            outputs.push(Output::from_address(address, value));
+        }
+
+        if inputs.len() > 255 || outputs.len() > 255 || left_over(&inputs, &outputs).is_none() {
+            return Err(PersistenceError::Invalid("balance"));
+        }
+
+        let mut witnesses = Vec::with_capacity(inputs.len());
+        for input in inputs.iter() {
+            let witness = match reader.u8()? {
+                0 => None,
+                1 => {
+                    let len = reader.count()?;
+                    let witness = Witness::read(&mut ReadBuf::from(reader.bytes(len)?))
+                        .map_err(|_| PersistenceError::Invalid("witness"))?;
+                    if !input.accepts(&witness) {
+                        return Err(PersistenceError::Invalid("witness"));
+                    }
+                    Some(witness)
+                }
+                _ => return Err(PersistenceError::Invalid("witness")),
+            };
+            witnesses.push(witness);
+        }
+
+        reader.finalize()?;
+
+        Ok(Self {
+            block0,
+            payload,
+            inputs,
+            outputs,
+            witnesses,
+        })
+    }
+}
+
+/// the value of the inputs left over once the outputs are paid, the fee of
+/// a balanced transaction. `None` if the inputs do not cover the outputs.
+fn left_over(inputs: &[UnsignedInput], outputs: &[Output<Address>]) -> Option<Value> {
+    let inputs_value = inputs
+        .iter()
+        .try_fold(Value::zero(), |total, input| {
+            total.checked_add(input.value())
+        })
+        .ok()?;
+    let outputs_value = outputs
+        .iter()
+        .try_fold(Value::zero(), |total, output| {
+            total.checked_add(output.value)
+        })
+        .ok()?;
+
+    inputs_value.checked_sub(outputs_value).ok()
+}
@@ -6,6 +6,7 @@ use chain_impl_mockchain::{
 };
 use ed25519_bip32::XPrv;
 
+use crate::store::DelegatedKey;
 use hdkeygen::Key;
 
 pub trait WitnessBuilder {
@@ -57,6 +58,12 @@ impl WitnessBuilder for UtxoWitnessBuilder<SecretKey<Ed25519Extended>> {
     }
 }
 
+impl WitnessBuilder for UtxoWitnessBuilder<DelegatedKey> {
+    fn build(&self, block0: &HeaderId, sign_data_hash: &TransactionSignDataHash) -> Witness {
+        UtxoWitnessBuilder(self.0.secret_key().clone()).build(block0, sign_data_hash)
+    }
+}
+
 impl WitnessBuilder for AccountWitnessBuilder {
     fn build(&self, block0: &HeaderId, sign_data_hash: &TransactionSignDataHash) -> Witness {
         match self {
@@ -2,13 +2,24 @@ mod utils;
 
 use self::utils::State;
 use chain_impl_mockchain::{
-    certificate::VoteCast,
+    account::DelegationType,
+    certificate::{PoolId, VoteCast},
+    fee::FeeAlgorithm as _,
     fragment::Fragment,
+    transaction::{NoExtra, Output},
     value::Value,
     vote::{Choice, Payload},
 };
 use std::convert::TryInto;
-use wallet::{transaction::dump_icarus_utxo, RecoveryBuilder};
+use wallet::{
+    delegation_type,
+    transaction::{
+        dump_icarus_utxo, KeyId, UnsignedInput, UnsignedTransaction, UnsignedTransactionError,
+        WitnessKind,
+    },
+    Direction, PaymentError, PersistenceError, RecoveryBuilder, SpendingCounterError, SyncStatus,
+    TransactionStatus,
+};
 
 const BLOCK0: &[u8] = include_bytes!("../../test-vectors/block0");
 const MNEMONICS: &str =
@@ -53,6 +64,63 @@ fn dump_from_icarus() {
     assert_ne!(account.confirmed_value(), Value::zero());
 }
 
+#[test]
+fn history_of_the_dump() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+
+    let mut yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+
+    let mut account = wallet.build_wallet().expect("recover account");
+
+    let state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+
+    assert!(
+        yoroi.check_fragments(state.initial_contents()),
+        "failed to check fragments"
+    );
+    assert!(!yoroi.history().is_empty());
+
+    let (fragment, _ignored) = dump_icarus_utxo(&settings, &address, &mut yoroi)
+        .next()
+        .expect("expected only one transaction");
+    let id = fragment.hash();
+
+    assert!(account.check_fragment(&id, &fragment));
+
+    let spent = yoroi
+        .history()
+        .get(&id)
+        .expect("the dump spends the yoroi UTxOs");
+    assert_eq!(spent.direction(), Direction::Outgoing);
+    assert!(spent.fee().is_some());
+    assert_eq!(spent.status(), TransactionStatus::Pending);
+
+    let received = account
+        .history()
+        .get(&id)
+        .expect("the dump is received on the account");
+    assert_eq!(received.direction(), Direction::Incoming);
+    assert_eq!(received.fee(), None);
+    assert_eq!(received.net_value(), account.value());
+
+    // the funds moved from the yoroi wallet to the account
+    let mut merged = spent.clone();
+    merged.merge(received);
+    assert_eq!(merged.direction(), Direction::Internal);
+
+    account.confirm(&id);
+    assert_eq!(
+        account.history().get(&id).unwrap().status(),
+        TransactionStatus::Confirmed
+    );
+}
+
 #[test]
 fn update_state_overrides_old() {
     let wallet = RecoveryBuilder::new()
@@ -137,3 +205,571 @@ fn cast_vote() {
         .apply_fragments(&[raw])
         .expect("couldn't apply votecast fragment");
 }
+
+#[test]
+fn send_to_address() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+
+    let mut yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+
+    let mut account = wallet.build_wallet().expect("recover account");
+    let mut receiver = wallet::Wallet::new_from_seed([1; 32]);
+
+    let mut state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+    let receiver_address = receiver.account_id().address(settings.discrimination());
+
+    assert!(
+        yoroi.check_fragments(state.initial_contents()),
+        "failed to check fragments"
+    );
+
+    let (fragment, _ignored) = dump_icarus_utxo(&settings, &address, &mut yoroi)
+        .next()
+        .expect("expected only one transaction");
+
+    assert!(account.check_fragment(&fragment.hash(), &fragment));
+
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("the dump fragments should be valid");
+
+    account.confirm(&fragment.hash());
+
+    let initial_value = account.confirmed_value();
+
+    assert!(matches!(
+        account.send_to(
+            &settings,
+            receiver_address.clone(),
+            initial_value.saturating_add(Value(1))
+        ),
+        Err(PaymentError::NotEnoughFunds { .. })
+    ));
+
+    let fragment = account
+        .send_to(&settings, receiver_address, Value(1_000))
+        .expect("enough funds to send");
+
+    assert!(account
+        .pending_transactions()
+        .any(|id| *id == fragment.hash()));
+    assert!(account.unconfirmed_value().unwrap() < initial_value);
+    let entry = account.history().get(&fragment.hash()).unwrap();
+    assert_eq!(entry.inputs().len(), 1);
+    assert!(entry.fee().is_some());
+
+    assert!(receiver.check_fragment(&fragment.hash(), &fragment));
+    assert_eq!(receiver.unconfirmed_value(), Some(Value(1_000)));
+
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("couldn't apply the payment fragment");
+}
+
+#[test]
+fn delegate_to_a_pool() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+
+    let mut yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+
+    let mut account = wallet.build_wallet().expect("recover account");
+
+    let mut state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+
+    // the pool registered in the block0
+    let pool = state
+        .initial_contents()
+        .find_map(|fragment| match fragment {
+            Fragment::PoolRegistration(tx) => Some(tx.as_slice().payload().into_payload().to_id()),
+            _ => None,
+        })
+        .expect("a pool is registered in the block0");
+
+    assert!(yoroi.check_fragments(state.initial_contents()));
+
+    let (fragment, _ignored) = dump_icarus_utxo(&settings, &address, &mut yoroi)
+        .next()
+        .expect("expected only one transaction");
+
+    assert!(account.check_fragment(&fragment.hash(), &fragment));
+
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("the dump fragments should be valid");
+
+    account.confirm(&fragment.hash());
+
+    let fragment = account
+        .owner_stake_delegation(&settings, &[(pool.clone(), 1)])
+        .expect("enough funds to delegate");
+    assert!(matches!(fragment, Fragment::OwnerStakeDelegation(_)));
+
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("couldn't apply the owner stake delegation fragment");
+
+    account.confirm(&fragment.hash());
+
+    let fragment = account
+        .stake_delegation(&settings, &[(pool, 1)])
+        .expect("enough funds to delegate");
+    assert!(matches!(fragment, Fragment::StakeDelegation(_)));
+
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("couldn't apply the stake delegation fragment");
+
+    account.confirm(&fragment.hash());
+
+    let fragment = account
+        .stake_delegation(&settings, &[])
+        .expect("enough funds to remove the delegation");
+
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("couldn't apply the fragment removing the delegation");
+}
+
+#[test]
+fn delegation_ratios() {
+    let pool1 = PoolId::from([1; 32]);
+    let pool2 = PoolId::from([2; 32]);
+
+    assert_eq!(delegation_type(&[]).unwrap(), DelegationType::NonDelegated);
+    assert_eq!(
+        delegation_type(&[(pool1.clone(), 0)]).unwrap(),
+        DelegationType::Full(pool1.clone())
+    );
+
+    match delegation_type(&[(pool1.clone(), 1), (pool2.clone(), 3)]).unwrap() {
+        DelegationType::Ratio(ratio) => {
+            assert_eq!(ratio.parts(), 4);
+            assert_eq!(ratio.pools(), &[(pool1.clone(), 1), (pool2.clone(), 3)][..]);
+        }
+        delegation => panic!("unexpected delegation {:?}", delegation),
+    }
+
+    assert!(matches!(
+        delegation_type(&[(pool1.clone(), 1), (pool2.clone(), 0)]),
+        Err(PaymentError::InvalidDelegation(_))
+    ));
+    assert!(matches!(
+        delegation_type(&[(pool1, 200), (pool2, 56)]),
+        Err(PaymentError::InvalidDelegation(_))
+    ));
+}
+
+#[test]
+fn remove_rejected_payment() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+
+    let mut account = wallet.build_wallet().expect("recover account");
+    let receiver = wallet::Wallet::new_from_seed([1; 32]);
+
+    let state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let receiver_address = receiver.account_id().address(settings.discrimination());
+
+    account.update_state(Value(100_000), 0);
+
+    let first = account
+        .send_to(&settings, receiver_address.clone(), Value(1_000))
+        .expect("enough funds to send");
+    let value_after_first = account.unconfirmed_value().unwrap();
+    let second = account
+        .send_to(&settings, receiver_address, Value(2_000))
+        .expect("enough funds to send");
+
+    let inputs = account
+        .remove_pending_transaction(&second.hash())
+        .expect("the second payment is pending");
+
+    assert_eq!(inputs.len(), 1);
+    assert!(account.pending_transactions().eq(vec![&first.hash()]));
+    assert_eq!(account.unconfirmed_value(), Some(value_after_first));
+
+    assert!(account.remove_pending_transaction(&second.hash()).is_none());
+    assert!(account.remove_pending_transaction(&first.hash()).is_some());
+    assert_eq!(account.unconfirmed_value(), None);
+}
+
+#[test]
+fn remove_rejected_payment_with_the_following_ones() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+
+    let mut account = wallet.build_wallet().expect("recover account");
+    let receiver = wallet::Wallet::new_from_seed([1; 32]);
+
+    let state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let block0 = settings.static_parameters.block0_initial_hash;
+    let receiver_address = receiver.account_id().address(settings.discrimination());
+
+    account.update_state(Value(100_000), 0);
+
+    let first = account
+        .send_to(&settings, receiver_address.clone(), Value(1_000))
+        .expect("enough funds to send");
+    let value_after_first = account.unconfirmed_value().unwrap();
+    let second = account
+        .send_to(&settings, receiver_address.clone(), Value(2_000))
+        .expect("enough funds to send");
+    let value_after_second = account.unconfirmed_value().unwrap();
+
+    let inputs = account
+        .remove_pending_transaction(&first.hash())
+        .expect("the first payment is pending");
+
+    // the second payment was signed with the spending counter following the
+    // first one's, the node rejects it as well
+    assert_eq!(
+        inputs.iter().map(|input| input.value()).collect::<Vec<_>>(),
+        vec![
+            Value(100_000).checked_sub(value_after_first).unwrap(),
+            value_after_first.checked_sub(value_after_second).unwrap(),
+        ]
+    );
+    assert!(account.pending_transactions().next().is_none());
+    assert_eq!(account.unconfirmed_value(), None);
+    for fragment in [&first, &second].iter() {
+        assert_eq!(
+            account.history().get(&fragment.hash()).unwrap().status(),
+            TransactionStatus::Rejected
+        );
+    }
+
+    // the next payment uses the spending counter of the rejected one
+    let third = account
+        .send_to(&settings, receiver_address, Value(3_000))
+        .expect("enough funds to send");
+
+    let mut node = wallet.build_wallet().expect("recover account");
+    node.update_state(Value(100_000), 0);
+    assert_eq!(
+        node.verify_fragment(&block0, &third.hash(), &third),
+        Ok(true)
+    );
+}
+
+#[test]
+fn export_import_keeps_pending_payments() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+
+    let mut account = wallet.build_wallet().expect("recover account");
+    let receiver = wallet::Wallet::new_from_seed([1; 32]);
+
+    let state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let receiver_address = receiver.account_id().address(settings.discrimination());
+
+    account.update_state(Value(100_000), 0);
+
+    let payment = account
+        .send_to(&settings, receiver_address.clone(), Value(1_000))
+        .expect("enough funds to send");
+
+    let mut imported = wallet::Wallet::import(&account.export()).expect("valid exported wallet");
+
+    assert_eq!(imported.account_id(), account.account_id());
+    assert_eq!(imported.confirmed_value(), account.confirmed_value());
+    assert_eq!(imported.unconfirmed_value(), account.unconfirmed_value());
+    assert!(imported.pending_transactions().eq(vec![&payment.hash()]));
+
+    // the spending counter is kept so the next payment is valid
+    let next = imported
+        .send_to(&settings, receiver_address.clone(), Value(1_000))
+        .expect("enough funds to send");
+    let expected = account
+        .send_to(&settings, receiver_address, Value(1_000))
+        .expect("enough funds to send");
+    assert_eq!(next.hash(), expected.hash());
+
+    assert!(wallet::Wallet::import(&[]).is_err());
+}
+
+#[test]
+fn verify_spending_counters() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+
+    let mut sender = wallet.build_wallet().expect("recover account");
+    let mut receiver = wallet::Wallet::new_from_seed([1; 32]);
+
+    let state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let block0 = settings.static_parameters.block0_initial_hash;
+    let receiver_address = receiver.account_id().address(settings.discrimination());
+
+    sender.update_state(Value(100_000), 0);
+
+    let first = sender
+        .send_to(&settings, receiver_address.clone(), Value(1_000))
+        .expect("enough funds to send");
+    let second = sender
+        .send_to(&settings, receiver_address, Value(2_000))
+        .expect("enough funds to send");
+
+    // the same account, not aware of the payments
+    let mut account = wallet.build_wallet().expect("recover account");
+    account.update_state(Value(100_000), 0);
+
+    assert_eq!(
+        account.verify_fragment(&block0, &second.hash(), &second),
+        Err(SpendingCounterError::Gap {
+            expected: 0,
+            used: 1
+        })
+    );
+    assert!(account.pending_transactions().next().is_none());
+
+    assert_eq!(
+        account.verify_fragment(&block0, &first.hash(), &first),
+        Ok(true)
+    );
+    assert_eq!(
+        account.verify_fragment(&block0, &second.hash(), &second),
+        Ok(true)
+    );
+    assert_eq!(account.unconfirmed_value(), sender.unconfirmed_value());
+
+    // the payments do not use the receiver's spending counter
+    receiver.update_state(Value(0), 0);
+    assert_eq!(
+        receiver.verify_fragment(&block0, &first.hash(), &first),
+        Ok(true)
+    );
+    assert_eq!(receiver.unconfirmed_value(), Some(Value(1_000)));
+
+    let mut ahead = wallet.build_wallet().expect("recover account");
+    ahead.update_state(Value(100_000), 2);
+    assert_eq!(
+        ahead.verify_fragment(&block0, &first.hash(), &first),
+        Err(SpendingCounterError::Duplicate {
+            expected: 2,
+            used: 0
+        })
+    );
+}
+
+#[test]
+fn reconcile_with_the_node() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+
+    let mut account = wallet.build_wallet().expect("recover account");
+    let receiver = wallet::Wallet::new_from_seed([1; 32]);
+
+    let state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let receiver_address = receiver.account_id().address(settings.discrimination());
+
+    account.update_state(Value(100_000), 0);
+
+    let first = account
+        .send_to(&settings, receiver_address.clone(), Value(1_000))
+        .expect("enough funds to send");
+    let value_after_first = account.unconfirmed_value().unwrap();
+    let second = account
+        .send_to(&settings, receiver_address, Value(2_000))
+        .expect("enough funds to send");
+    let value_after_second = account.unconfirmed_value().unwrap();
+
+    // the node did not apply any of the payments yet
+    let reconciliation = account.reconcile_state(Value(100_000), 0);
+    assert_eq!(reconciliation.status(), SyncStatus::Consistent);
+    assert!(reconciliation.dropped().is_empty());
+    assert!(reconciliation.confirmed().is_empty());
+    assert!(account
+        .pending_transactions()
+        .eq(vec![&first.hash(), &second.hash()]));
+    assert_eq!(account.unconfirmed_value(), Some(value_after_second));
+
+    // the node applied the first payment
+    let reconciliation = account.reconcile_state(value_after_first, 1);
+    assert_eq!(reconciliation.status(), SyncStatus::Ahead);
+    assert_eq!(reconciliation.dropped(), &[first.hash()]);
+    assert!(account.pending_transactions().eq(vec![&second.hash()]));
+    assert_eq!(account.confirmed_value(), value_after_first);
+    assert_eq!(account.unconfirmed_value(), Some(value_after_second));
+    assert_eq!(
+        account.history().get(&first.hash()).unwrap().status(),
+        TransactionStatus::Dropped
+    );
+    assert_eq!(
+        account.history().get(&second.hash()).unwrap().status(),
+        TransactionStatus::Pending
+    );
+
+    // the node rolled back the first payment, the second one is still in
+    // the future
+    let reconciliation = account.reconcile_state(Value(100_000), 0);
+    assert_eq!(reconciliation.status(), SyncStatus::Behind);
+    assert!(reconciliation.dropped().is_empty());
+    assert!(account.pending_transactions().eq(vec![&second.hash()]));
+
+    // the first payment is found on chain after all
+    account.confirm(&first.hash());
+    assert_eq!(
+        account.history().get(&first.hash()).unwrap().status(),
+        TransactionStatus::Confirmed
+    );
+}
+
+#[test]
+fn reconcile_a_transaction_confirmed_out_of_order() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+
+    let mut account = wallet.build_wallet().expect("recover account");
+    let receiver = wallet::Wallet::new_from_seed([1; 32]);
+
+    let state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let receiver_address = receiver.account_id().address(settings.discrimination());
+
+    account.update_state(Value(100_000), 0);
+
+    let first = account
+        .send_to(&settings, receiver_address.clone(), Value(1_000))
+        .expect("enough funds to send");
+    let second = account
+        .send_to(&settings, receiver_address.clone(), Value(2_000))
+        .expect("enough funds to send");
+    let value_after_second = account.unconfirmed_value().unwrap();
+    let third = account
+        .send_to(&settings, receiver_address, Value(3_000))
+        .expect("enough funds to send");
+    let value_after_third = account.unconfirmed_value().unwrap();
+
+    // the second payment is confirmed while the first one is still pending
+    account.confirm(&second.hash());
+    assert!(account
+        .pending_transactions()
+        .eq(vec![&first.hash(), &third.hash()]));
+
+    // the node applied the first and the second payments
+    let reconciliation = account.reconcile_state(value_after_second, 2);
+    assert_eq!(reconciliation.status(), SyncStatus::Ahead);
+    assert_eq!(reconciliation.dropped(), &[first.hash()]);
+    assert_eq!(reconciliation.confirmed(), &[second.hash()]);
+    assert!(account.pending_transactions().eq(vec![&third.hash()]));
+    assert_eq!(account.confirmed_value(), value_after_second);
+    assert_eq!(account.unconfirmed_value(), Some(value_after_third));
+    assert_eq!(
+        account.history().get(&second.hash()).unwrap().status(),
+        TransactionStatus::Confirmed
+    );
+}
+
+#[test]
+fn sign_unsigned_transaction_offline() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+
+    let mut account = wallet.build_wallet().expect("recover account");
+    let receiver = wallet::Wallet::new_from_seed([1; 32]);
+
+    let state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let receiver_address = receiver.account_id().address(settings.discrimination());
+
+    account.update_state(Value(100_000), 0);
+
+    let fee = settings.parameters.fees.calculate(None, 1, 1);
+    let needed = Value(1_000).checked_add(fee).unwrap();
+    let account_id = account.account_id();
+    let account_tx = account.new_transaction(needed);
+    let witness_builder = account_tx.witness_builder();
+
+    // the watch-only side
+    let unsigned = UnsignedTransaction::new(
+        &settings,
+        NoExtra,
+        vec![UnsignedInput::new(
+            account_tx.input(),
+            KeyId::PublicKey(account_id.as_ref().to_vec()),
+            WitnessKind::Account {
+                spending_counter: 0,
+            },
+        )],
+        vec![Output::from_address(receiver_address.clone(), Value(1_000))],
+    )
+    .expect("balanced transaction");
+    assert_eq!(unsigned.fee(), fee);
+    assert!(UnsignedTransaction::<NoExtra>::new(
+        &settings,
+        NoExtra,
+        unsigned.inputs().to_vec(),
+        vec![Output::from_address(receiver_address.clone(), Value(2_000))],
+    )
+    .is_err());
+
+    // the outputs of an exported transaction worth more than its inputs
+    let mut unbalanced = unsigned.export();
+    let value = unbalanced.len() - 1 - 8;
+    unbalanced[value..value + 8].copy_from_slice(&u64::MAX.to_be_bytes());
+    assert!(matches!(
+        UnsignedTransaction::<NoExtra>::import(&unbalanced),
+        Err(PersistenceError::Invalid(_))
+    ));
+
+    // the signing side
+    let mut to_sign =
+        UnsignedTransaction::<NoExtra>::import(&unsigned.export()).expect("valid unsigned tx");
+    assert_eq!(to_sign.block0(), unsigned.block0());
+    assert_eq!(to_sign.sign_data_hash(), unsigned.sign_data_hash());
+    assert_eq!(
+        to_sign.inputs()[0].key(),
+        &KeyId::PublicKey(account_id.as_ref().to_vec())
+    );
+    assert!(to_sign.missing_witnesses().eq(vec![0]));
+    assert!(matches!(
+        to_sign.sign(1, &witness_builder),
+        Err(UnsignedTransactionError::InvalidIndex(1))
+    ));
+    to_sign.sign(0, &witness_builder).expect("valid witness");
+    assert!(to_sign.is_signed());
+
+    // the account's input signed by the software signer
+    let signer = wallet
+        .build_software_signer()
+        .expect("recover the software signer");
+    let mut signed_by_signer =
+        UnsignedTransaction::<NoExtra>::import(&unsigned.export()).expect("valid unsigned tx");
+    signed_by_signer
+        .sign_with(&signer)
+        .expect("sign the account's input");
+    assert_eq!(signed_by_signer.export(), to_sign.export());
+
+    // the assembling side
+    let signed =
+        UnsignedTransaction::<NoExtra>::import(&to_sign.export()).expect("valid signed tx");
+    let fragment = Fragment::Transaction(signed.finalize(()).expect("all the inputs are signed"));
+
+    let expected = account
+        .send_to(&settings, receiver_address, Value(1_000))
+        .expect("enough funds to send");
+    assert_eq!(fragment.hash(), expected.hash());
+}
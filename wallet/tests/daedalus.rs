@@ -3,7 +3,12 @@ const BLOCK0: &[u8] = include_bytes!("../../test-vectors/block0");
 mod utils;
 
 use self::utils::State;
-use chain_impl_mockchain::value::Value;
+use chain_impl_mockchain::{
+    fragment::Fragment,
+    legacy::{OldAddress, UtxoDeclaration},
+    value::Value,
+};
+use rand_chacha::{rand_core::SeedableRng as _, ChaChaRng};
 use wallet::{transaction::dump_daedalus_utxo, RecoveryBuilder};
 
 /// test to recover a daedalus style address in the test-vectors block0
@@ -81,3 +86,81 @@ fn daedalus_wallet2() {
         .apply_fragments(&[fragment.to_raw()])
         .expect("the dump fragments should be valid");
 }
+
+#[test]
+fn export_import_daedalus_wallet() {
+    const MNEMONICS: &str =
+        "tired owner misery large dream glad upset welcome shuffle eagle pulp time";
+    const WALLET_VALUE: Value = Value(100_000 + 1010);
+
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+    let mut daedalus = wallet
+        .build_daedalus()
+        .expect("recover a Legacy/Daedalus wallet");
+    let account = wallet.build_wallet().expect("recover account");
+
+    let mut state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+
+    assert!(daedalus.check_fragments(state.initial_contents()));
+
+    let mut imported =
+        wallet::scheme::rindex::Wallet::import(&daedalus.export()).expect("valid exported wallet");
+
+    assert_eq!(imported.unconfirmed_value(), Some(WALLET_VALUE));
+    assert!(imported
+        .pending_transactions()
+        .eq(daedalus.pending_transactions()));
+
+    // the keys of the UTxOs are derived again and can sign the dump
+    let (fragment, _) = dump_daedalus_utxo(&settings, &address, &mut imported)
+        .next()
+        .unwrap();
+
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("the dump fragments should be valid");
+}
+
+#[test]
+fn daedalus_new_addresses() {
+    const MNEMONICS: &str =
+        "tired owner misery large dream glad upset welcome shuffle eagle pulp time";
+    const OTHER_MNEMONICS: &str =
+        "edge club wrap where juice nephew whip entry cover bullet cause jeans";
+
+    let mut daedalus = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics")
+        .build_daedalus()
+        .expect("recover a Legacy/Daedalus wallet");
+    let other = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, OTHER_MNEMONICS)
+        .expect("valid mnemonics")
+        .build_daedalus()
+        .expect("recover a Legacy/Daedalus wallet");
+
+    let mut random = ChaChaRng::from_seed([0; 32]);
+    let addresses: Vec<OldAddress> = (0..10).map(|_| daedalus.new_address(&mut random)).collect();
+
+    for (index, address) in addresses.iter().enumerate() {
+        assert!(daedalus.check_address(address));
+        assert!(!other.check_address(address));
+        assert!(!addresses[..index].contains(address));
+    }
+
+    let fragment = Fragment::OldUtxoDeclaration(UtxoDeclaration {
+        addrs: vec![(addresses[0].clone(), Value(1_000))],
+    });
+    assert!(daedalus.check_fragment(&fragment.hash(), &fragment));
+    assert_eq!(daedalus.unconfirmed_value(), Some(Value(1_000)));
+
+    let imported =
+        wallet::scheme::rindex::Wallet::import(&daedalus.export()).expect("import the wallet");
+    assert!(addresses
+        .iter()
+        .all(|address| imported.check_address(address)));
+}
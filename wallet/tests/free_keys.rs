@@ -1,15 +1,20 @@
 mod utils;
 
 use self::utils::State;
-use chain_crypto::{bech32::Bech32, SecretKey};
+use chain_addr::{Address, Kind};
+use chain_crypto::{bech32::Bech32, Ed25519Extended, SecretKey};
 use chain_impl_mockchain::{
     certificate::VoteCast,
     fragment::Fragment,
+    transaction::{NoExtra, Output},
     value::Value,
     vote::{Choice, Payload},
 };
 use std::convert::TryInto;
-use wallet::transaction::dump_free_utxo;
+use wallet::{
+    transaction::{dump_free_utxo, select_inputs, UtxoWitnessBuilder, DEFAULT_STRATEGIES},
+    TransactionBuilder,
+};
 
 const BLOCK0: &[u8] = include_bytes!("../../test-vectors/block0");
 const ACCOUNT: &str = include_str!("../../test-vectors/free_keys/key1.prv");
@@ -128,3 +133,227 @@ fn cast_vote() {
         .apply_fragments(&[raw])
         .expect("couldn't apply votecast fragment");
 }
+
+#[test]
+fn export_import_free_keys() {
+    let builder = wallet::RecoveryBuilder::new();
+
+    let builder = builder
+        .account_secret_key(SecretKey::try_from_bech32_str(String::from(ACCOUNT).trim()).unwrap());
+
+    let builder = [UTXO1, UTXO2].iter().fold(builder, |builder, key| {
+        builder.add_key(SecretKey::try_from_bech32_str(String::from(*key).trim()).unwrap())
+    });
+
+    let mut free_keys = builder.build_free_utxos().unwrap();
+
+    let account = builder.build_wallet().unwrap();
+
+    let mut state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+
+    for fragment in state.initial_contents() {
+        free_keys.check_fragment(&fragment.hash(), fragment);
+    }
+
+    let mut imported = wallet::scheme::freeutxo::Wallet::import(&free_keys.export())
+        .expect("valid exported wallet");
+
+    assert_eq!(imported.unconfirmed_value(), Some(WALLET_VALUE));
+
+    let (fragment, ignored) = dump_free_utxo(&settings, &address, &mut imported)
+        .next()
+        .unwrap();
+
+    assert!(ignored.is_empty());
+
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("the dump fragments should be valid");
+}
+
+#[test]
+fn keep_the_delegation_of_group_addresses() {
+    let delegation =
+        SecretKey::<Ed25519Extended>::try_from_bech32_str(String::from(ACCOUNT).trim())
+            .unwrap()
+            .to_public();
+    let utxo1 =
+        SecretKey::<Ed25519Extended>::try_from_bech32_str(String::from(UTXO1).trim()).unwrap();
+
+    let builder = [UTXO1, UTXO2]
+        .iter()
+        .fold(wallet::RecoveryBuilder::new(), |builder, key| {
+            builder.add_key(SecretKey::try_from_bech32_str(String::from(*key).trim()).unwrap())
+        });
+    let mut free_keys = builder.build_free_utxos().unwrap();
+
+    let mut state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let discrimination = settings.discrimination();
+
+    for fragment in state.initial_contents() {
+        free_keys.check_fragment(&fragment.hash(), fragment);
+    }
+    assert!(free_keys
+        .utxos()
+        .groups()
+        .all(|group| group.delegation().is_none()));
+
+    // delegate some of the funds to the account with a group address
+    let group_address = Address(
+        discrimination,
+        Kind::Group(utxo1.to_public(), delegation.clone()),
+    );
+    let mut builder = TransactionBuilder::new(&settings, NoExtra);
+    builder.add_output(Output::from_address(group_address.clone(), Value(5_000)));
+    select_inputs(
+        &mut builder,
+        free_keys.utxos(),
+        DEFAULT_STRATEGIES,
+        &|key| UtxoWitnessBuilder(key),
+        |key| key.address(discrimination),
+    )
+    .expect("enough funds in the wallet");
+    let fragment = Fragment::Transaction(builder.finalize_tx(()).unwrap());
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("the transaction should be valid");
+    assert!(free_keys.check_fragment(&fragment.hash(), &fragment));
+
+    let delegated = free_keys.utxos().filter(|key| key.delegation().is_some());
+    assert_eq!(delegated.total_value(), Value(5_000));
+    assert!(delegated
+        .groups()
+        .all(|group| group.delegation() == Some(&delegation)));
+
+    // spending the delegated UTxOs sends the change to the same group address
+    let address = Address(discrimination, Kind::Single(utxo1.to_public()));
+    let mut builder = TransactionBuilder::new(&settings, NoExtra);
+    builder.add_output(Output::from_address(address, Value(1_000)));
+    let selection = select_inputs(
+        &mut builder,
+        &delegated,
+        DEFAULT_STRATEGIES,
+        &|key| UtxoWitnessBuilder(key),
+        |key| key.address(discrimination),
+    )
+    .expect("enough funds in the delegated UTxOs");
+    assert!(!selection.change().is_empty());
+    assert!(selection
+        .change()
+        .iter()
+        .all(|output| output.address == group_address));
+
+    let fragment = Fragment::Transaction(builder.finalize_tx(()).unwrap());
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("the transaction should be valid");
+    assert!(free_keys.check_fragment(&fragment.hash(), &fragment));
+
+    let change = selection
+        .change()
+        .iter()
+        .fold(Value::zero(), |total, output| {
+            (total + output.value).unwrap()
+        });
+
+    let imported = wallet::scheme::freeutxo::Wallet::import(&free_keys.export())
+        .expect("valid exported wallet");
+    let delegated = imported
+        .utxos()
+        .filter(|key| key.delegation() == Some(&delegation));
+    assert_eq!(delegated.total_value(), change);
+}
+
+#[test]
+fn add_and_remove_keys_at_runtime() {
+    let utxo1 =
+        SecretKey::<Ed25519Extended>::try_from_bech32_str(String::from(UTXO1).trim()).unwrap();
+    let utxo2 =
+        SecretKey::<Ed25519Extended>::try_from_bech32_str(String::from(UTXO2).trim()).unwrap();
+
+    let state = State::new(BLOCK0);
+
+    let mut only_utxo2 = wallet::scheme::freeutxo::Wallet::from_keys(vec![utxo2.clone()]);
+    for fragment in state.initial_contents() {
+        only_utxo2.check_fragment(&fragment.hash(), fragment);
+        only_utxo2.confirm(&fragment.hash());
+    }
+
+    let mut free_keys = wallet::scheme::freeutxo::Wallet::from_keys(vec![utxo1.clone()]);
+    for fragment in state.initial_contents() {
+        free_keys.check_fragment(&fragment.hash(), fragment);
+        free_keys.confirm(&fragment.hash());
+    }
+    assert_eq!(
+        (free_keys.confirmed_value() + only_utxo2.confirmed_value()).unwrap(),
+        WALLET_VALUE
+    );
+
+    // the fragments already checked are rescanned for the UTxOs of the new key
+    assert!(free_keys.add_key(utxo2.clone(), state.initial_contents()));
+    assert!(!free_keys.add_key(utxo2.clone(), state.initial_contents()));
+    assert!(free_keys.contains_key(&utxo2.to_public()));
+    assert_eq!(free_keys.confirmed_value(), WALLET_VALUE);
+    assert_eq!(free_keys.unconfirmed_value(), None);
+    assert!(free_keys
+        .history()
+        .iter()
+        .flat_map(|entry| entry.outputs())
+        .any(|output| only_utxo2.utxos().contains(output)));
+
+    let imported = wallet::scheme::freeutxo::Wallet::import(&free_keys.export())
+        .expect("valid exported wallet");
+    assert_eq!(imported.confirmed_value(), WALLET_VALUE);
+
+    // the UTxOs of a removed key are not in the wallet anymore
+    assert!(free_keys.remove_key(&utxo1.to_public()).is_some());
+    assert!(free_keys.remove_key(&utxo1.to_public()).is_none());
+    assert!(!free_keys.contains_key(&utxo1.to_public()));
+    assert_eq!(free_keys.confirmed_value(), only_utxo2.confirmed_value());
+
+    let imported = wallet::scheme::freeutxo::Wallet::import(&free_keys.export())
+        .expect("valid exported wallet");
+    assert_eq!(imported.confirmed_value(), only_utxo2.confirmed_value());
+}
+
+#[test]
+fn add_key_after_a_block0_ending_without_transaction() {
+    let utxo1 =
+        SecretKey::<Ed25519Extended>::try_from_bech32_str(String::from(UTXO1).trim()).unwrap();
+    let utxo2 =
+        SecretKey::<Ed25519Extended>::try_from_bech32_str(String::from(UTXO2).trim()).unwrap();
+
+    let state = State::new(BLOCK0);
+
+    // the transactions of the block0 followed by its other fragments
+    let (transactions, others): (Vec<&Fragment>, Vec<&Fragment>) = state
+        .initial_contents()
+        .partition(|fragment| matches!(fragment, Fragment::Transaction(_)));
+    assert!(!others.is_empty());
+    let block0: Vec<&Fragment> = transactions.iter().chain(others.iter()).copied().collect();
+
+    let new_wallet = || {
+        let mut free_keys = wallet::scheme::freeutxo::Wallet::from_keys(vec![utxo1.clone()]);
+        for fragment in block0.iter() {
+            free_keys.check_fragment(&fragment.hash(), fragment);
+            free_keys.confirm(&fragment.hash());
+        }
+        free_keys
+    };
+
+    // only the state of the last fragment is kept once confirmed
+    let mut free_keys = new_wallet();
+    assert!(free_keys.add_key(utxo2.clone(), block0.iter().copied()));
+    assert_eq!(free_keys.confirmed_value(), WALLET_VALUE);
+    assert_eq!(free_keys.unconfirmed_value(), None);
+
+    // the UTxOs found in fragments that are not in the states of the wallet
+    // are added to its first state
+    let mut free_keys = new_wallet();
+    assert!(free_keys.add_key(utxo2.clone(), transactions.iter().copied()));
+    assert_eq!(free_keys.confirmed_value(), WALLET_VALUE);
+    assert_eq!(free_keys.unconfirmed_value(), None);
+}
@@ -1,8 +1,22 @@
 mod utils;
 
 use self::utils::State;
-use chain_impl_mockchain::value::Value;
-use wallet::{transaction::dump_icarus_utxo, RecoveryBuilder};
+use chain_crypto::{Ed25519, PublicKey};
+use chain_impl_mockchain::{
+    fragment::Fragment,
+    legacy::{OldAddress, UtxoDeclaration},
+    transaction::{Input, NoExtra, Output},
+    value::Value,
+};
+use chain_path_derivation::HardDerivation;
+use wallet::{
+    scheme::bip44::{AddressError, WatchOnlyWallet, DEFAULT_GAP_LIMIT},
+    transaction::{
+        dump_icarus_account_utxo, dump_icarus_utxo, select_inputs, FinalizeError, InputStrategy,
+        KeyId, OldUtxoWitnessBuilder, SelectionError, WitnessKind, DEFAULT_STRATEGIES,
+    },
+    PersistenceError, RecoveryBuilder, RecoveryError, TransactionBuilder,
+};
 
 const BLOCK0: &[u8] = include_bytes!("../../test-vectors/block0");
 const MNEMONICS: &str =
@@ -42,3 +56,547 @@ fn yoroi1() {
         .apply_fragments(&[fragment.to_raw()])
         .expect("the dump fragments should be valid");
 }
+
+#[test]
+fn select_inputs_with_default_strategies() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+    let mut yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+    let account = wallet.build_wallet().expect("recover account");
+
+    let mut state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+
+    assert!(yoroi.check_fragments(state.initial_contents()));
+
+    let mut builder = TransactionBuilder::new(&settings, NoExtra);
+    builder.add_output(Output::from_address(address.clone(), Value(5_000)));
+
+    let selection = select_inputs(
+        &mut builder,
+        yoroi.utxos(),
+        DEFAULT_STRATEGIES,
+        &|key| OldUtxoWitnessBuilder(key),
+        |_| address.clone(),
+    )
+    .expect("enough funds in the wallet");
+
+    assert_eq!(
+        selection.strategy().input(),
+        InputStrategy::PrivacyPreserving
+    );
+    assert_eq!(selection.inputs().len(), 1);
+    assert!(!selection.change().is_empty());
+
+    let tx = builder.finalize_tx(()).expect("balanced transaction");
+    let fragment = Fragment::Transaction(tx);
+
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("the transaction should be valid");
+}
+
+#[test]
+fn select_inputs_not_enough_funds() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+    let mut yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+    let account = wallet.build_wallet().expect("recover account");
+
+    let state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+
+    assert!(yoroi.check_fragments(state.initial_contents()));
+
+    let mut builder = TransactionBuilder::new(&settings, NoExtra);
+    builder.add_output(Output::from_address(address.clone(), WALLET_VALUE));
+
+    let result = select_inputs(
+        &mut builder,
+        yoroi.utxos(),
+        DEFAULT_STRATEGIES,
+        &|key| OldUtxoWitnessBuilder(key),
+        |_| address.clone(),
+    );
+
+    assert!(matches!(result, Err(SelectionError::NotEnoughFunds { .. })));
+    assert!(builder.inputs().is_empty());
+}
+
+#[test]
+fn remove_rejected_dump() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+    let mut yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+    let account = wallet.build_wallet().expect("recover account");
+
+    let state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+
+    assert!(yoroi.check_fragments(state.initial_contents()));
+    for fragment in state.initial_contents() {
+        yoroi.confirm(&fragment.hash());
+    }
+
+    let (fragment, ignored) = dump_icarus_utxo(&settings, &address, &mut yoroi)
+        .next()
+        .unwrap();
+
+    assert_eq!(yoroi.unconfirmed_value(), Some(Value(1)));
+
+    let inputs = yoroi
+        .remove_pending_transaction(&fragment.hash())
+        .expect("the dump transaction is pending");
+
+    assert_eq!(inputs.len() + ignored.len(), 5);
+    assert_eq!(yoroi.unconfirmed_value(), None);
+    assert_eq!(yoroi.confirmed_value(), WALLET_VALUE);
+}
+
+#[test]
+fn export_import_keeps_pending_dump() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+    let mut yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+    let account = wallet.build_wallet().expect("recover account");
+
+    let state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+
+    assert!(yoroi.check_fragments(state.initial_contents()));
+    for fragment in state.initial_contents() {
+        yoroi.confirm(&fragment.hash());
+    }
+
+    let (fragment, _) = dump_icarus_utxo(&settings, &address, &mut yoroi)
+        .next()
+        .unwrap();
+
+    let mut imported = wallet::scheme::bip44::Wallet::<OldAddress>::import(&yoroi.export())
+        .expect("valid exported wallet");
+
+    assert_eq!(imported.confirmed_value(), WALLET_VALUE);
+    assert_eq!(imported.unconfirmed_value(), Some(Value(1)));
+    assert!(imported.pending_transactions().eq(vec![&fragment.hash()]));
+
+    // the UTxOs and their keys are back once the dump is rejected
+    imported
+        .remove_pending_transaction(&fragment.hash())
+        .expect("the dump transaction is pending");
+    assert_eq!(imported.unconfirmed_value(), None);
+    assert!(dump_icarus_utxo(&settings, &address, &mut imported)
+        .next()
+        .is_some());
+
+    assert!(wallet::scheme::bip44::Wallet::<PublicKey<Ed25519>>::import(&yoroi.export()).is_err());
+}
+
+#[test]
+fn import_forged_address_range() {
+    let yoroi = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics")
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+    let exported = yoroi.export();
+
+    // after the version, the kind, the coin type path and key come the gap
+    // limit, the account gap limit, the protocol magic (mainnet), the number
+    // of accounts and the next address index of the first account
+    let gap_limit = 1 + 1 + 4 + 2 * 4 + 96;
+    let next_index = gap_limit + 4 + 4 + 1 + 4;
+    assert_eq!(
+        exported[next_index..next_index + 4],
+        DEFAULT_GAP_LIMIT.to_be_bytes()
+    );
+
+    let import = |forged: &[u8]| wallet::scheme::bip44::Wallet::<OldAddress>::import(forged);
+    let forge = |offset: usize, value: u32| {
+        let mut forged = exported.clone();
+        forged[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+        forged
+    };
+
+    assert!(import(&exported).is_ok());
+    // no address has been used, no other range has been derived
+    assert!(matches!(
+        import(&forge(next_index, 2 * DEFAULT_GAP_LIMIT)),
+        Err(PersistenceError::Invalid(_))
+    ));
+    assert!(matches!(
+        import(&forge(next_index, 0x7FFF_FFFF)),
+        Err(PersistenceError::Invalid(_))
+    ));
+    assert!(matches!(
+        import(&forge(gap_limit, u32::MAX)),
+        Err(PersistenceError::Invalid(_))
+    ));
+}
+
+#[test]
+fn sign_with_software_signer() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+    let mut yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+    let account = wallet.build_wallet().expect("recover account");
+    let signer = wallet
+        .build_software_signer()
+        .expect("recover the root key");
+
+    let mut state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+
+    assert!(yoroi.check_fragments(state.initial_contents()));
+
+    let utxo = yoroi
+        .utxos()
+        .utxos()
+        .find(|utxo| utxo.value >= Value(10_000))
+        .expect("a big enough utxo");
+    let key = yoroi.utxos().get_signing_key(utxo).unwrap();
+
+    let mk_builder = || {
+        let mut builder = TransactionBuilder::new(&settings, NoExtra);
+        builder.add_input_with_key(
+            Input::from_utxo(**utxo),
+            KeyId::Path(key.path().clone().coerce_unchecked()),
+            WitnessKind::OldUtxo,
+        );
+        let fee = builder.estimate_fee_with(0, 1);
+        builder.add_output(Output::from_address(
+            address.clone(),
+            utxo.value.checked_sub(fee).unwrap(),
+        ));
+        builder
+    };
+
+    assert!(matches!(
+        mk_builder().finalize_tx(()),
+        Err(FinalizeError::MissingSigner(0))
+    ));
+
+    let tx = mk_builder()
+        .finalize_tx_with_signer(&signer, ())
+        .expect("signed by the software signer");
+    let fragment = Fragment::Transaction(tx);
+
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("the transaction should be valid");
+}
+
+#[test]
+fn watch_only_from_account_public_keys() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+    let yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+    let account = wallet.build_wallet().expect("recover account");
+    let signer = wallet
+        .build_software_signer()
+        .expect("recover the root key");
+
+    let mut state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+
+    let mut watched =
+        WatchOnlyWallet::<OldAddress>::from_account_keys(yoroi.account_public_keys().cloned());
+    assert!(watched.check_fragments(state.initial_contents()));
+    assert_eq!(watched.unconfirmed_value(), Some(WALLET_VALUE));
+
+    let watched = WatchOnlyWallet::<OldAddress>::import(&watched.export())
+        .expect("import the exported watch-only wallet");
+    assert_eq!(watched.utxos().total_value(), WALLET_VALUE);
+
+    let mut unsigned = watched
+        .unsigned_transaction(
+            &settings,
+            NoExtra,
+            vec![Output::from_address(address.clone(), Value(10_000))],
+            DEFAULT_STRATEGIES,
+            address,
+        )
+        .expect("enough funds for the transaction");
+    assert!(unsigned
+        .inputs()
+        .iter()
+        .all(|input| input.witness_kind() == WitnessKind::OldUtxo));
+
+    unsigned.sign_with(&signer).expect("sign all the inputs");
+    let tx = unsigned.finalize(()).expect("all the inputs are signed");
+
+    state
+        .apply_fragments(&[Fragment::Transaction(tx).to_raw()])
+        .expect("the transaction should be valid");
+}
+
+#[test]
+fn import_forged_watch_only_address_range() {
+    let yoroi = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics")
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+    let watched =
+        WatchOnlyWallet::<OldAddress>::from_account_keys(yoroi.account_public_keys().cloned());
+    let exported = watched.export();
+
+    // after the version and the kind come the gap limit, the protocol magic
+    // (mainnet), the number of accounts, the path and the key of the first
+    // account and its next address index
+    let gap_limit = 1 + 1;
+    let next_index = gap_limit + 4 + 1 + 4 + 4 + 3 * 4 + 64;
+    assert_eq!(
+        exported[next_index..next_index + 4],
+        DEFAULT_GAP_LIMIT.to_be_bytes()
+    );
+
+    let import = |forged: &[u8]| WatchOnlyWallet::<OldAddress>::import(forged);
+    let forge = |offset: usize, value: u32| {
+        let mut forged = exported.clone();
+        forged[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+        forged
+    };
+
+    assert!(import(&exported).is_ok());
+    assert!(matches!(
+        import(&forge(next_index, 0x7FFF_FFFF)),
+        Err(PersistenceError::Invalid(_))
+    ));
+    assert!(matches!(
+        import(&forge(gap_limit, u32::MAX)),
+        Err(PersistenceError::Invalid(_))
+    ));
+}
+
+#[test]
+fn issue_receive_addresses_within_the_gap_limit() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+    let mut yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+
+    let state = State::new(BLOCK0);
+    assert!(yoroi.check_fragments(state.initial_contents()));
+
+    let used: Vec<OldAddress> = state
+        .initial_contents()
+        .filter_map(|fragment| match fragment {
+            Fragment::OldUtxoDeclaration(utxos) => Some(utxos.addrs.clone()),
+            _ => None,
+        })
+        .flatten()
+        .map(|(address, _)| address)
+        .collect();
+
+    let mut issued = Vec::new();
+    while let Ok(address) = yoroi.next_receive_address() {
+        assert!(!used.contains(&address), "a used address is issued again");
+        assert!(!issued.contains(&address), "an address is issued twice");
+        issued.push(address);
+    }
+    assert_eq!(issued.len(), 20);
+    assert!(matches!(
+        yoroi.next_receive_address(),
+        Err(AddressError::GapLimitReached(20))
+    ));
+
+    // the change addresses are on their own chain
+    assert!(yoroi.next_change_address().is_ok());
+
+    let mut imported = wallet::scheme::bip44::Wallet::<OldAddress>::import(&yoroi.export())
+        .expect("import the exported wallet");
+    assert!(imported.next_receive_address().is_err());
+}
+
+#[test]
+fn receive_funds_on_an_issued_address() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+    let mut yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+    let signer = wallet
+        .build_software_signer()
+        .expect("recover the root key");
+
+    let mut state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    assert!(yoroi.check_fragments(state.initial_contents()));
+
+    let mut bip44 = WatchOnlyWallet::<PublicKey<Ed25519>>::from_account_keys(
+        yoroi.account_public_keys().cloned(),
+    );
+    let address = bip44
+        .next_receive_address(settings.discrimination(), None)
+        .expect("an address within the gap limit");
+    let change = bip44
+        .next_change_address(settings.discrimination(), None)
+        .expect("an address within the gap limit");
+    assert_ne!(address, change);
+
+    let mut watched =
+        WatchOnlyWallet::<OldAddress>::from_account_keys(yoroi.account_public_keys().cloned());
+    assert!(watched.check_fragments(state.initial_contents()));
+    let mut unsigned = watched
+        .unsigned_transaction(
+            &settings,
+            NoExtra,
+            vec![Output::from_address(address.clone(), Value(10_000))],
+            DEFAULT_STRATEGIES,
+            change,
+        )
+        .expect("enough funds for the transaction");
+    unsigned.sign_with(&signer).expect("sign all the inputs");
+    let fragment = Fragment::Transaction(unsigned.finalize(()).unwrap());
+
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("the transaction should be valid");
+
+    assert!(bip44.check_fragment(&fragment.hash(), &fragment));
+    assert!(bip44.utxos().total_value() >= Value(10_000));
+    assert_ne!(
+        bip44
+            .next_receive_address(settings.discrimination(), None)
+            .unwrap(),
+        address
+    );
+}
+
+#[test]
+fn discover_accounts_and_dump_one_account() {
+    let wallet = RecoveryBuilder::new()
+        .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+        .expect("valid mnemonics");
+    let mut yoroi = wallet
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+    let account = wallet.build_wallet().expect("recover account");
+
+    let mut state = State::new(BLOCK0);
+    let settings = state.settings().expect("valid initial settings");
+    let address = account.account_id().address(settings.discrimination());
+    assert!(yoroi.check_fragments(state.initial_contents()));
+
+    // the first account is used, the next one is kept unused to discover
+    // the funds sent to it
+    let accounts: Vec<HardDerivation> = yoroi.accounts().collect();
+    assert!(accounts.len() >= 2);
+    let first = accounts[0];
+
+    let total = accounts
+        .iter()
+        .map(|account| yoroi.account_value(*account))
+        .fold(Value::zero(), |total, value| (total + value).unwrap());
+    assert_eq!(total, WALLET_VALUE);
+    assert_eq!(
+        yoroi.account_value(*accounts.last().unwrap()),
+        Value::zero()
+    );
+
+    let (fragment, _) = dump_icarus_account_utxo(&settings, &address, &mut yoroi, first)
+        .next()
+        .expect("the first account has funds to send");
+    state
+        .apply_fragments(&[fragment.to_raw()])
+        .expect("the dump fragments should be valid");
+
+    let imported = wallet::scheme::bip44::Wallet::<OldAddress>::import(&yoroi.export())
+        .expect("import the exported wallet");
+    assert_eq!(imported.accounts().collect::<Vec<_>>(), accounts);
+}
+
+#[test]
+fn discover_addresses_after_a_wide_gap() {
+    const WIDE_GAP: u32 = 30;
+
+    let builder = || {
+        RecoveryBuilder::new()
+            .mnemonics(&bip39::dictionary::ENGLISH, MNEMONICS)
+            .expect("valid mnemonics")
+    };
+
+    // issue the address 25 of the external chain, after 25 unused addresses
+    let mut issuer = builder()
+        .gap_limit(WIDE_GAP)
+        .build_yoroi()
+        .expect("recover an Icarus/Yoroi wallet");
+    let address = (0..=25)
+        .map(|_| issuer.next_receive_address().unwrap())
+        .last()
+        .unwrap();
+    let fragment = Fragment::OldUtxoDeclaration(UtxoDeclaration {
+        addrs: vec![(address, Value(10))],
+    });
+
+    let mut yoroi = builder()
+        .build_yoroi()
+        .expect("recover with the default gap");
+    assert_eq!(yoroi.gap_limit(), DEFAULT_GAP_LIMIT);
+    assert!(!yoroi.check_fragment(&fragment.hash(), &fragment));
+    assert_eq!(yoroi.discovery_report().used_paths().count(), 0);
+
+    let mut yoroi = builder()
+        .gap_limit(WIDE_GAP)
+        .build_yoroi()
+        .expect("recover with a wider gap");
+    assert!(yoroi.check_fragment(&fragment.hash(), &fragment));
+
+    let report = yoroi.discovery_report();
+    assert_eq!(report.gap_limit(), WIDE_GAP);
+    assert_eq!(report.used_paths().count(), 1);
+    let account = &report.accounts()[0];
+    assert!(account.is_used());
+    assert_eq!(
+        account.external().highest_used().map(|index| *index),
+        Some(25)
+    );
+    assert_eq!(account.internal().highest_used(), None);
+    assert!(!report.gap_limit_reached());
+
+    // the address was found after 25 unused addresses, as far as a gap limit
+    // of 26 allows
+    let mut yoroi = builder()
+        .gap_limit(26)
+        .build_yoroi()
+        .expect("recover with a gap of 26");
+    assert!(yoroi.check_fragment(&fragment.hash(), &fragment));
+    assert!(yoroi.discovery_report().gap_limit_reached());
+
+    let imported = wallet::scheme::bip44::Wallet::<OldAddress>::import(&yoroi.export())
+        .expect("import the exported wallet");
+    assert_eq!(imported.gap_limit(), 26);
+    assert!(imported.discovery_report().gap_limit_reached());
+
+    assert!(matches!(
+        builder().gap_limit(0).build_yoroi(),
+        Err(RecoveryError::InvalidGapLimit)
+    ));
+}